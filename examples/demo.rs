@@ -10,8 +10,9 @@ pub use titik::crossterm::{
 };
 
 use titik::{
-    Button, Callback, Checkbox, FlexBox, GroupBox, Image, Link, ListBox, Radio,
-    Renderer, Slider, TabBox, TextArea, TextInput, Widget,
+    Button, Callback, Checkbox, FlexBox, GroupBox, Image, Link, ListBox,
+    ProgressBar, RadioGroup, Renderer, Slider, Svg, TabBox, TextArea,
+    TextInput, Widget,
 };
 
 fn build_ui() -> Box<dyn Widget<()>> {
@@ -21,6 +22,8 @@ fn build_ui() -> Box<dyn Widget<()>> {
 
     let mut slider = Slider::new(0.5);
     slider.use_thick_track(true);
+    let mut progress_bar = ProgressBar::new(0.42);
+    progress_bar.show_percentage(true);
     let mut tab1 = TabBox::new();
     tab1.set_tab_labels(vec![
         "Tab1".into(),
@@ -38,8 +41,7 @@ fn build_ui() -> Box<dyn Widget<()>> {
 
     let cb1 = Checkbox::new("Checkbox1");
     let cb2 = Checkbox::new("Checkbox2");
-    let rb1 = Radio::new("Radio1");
-    let rb2 = Radio::new("Radio2");
+    let radio_group = RadioGroup::new(vec!["Radio1", "Radio2"]);
     let mut link1 = Link::new("https://github.com", "Github");
     link1.set_border(true);
 
@@ -57,8 +59,7 @@ fn build_ui() -> Box<dyn Widget<()>> {
 
     gb1.add_child(Box::new(cb1));
     gb1.add_child(Box::new(cb2));
-    gb1.add_child(Box::new(rb1));
-    gb1.add_child(Box::new(rb2));
+    gb1.add_child(Box::new(radio_group));
     gb1.add_child(Box::new(link1));
 
     let input1 = TextInput::new("Hello world!");
@@ -95,7 +96,10 @@ fn build_ui() -> Box<dyn Widget<()>> {
     btn2.set_rounded(true);
     btn2.set_id("btn2");
     let mut img: Image<()> =
-        Image::new(include_bytes!("../horse.jpg").to_vec());
+        Image::new(include_bytes!("../horse.jpg").to_vec())
+            .expect("must load the image");
+    let svg: Svg<()> = Svg::new(include_bytes!("bob.svg").to_vec())
+        .expect("must parse the svg");
 
     let mut btn1: Button<()> = Button::new("Button 1");
     btn1.set_id("btn1");
@@ -106,6 +110,7 @@ fn build_ui() -> Box<dyn Widget<()>> {
 
     root_node.add_child(Box::new(btn1));
     root_node.add_child(Box::new(slider));
+    root_node.add_child(Box::new(progress_bar));
     root_node.add_child(Box::new(btn2));
     tab1.add_child(Box::new(gb1));
     let mut row = FlexBox::new();
@@ -113,6 +118,7 @@ fn build_ui() -> Box<dyn Widget<()>> {
     row.set_expand_width(true);
     row.set_expand_height(false);
     row.add_child(Box::new(img));
+    row.add_child(Box::new(svg));
     root_node.add_child(Box::new(row));
     root_node.add_child(Box::new(tab1));
     //root_node.add_child(Box::new(gb1));
@@ -0,0 +1,186 @@
+//! Parse the text which contains ANSI escape sequences, such as the output
+//! of `cargo` or `git`, into a styled text.
+//! Only the SGR (Select Graphic Rendition) sequences are used for styling,
+//! the rest of the escape sequences are removed from the text.
+use crate::crossterm::style::{Attribute, Color};
+use crate::{Span, StyledText};
+
+/// the escape character which starts the escape sequences
+const ESC: char = '\u{1b}';
+/// the bell character which can terminate the OSC sequences
+const BEL: char = '\u{7}';
+
+/// the 16 colors in the order of their SGR codes, the first 8 are
+/// the normal colors and the next 8 are the bright colors
+const COLORS: [Color; 16] = [
+    Color::Black,
+    Color::DarkRed,
+    Color::DarkGreen,
+    Color::DarkYellow,
+    Color::DarkBlue,
+    Color::DarkMagenta,
+    Color::DarkCyan,
+    Color::Grey,
+    Color::DarkGrey,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+];
+
+/// parse the text with ANSI escape sequences into a styled text
+pub(crate) fn parse(s: &str) -> StyledText {
+    let mut styled = StyledText::new();
+    let mut style = Span::default();
+    let mut text = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ESC => match chars.next() {
+                // CSI: parameters followed by the final byte in the range @ to ~
+                Some('[') => {
+                    let mut params = String::new();
+                    for ch in chars.by_ref() {
+                        if ('@'..='~').contains(&ch) {
+                            if ch == 'm' {
+                                styled.push(style.with_text(&text));
+                                text.clear();
+                                apply_sgr(&mut style, &params);
+                            }
+                            break;
+                        }
+                        params.push(ch);
+                    }
+                }
+                // OSC: terminated by BEL or ESC \
+                Some(']') => {
+                    while let Some(ch) = chars.next() {
+                        if ch == BEL {
+                            break;
+                        }
+                        if ch == ESC && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // the other escape sequences are 2 characters long
+                _ => (),
+            },
+            '\r' => (),
+            _ => text.push(ch),
+        }
+    }
+    styled.push(style.with_text(&text));
+    styled
+}
+
+/// apply the SGR parameters such as `1;31` to the style
+fn apply_sgr(style: &mut Span, params: &str) {
+    let codes: Vec<u16> = params
+        .split([';', ':'])
+        .map(|code| code.parse().unwrap_or(0))
+        .collect();
+    let mut codes = codes.into_iter();
+    while let Some(code) = codes.next() {
+        match code {
+            0 => *style = Span::default(),
+            1 => style.attributes.set(Attribute::Bold),
+            2 => style.attributes.set(Attribute::Dim),
+            3 => style.attributes.set(Attribute::Italic),
+            4 => style.attributes.set(Attribute::Underlined),
+            7 => style.attributes.set(Attribute::Reverse),
+            9 => style.attributes.set(Attribute::CrossedOut),
+            22 => {
+                style.attributes.unset(Attribute::Bold);
+                style.attributes.unset(Attribute::Dim);
+            }
+            23 => style.attributes.unset(Attribute::Italic),
+            24 => style.attributes.unset(Attribute::Underlined),
+            27 => style.attributes.unset(Attribute::Reverse),
+            29 => style.attributes.unset(Attribute::CrossedOut),
+            30..=37 => {
+                style.foreground_color = Some(COLORS[code as usize - 30])
+            }
+            38 => style.foreground_color = extended_color(&mut codes),
+            39 => style.foreground_color = None,
+            40..=47 => {
+                style.background_color = Some(COLORS[code as usize - 40])
+            }
+            48 => style.background_color = extended_color(&mut codes),
+            49 => style.background_color = None,
+            90..=97 => {
+                style.foreground_color = Some(COLORS[code as usize - 90 + 8])
+            }
+            100..=107 => {
+                style.background_color = Some(COLORS[code as usize - 100 + 8])
+            }
+            _ => (),
+        }
+    }
+}
+
+/// the 256 color `5;n` or the true color `2;r;g;b` which follows 38 or 48
+fn extended_color(codes: &mut impl Iterator<Item = u16>) -> Option<Color> {
+    let mut next = || codes.next().map(|code| code.min(255) as u8);
+    match next()? {
+        5 => Some(Color::AnsiValue(next()?)),
+        2 => Some(Color::Rgb {
+            r: next()?,
+            g: next()?,
+            b: next()?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_and_attributes() {
+        let styled =
+            parse("\u{1b}[1;31merror\u{1b}[0m: \u{1b}[4mfile\u{1b}[24m.rs");
+        assert_eq!(styled.as_str(), "error: file.rs");
+        let spans = styled.spans();
+        assert_eq!(spans[0].text, "error");
+        assert_eq!(spans[0].foreground_color, Some(Color::DarkRed));
+        assert!(spans[0].attributes.has(Attribute::Bold));
+        assert_eq!(spans[1].text, ": ");
+        assert_eq!(spans[1].foreground_color, None);
+        assert!(spans[2].attributes.has(Attribute::Underlined));
+        assert!(!spans[3].attributes.has(Attribute::Underlined));
+    }
+
+    #[test]
+    fn extended_colors() {
+        let styled =
+            parse("\u{1b}[38;5;208ma\u{1b}[48;2;10;20;30;92mb\u{1b}[39;49mc");
+        let spans = styled.spans();
+        assert_eq!(spans[0].foreground_color, Some(Color::AnsiValue(208)));
+        assert_eq!(
+            spans[1].background_color,
+            Some(Color::Rgb {
+                r: 10,
+                g: 20,
+                b: 30
+            })
+        );
+        assert_eq!(spans[1].foreground_color, Some(Color::Green));
+        assert_eq!(spans[2].foreground_color, None);
+        assert_eq!(spans[2].background_color, None);
+    }
+
+    #[test]
+    fn other_sequences_are_removed() {
+        let styled = parse(
+            "\u{1b}]0;title\u{7}\u{1b}[2Kdone\r\u{1b}]8;;http://x\u{1b}\\!",
+        );
+        assert_eq!(styled.as_str(), "done!");
+        assert_eq!(styled.spans().len(), 1);
+    }
+}
@@ -5,11 +5,11 @@ use crate::crossterm::{
         SetBackgroundColor, SetForegroundColor,
     },
 };
-use crate::symbol;
+use crate::{symbol, unicode, widget::delete_kitty, StyledText};
 use ito_canvas::unicode_canvas::Canvas;
 use std::io::Stdout;
+use std::rc::Rc;
 use std::{fmt, io::Write};
-use unicode_width::UnicodeWidthStr;
 
 /// Cell contains the attributes of the char used in the buffer.
 /// This information is needed when rendering each cell to the terminal
@@ -30,6 +30,18 @@ pub struct Cell {
 #[derive(PartialEq, Debug)]
 pub struct Buffer {
     pub(crate) cells: Vec<Vec<Cell>>,
+    /// the terminal graphics drawn into this buffer
+    pub(crate) graphics: Vec<Graphics>,
+}
+
+/// The escape sequence of the terminal graphics, written at x,y location
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Graphics {
+    pub(crate) x: usize,
+    pub(crate) y: usize,
+    /// the id of the kitty image, which is deleted when it is no longer drawn
+    pub(crate) image_id: Option<u32>,
+    pub(crate) data: Rc<Vec<u8>>,
 }
 
 impl Cell {
@@ -45,9 +57,9 @@ impl Cell {
     }
 
     /// returns the unicode width of the cell.
-    /// Some characters are wide such as CJK
+    /// Some characters are wide such as CJK and emoji
     pub fn unicode_width(&self) -> usize {
-        UnicodeWidthStr::width(&*self.symbol)
+        unicode::str_width(&self.symbol)
     }
 
     /// creates an empty Cell
@@ -99,14 +111,18 @@ impl Buffer {
             .into_iter()
             .map(|_| (0..width).into_iter().map(|_| Cell::empty()).collect())
             .collect();
-        Buffer { cells }
+        Buffer {
+            cells,
+            graphics: vec![],
+        }
     }
 
     /// reset the content of the buffer to empty
     pub fn reset(&mut self) {
         self.cells.iter_mut().for_each(|line| {
             line.iter_mut().for_each(|cell| *cell = Cell::empty())
-        })
+        });
+        self.graphics.clear();
     }
 
     /// set the character of this location with symbol
@@ -114,21 +130,117 @@ impl Buffer {
         self.set_cell(x, y, Cell::new(symbol));
     }
 
-    /// enumerate the characters in the string and set the cells horizontally incrementing on the x
-    /// component
-    pub fn write_str<S: ToString>(&mut self, x: usize, y: usize, s: S) {
-        for (i, ch) in s.to_string().chars().enumerate() {
-            self.set_cell(x + i, y, Cell::new(ch));
-        }
+    /// write the string horizontally starting at this location.
+    /// Each grapheme cluster is set in one cell and the x is advanced
+    /// by the width of the grapheme, so wide characters occupies 2 cells.
+    /// Returns the number of cells used.
+    pub fn write_str<S: ToString>(
+        &mut self,
+        x: usize,
+        y: usize,
+        s: S,
+    ) -> usize {
+        self.write_graphemes(x, y, &s.to_string(), |_| ())
     }
 
     /// write string as bold
-    pub fn write_bold_str<S: ToString>(&mut self, x: usize, y: usize, s: S) {
-        for (i, ch) in s.to_string().chars().enumerate() {
-            let mut cell = Cell::new(ch);
-            cell.bold();
-            self.set_cell(x + i, y, cell);
+    pub fn write_bold_str<S: ToString>(
+        &mut self,
+        x: usize,
+        y: usize,
+        s: S,
+    ) -> usize {
+        self.write_graphemes(x, y, &s.to_string(), Cell::bold)
+    }
+
+    /// write the string which is truncated to fit in `max_width` cells,
+    /// an ellipsis is added at the end when the string is truncated.
+    /// Returns the number of cells used.
+    pub fn write_str_truncated<S: ToString>(
+        &mut self,
+        x: usize,
+        y: usize,
+        s: S,
+        max_width: usize,
+    ) -> usize {
+        let truncated = unicode::truncate(&s.to_string(), max_width);
+        self.write_graphemes(x, y, &truncated, |_| ())
+    }
+
+    /// write the styled text horizontally starting at this location,
+    /// each cell gets the colors and attributes of its span.
+    /// Returns the number of cells used.
+    pub fn write_styled(
+        &mut self,
+        x: usize,
+        y: usize,
+        text: &StyledText,
+    ) -> usize {
+        self.write_styled_with(x, y, text, |_| ())
+    }
+
+    /// write the styled text as bold
+    pub fn write_bold_styled(
+        &mut self,
+        x: usize,
+        y: usize,
+        text: &StyledText,
+    ) -> usize {
+        self.write_styled_with(x, y, text, Cell::bold)
+    }
+
+    /// write the styled text which is truncated to fit in `max_width` cells,
+    /// an ellipsis is added at the end when the text is truncated.
+    /// Returns the number of cells used.
+    pub fn write_styled_truncated(
+        &mut self,
+        x: usize,
+        y: usize,
+        text: &StyledText,
+        max_width: usize,
+    ) -> usize {
+        self.write_styled(x, y, &text.truncate(max_width))
+    }
+
+    /// write each span of the styled text, the cells can be
+    /// styled further with `f`
+    fn write_styled_with<F>(
+        &mut self,
+        x: usize,
+        y: usize,
+        text: &StyledText,
+        f: F,
+    ) -> usize
+    where
+        F: Fn(&mut Cell),
+    {
+        let mut column = x;
+        for span in text.spans() {
+            column += self.write_graphemes(column, y, &span.text, |cell| {
+                span.style_cell(cell);
+                f(cell);
+            });
+        }
+        column - x
+    }
+
+    /// set each grapheme of the string into the cells, the cells can be
+    /// styled with `f`. Zero width graphemes such as control characters are skipped.
+    fn write_graphemes<F>(&mut self, x: usize, y: usize, s: &str, f: F) -> usize
+    where
+        F: Fn(&mut Cell),
+    {
+        let mut column = x;
+        for (grapheme, width) in unicode::graphemes(s) {
+            if width == 0 {
+                continue;
+            }
+            let mut cell = Cell::new(grapheme);
+            f(&mut cell);
+            self.set_cell(column, y, cell);
+            column += width;
         }
+        column - x
     }
 
     /// get the characters from the drawing canvas and
@@ -154,6 +266,33 @@ impl Buffer {
         }
     }
 
+    /// set the escape sequence of the terminal graphics at x,y location
+    /// which covers the width and height cells. The cells are reserved,
+    /// so they are not written when rendering and the graphics is kept.
+    /// Kitty images are drawn on their own layer, the `image_id` is used
+    /// to delete the image when it is no longer drawn.
+    pub fn set_graphics(
+        &mut self,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+        image_id: Option<u32>,
+        data: Rc<Vec<u8>>,
+    ) {
+        for j in 0..height {
+            for i in 0..width {
+                self.set_symbol(x + i, y + j, '\0');
+            }
+        }
+        self.graphics.push(Graphics {
+            x,
+            y,
+            image_id,
+            data,
+        });
+    }
+
     /// get the diff of 2 buffers
     pub fn diff<'a>(&self, new: &'a Self) -> Vec<(usize, usize, &'a Cell)> {
         let mut patches = vec![];
@@ -193,6 +332,37 @@ impl Buffer {
         }
         Ok(())
     }
+
+    /// writes the terminal graphics which are not in the last written graphics,
+    /// the unchanged graphics are still displayed since their cells are reserved.
+    /// The kitty images which are no longer drawn are deleted.
+    pub(crate) fn render_graphics<W: Write>(
+        &self,
+        w: &mut W,
+        last: &[Graphics],
+    ) -> crossterm::Result<()> {
+        for graphics in last.iter() {
+            if let Some(image_id) = graphics.image_id {
+                let is_drawn = self
+                    .graphics
+                    .iter()
+                    .any(|current| current.image_id == Some(image_id));
+                if !is_drawn {
+                    w.write_all(delete_kitty(image_id).as_bytes())?;
+                }
+            }
+        }
+        for graphics in self.graphics.iter() {
+            if !last.contains(graphics) {
+                queue!(
+                    w,
+                    cursor::MoveTo(graphics.x as u16, graphics.y as u16)
+                )?;
+                w.write_all(&graphics.data)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for Cell {
@@ -277,4 +447,26 @@ mod test {
         assert_eq!(Cell::new(' '), buf2.cells[1][1]);
         assert_eq!(Cell::new(' '), buf2.cells[1][2]);
     }
+
+    #[test]
+    fn write_wide_and_combined_characters() {
+        let mut buf = Buffer::new(10, 1);
+        let used = buf.write_str(0, 0, "a文e\u{301}😀");
+        assert_eq!(used, 6);
+        assert_eq!(buf.cells[0][1].symbol, "文");
+        assert!(buf.cells[0][2].is_filler());
+        assert_eq!(buf.cells[0][3].symbol, "e\u{301}");
+        assert_eq!(buf.cells[0][4].symbol, "😀");
+        assert!(buf.cells[0][5].is_filler());
+        assert!(buf.cells[0][6].is_blank());
+    }
+
+    #[test]
+    fn write_truncated() {
+        let mut buf = Buffer::new(10, 1);
+        let used = buf.write_str_truncated(0, 0, "文字文字", 6);
+        assert_eq!(used, 5);
+        assert_eq!(buf.cells[0][4].symbol, "…");
+        assert!(buf.cells[0][5].is_blank());
+    }
 }
@@ -0,0 +1,79 @@
+//! An internal clipboard which is shared by the text widgets.
+//! The copied text can also be sent to the clipboard of the system
+//! with the OSC 52 escape sequence, when the terminal supports it.
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+static CLIPBOARD: Mutex<String> = Mutex::new(String::new());
+
+const BASE64_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// put the text into the internal clipboard
+pub(crate) fn set(text: &str) {
+    *CLIPBOARD.lock().expect("must lock the clipboard") = text.to_string();
+}
+
+/// return the text of the internal clipboard
+pub(crate) fn get() -> String {
+    CLIPBOARD.lock().expect("must lock the clipboard").clone()
+}
+
+/// put the text into the clipboard of the system with the OSC 52 escape sequence
+pub(crate) fn set_osc52(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "{}", osc52(text))?;
+    stdout.flush()
+}
+
+/// the OSC 52 escape sequence which sets the clipboard to this text
+fn osc52(text: &str) -> String {
+    format!("\u{1b}]52;c;{}\u{7}", base64(text.as_bytes()))
+}
+
+/// the standard base64 encoding, with padding
+pub(crate) fn base64(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let indexes = [
+            b[0] >> 2,
+            (b[0] & 0x03) << 4 | b[1] >> 4,
+            (b[1] & 0x0f) << 2 | b[2] >> 6,
+            b[2] & 0x3f,
+        ];
+        // a chunk of n bytes is encoded into n + 1 characters
+        for (i, index) in indexes.iter().enumerate() {
+            if i <= chunk.len() {
+                encoded.push(BASE64_CHARS[*index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osc52_is_base64_encoded() {
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(osc52("hi"), "\u{1b}]52;c;aGk=\u{7}");
+    }
+
+    #[test]
+    fn base64_encoding() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"foob"), "Zm9vYg==");
+        assert_eq!(base64(&[255, 0, 0]), "/wAA");
+    }
+}
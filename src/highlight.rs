@@ -0,0 +1,437 @@
+//! Syntax highlighting of the lines in a `TextArea`.
+use crate::crossterm::style::{Attribute, Color};
+use crate::text_buffer::{AreaBuffer, LineChange};
+use crate::{Span, StyledText};
+use std::{cell::RefCell, fmt};
+
+/// the state at the start of the text, and outside of any multi line construct
+const NORMAL: usize = 0;
+/// the state inside a block comment
+const IN_BLOCK_COMMENT: usize = 1;
+
+const SQL_KEYWORDS: &str = "select from where insert into values update set \
+    delete create table drop alter index join left right inner outer on as \
+    and or not null is in like order group by having limit offset distinct \
+    union primary key foreign references default true false";
+
+/// Highlight the syntax of a text one line at a time.
+///
+/// The state is carried from the end of a line to the start of the next line,
+/// this is used for the constructs which span multiple lines such as block comments.
+/// The state of the first line is 0.
+pub trait Highlighter {
+    /// return the styled line and the state at the end of this line,
+    /// `state` is the state at the end of the previous line
+    fn highlight_line(&self, line: &str, state: usize) -> (StyledText, usize);
+
+    /// highlight a range of lines which starts with this state
+    fn highlight_lines(
+        &self,
+        lines: &[&str],
+        state: usize,
+    ) -> (Vec<StyledText>, usize) {
+        let mut state = state;
+        let mut styled_lines = Vec::with_capacity(lines.len());
+        for line in lines {
+            let (styled, end_state) = self.highlight_line(line, state);
+            styled_lines.push(styled);
+            state = end_state;
+        }
+        (styled_lines, state)
+    }
+}
+
+/// A simple highlighter for the keywords, strings, numbers and comments.
+///```ignore
+/// let mut text_area = TextArea::new("SELECT * FROM users; -- all");
+/// text_area.set_highlighter(KeywordHighlighter::sql());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct KeywordHighlighter {
+    keywords: Vec<String>,
+    case_sensitive: bool,
+    line_comment: Option<String>,
+    block_comment: Option<(String, String)>,
+    quotes: Vec<char>,
+}
+
+impl KeywordHighlighter {
+    /// create a highlighter with no keywords and no comments
+    pub fn new() -> Self {
+        KeywordHighlighter::default()
+    }
+
+    /// a highlighter for SQL, the keywords are matched regardless of the case
+    pub fn sql() -> Self {
+        let mut highlighter = KeywordHighlighter::new();
+        let keywords: Vec<&str> = SQL_KEYWORDS.split_whitespace().collect();
+        highlighter.set_keywords(&keywords);
+        highlighter.set_case_sensitive(false);
+        highlighter.set_line_comment("--");
+        highlighter.set_block_comment("/*", "*/");
+        highlighter.set_quotes(&['\'', '"']);
+        highlighter
+    }
+
+    /// a highlighter for TOML
+    pub fn toml() -> Self {
+        let mut highlighter = KeywordHighlighter::new();
+        highlighter.set_keywords(&["true", "false", "inf", "nan"]);
+        highlighter.set_case_sensitive(true);
+        highlighter.set_line_comment("#");
+        highlighter.set_quotes(&['\'', '"']);
+        highlighter
+    }
+
+    /// set the words which are highlighted as keywords
+    pub fn set_keywords(&mut self, keywords: &[&str]) {
+        self.keywords = keywords.iter().map(ToString::to_string).collect();
+    }
+
+    /// set whether the case of the keywords has to match
+    pub fn set_case_sensitive(&mut self, case_sensitive: bool) {
+        self.case_sensitive = case_sensitive;
+    }
+
+    /// set the text which starts a comment up to the end of the line
+    pub fn set_line_comment(&mut self, start: &str) {
+        self.line_comment = Some(start.to_string());
+    }
+
+    /// set the texts which start and end a comment, which can span multiple lines
+    pub fn set_block_comment(&mut self, start: &str, end: &str) {
+        self.block_comment = Some((start.to_string(), end.to_string()));
+    }
+
+    /// set the characters which quote the strings
+    pub fn set_quotes(&mut self, quotes: &[char]) {
+        self.quotes = quotes.to_vec();
+    }
+
+    fn is_line_comment(&self, rest: &str) -> bool {
+        self.line_comment
+            .as_ref()
+            .map(|start| rest.starts_with(start.as_str()))
+            .unwrap_or(false)
+    }
+
+    fn is_keyword(&self, word: &str) -> bool {
+        self.keywords.iter().any(|keyword| {
+            if self.case_sensitive {
+                keyword == word
+            } else {
+                keyword.eq_ignore_ascii_case(word)
+            }
+        })
+    }
+}
+
+fn styled_span(text: &str, color: Color, bold: bool) -> Span {
+    let mut span = Span::new(text);
+    span.color(color);
+    if bold {
+        span.attributes(vec![Attribute::Bold]);
+    }
+    span
+}
+
+/// the byte index where the string which starts with the quote at `start` ends,
+/// the quote can be escaped with a backslash
+fn string_end(line: &str, start: usize, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, ch) in line[start + quote.len_utf8()..].char_indices() {
+        if escaped {
+            escaped = false;
+        } else if ch == '\\' {
+            escaped = true;
+        } else if ch == quote {
+            return start + quote.len_utf8() + i + ch.len_utf8();
+        }
+    }
+    line.len()
+}
+
+/// the byte index where the word or number which starts at `start` ends
+fn word_end(line: &str, start: usize) -> usize {
+    line[start..]
+        .char_indices()
+        .find(|(_, ch)| !(ch.is_alphanumeric() || *ch == '_' || *ch == '.'))
+        .map(|(i, _)| start + i)
+        .unwrap_or_else(|| line.len())
+}
+
+impl Highlighter for KeywordHighlighter {
+    fn highlight_line(&self, line: &str, state: usize) -> (StyledText, usize) {
+        let mut styled = StyledText::new();
+        let mut state = state;
+        let mut plain_start = 0;
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            let (end, span) = if state == IN_BLOCK_COMMENT {
+                let (_, comment_end) =
+                    self.block_comment.as_ref().expect("must have a block");
+                let end = match rest.find(comment_end.as_str()) {
+                    Some(found) => {
+                        state = NORMAL;
+                        i + found + comment_end.len()
+                    }
+                    None => line.len(),
+                };
+                (
+                    end,
+                    Some(styled_span(&line[i..end], Color::DarkGrey, false)),
+                )
+            } else if self.is_line_comment(rest) {
+                (line.len(), Some(styled_span(rest, Color::DarkGrey, false)))
+            } else if let Some((start, _)) = self
+                .block_comment
+                .as_ref()
+                .filter(|(start, _)| rest.starts_with(start.as_str()))
+            {
+                // the rest of the comment is styled in the next round
+                state = IN_BLOCK_COMMENT;
+                let end = i + start.len();
+                (
+                    end,
+                    Some(styled_span(&line[i..end], Color::DarkGrey, false)),
+                )
+            } else {
+                let ch = rest.chars().next().expect("must have a char");
+                if self.quotes.contains(&ch) {
+                    let end = string_end(line, i, ch);
+                    (end, Some(styled_span(&line[i..end], Color::Green, false)))
+                } else if ch.is_ascii_digit() {
+                    let end = word_end(line, i);
+                    (
+                        end,
+                        Some(styled_span(&line[i..end], Color::Magenta, false)),
+                    )
+                } else if ch.is_alphabetic() || ch == '_' {
+                    let end = word_end(line, i);
+                    let word = &line[i..end];
+                    if self.is_keyword(word) {
+                        (end, Some(styled_span(word, Color::Blue, true)))
+                    } else {
+                        (end, None)
+                    }
+                } else {
+                    (i + ch.len_utf8(), None)
+                }
+            };
+            if let Some(span) = span {
+                styled.push_str(&line[plain_start..i]);
+                styled.push(span);
+                plain_start = end;
+            }
+            i = end;
+        }
+        styled.push_str(&line[plain_start..]);
+        (styled, state)
+    }
+}
+
+/// a highlighted line and the states at the start and at the end of it
+struct CachedLine {
+    styled: StyledText,
+    start_state: usize,
+    end_state: usize,
+}
+
+/// The highlighter of a `TextArea` and the lines it has highlighted.
+/// The lines are highlighted only when they are drawn, and the lines which
+/// are not edited are highlighted again only when their starting state changes.
+pub(crate) struct HighlightCache {
+    highlighter: Box<dyn Highlighter>,
+    lines: RefCell<CachedLines>,
+}
+
+struct CachedLines {
+    /// the lines which are edited have no highlight yet
+    lines: Vec<Option<CachedLine>>,
+    /// the lines before this are highlighted with the right starting state
+    valid_until: usize,
+}
+
+impl HighlightCache {
+    pub fn new(highlighter: Box<dyn Highlighter>, line_count: usize) -> Self {
+        let cache = HighlightCache {
+            highlighter,
+            lines: RefCell::new(CachedLines {
+                lines: vec![],
+                valid_until: 0,
+            }),
+        };
+        cache.reset(line_count);
+        cache
+    }
+
+    /// drop all the highlighted lines, such as when the whole content is replaced
+    pub fn reset(&self, line_count: usize) {
+        let mut cache = self.lines.borrow_mut();
+        cache.lines = (0..line_count).map(|_| None).collect();
+        cache.valid_until = 0;
+    }
+
+    /// the edited lines are highlighted again, the lines after it are
+    /// highlighted again only when the state at the end of the edit is changed
+    pub fn apply(&mut self, change: LineChange) {
+        let cache = self.lines.get_mut();
+        let start = change.line.min(cache.lines.len());
+        let end = (change.line + change.removed).min(cache.lines.len());
+        cache
+            .lines
+            .splice(start..end, (0..change.inserted).map(|_| None));
+        cache.valid_until = cache.valid_until.min(start);
+    }
+
+    /// return the highlighted line, the lines before it which are
+    /// not yet highlighted are highlighted first
+    pub fn line(&self, area_buffer: &AreaBuffer, y: usize) -> StyledText {
+        let mut cache = self.lines.borrow_mut();
+        if y >= cache.lines.len() {
+            return StyledText::from(area_buffer.line(y).as_ref());
+        }
+        while cache.valid_until <= y {
+            let j = cache.valid_until;
+            let state = match j.checked_sub(1) {
+                Some(prev) => cache.lines[prev]
+                    .as_ref()
+                    .map(|line| line.end_state)
+                    .unwrap_or(NORMAL),
+                None => NORMAL,
+            };
+            match &cache.lines[j] {
+                // the rest of the lines up to the next edited line are still valid
+                Some(line) if line.start_state == state => {
+                    cache.valid_until = cache.lines[j..]
+                        .iter()
+                        .position(Option::is_none)
+                        .map(|found| j + found)
+                        .unwrap_or_else(|| cache.lines.len());
+                }
+                _ => {
+                    let (styled, end_state) = self
+                        .highlighter
+                        .highlight_line(&area_buffer.line(j), state);
+                    cache.lines[j] = Some(CachedLine {
+                        styled,
+                        start_state: state,
+                        end_state,
+                    });
+                    cache.valid_until = j + 1;
+                }
+            }
+        }
+        cache.lines[y]
+            .as_ref()
+            .map(|line| line.styled.clone())
+            .expect("must be highlighted")
+    }
+}
+
+impl fmt::Debug for HighlightCache {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HighlightCache")
+            .field("valid_until", &self.lines.borrow().valid_until)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(styled: &StyledText) -> Vec<(&str, Option<Color>)> {
+        styled
+            .spans()
+            .iter()
+            .map(|span| (span.text.as_str(), span.foreground_color))
+            .collect()
+    }
+
+    #[test]
+    fn sql_keywords_strings_and_comments() {
+        let sql = KeywordHighlighter::sql();
+        let (styled, state) = sql
+            .highlight_line("Select name from t where id = 'x' -- c", NORMAL);
+        assert_eq!(styled.as_str(), "Select name from t where id = 'x' -- c");
+        assert_eq!(
+            texts(&styled),
+            vec![
+                ("Select", Some(Color::Blue)),
+                (" name ", None),
+                ("from", Some(Color::Blue)),
+                (" t ", None),
+                ("where", Some(Color::Blue)),
+                (" id = ", None),
+                ("'x'", Some(Color::Green)),
+                (" ", None),
+                ("-- c", Some(Color::DarkGrey)),
+            ]
+        );
+        assert_eq!(state, NORMAL);
+    }
+
+    #[test]
+    fn block_comment_spans_lines() {
+        let sql = KeywordHighlighter::sql();
+        let (styled, state) =
+            sql.highlight_lines(&["1 /* a", "select", "*/ null"], NORMAL);
+        assert_eq!(
+            texts(&styled[0]),
+            vec![
+                ("1", Some(Color::Magenta)),
+                (" ", None),
+                ("/*", Some(Color::DarkGrey)),
+                (" a", Some(Color::DarkGrey)),
+            ]
+        );
+        assert_eq!(texts(&styled[1]), vec![("select", Some(Color::DarkGrey))]);
+        assert_eq!(
+            texts(&styled[2]),
+            vec![
+                ("*/", Some(Color::DarkGrey)),
+                (" ", None),
+                ("null", Some(Color::Blue)),
+            ]
+        );
+        assert_eq!(state, NORMAL);
+    }
+
+    /// count the lines which are highlighted
+    struct Counting(std::rc::Rc<std::cell::Cell<usize>>);
+
+    impl Highlighter for Counting {
+        fn highlight_line(
+            &self,
+            line: &str,
+            state: usize,
+        ) -> (StyledText, usize) {
+            self.0.set(self.0.get() + 1);
+            KeywordHighlighter::toml().highlight_line(line, state)
+        }
+    }
+
+    #[test]
+    fn only_changed_lines_are_highlighted_again() {
+        let mut area_buffer =
+            AreaBuffer::from("a = 1\n# b\nc = true".to_string());
+        let count = std::rc::Rc::new(std::cell::Cell::new(0));
+        let mut cache =
+            HighlightCache::new(Box::new(Counting(count.clone())), 3);
+        cache.line(&area_buffer, 2);
+        assert_eq!(count.get(), 3);
+
+        area_buffer.set_cursor_loc(0, 1);
+        area_buffer.insert_str("x");
+        for change in area_buffer.take_line_changes() {
+            cache.apply(change);
+        }
+        let styled = cache.line(&area_buffer, 2);
+        assert_eq!(styled.as_str(), "c = true");
+        // only the edited line is highlighted again
+        assert_eq!(count.get(), 4);
+        assert_eq!(cache.line(&area_buffer, 1).as_str(), "x# b");
+    }
+}
@@ -51,20 +51,28 @@ pub use crossterm_new as crossterm;
 
 pub use event::Event;
 pub use expanse;
+pub use highlight::{Highlighter, KeywordHighlighter};
+pub use image;
 pub use mt_dom;
 pub use renderer::{Dispatch, Renderer};
+pub use styled_text::{Span, StyledText};
 pub use value::Value;
 pub use widget::*;
 
+mod ansi;
 mod buffer;
 mod callback;
+mod clipboard;
 mod cmd;
 pub mod command;
 pub mod event;
 mod find_node;
+mod highlight;
 pub mod renderer;
+mod styled_text;
 #[allow(unused)]
 mod symbol;
 mod text_buffer;
+mod unicode;
 mod value;
 mod widget;
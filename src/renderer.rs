@@ -2,7 +2,7 @@
 //! This has the event loop which calculates and process the events to the target widget
 
 use crate::Event;
-use crate::{command, find_node, Buffer, Widget};
+use crate::{buffer::Graphics, command, find_node, Buffer, Widget};
 
 use crate::crossterm::{
     event::{self, KeyCode, KeyModifiers},
@@ -12,6 +12,7 @@ use crate::crossterm::{
 use expanse::{geometry::Size, number::Number};
 use std::io::Stdout;
 use std::io::Write;
+use std::time::{Duration, Instant};
 
 /// A Dispatch trait which the implementing APP will update
 /// its own state based on the supplied msg.
@@ -28,6 +29,12 @@ pub struct Renderer<'a, MSG> {
     root_node: &'a mut dyn Widget<MSG>,
     terminal_size: (u16, u16),
     focused_widget_idx: Option<usize>,
+    /// when set, the widgets are ticked at this interval when there are no events
+    tick_rate: Option<Duration>,
+    /// the time the widgets are last ticked
+    last_tick: Instant,
+    /// the terminal graphics written in the last draw
+    graphics: Vec<Graphics>,
 }
 
 impl<'a, MSG> Renderer<'a, MSG> {
@@ -50,6 +57,38 @@ impl<'a, MSG> Renderer<'a, MSG> {
             root_node,
             terminal_size: (width, height),
             focused_widget_idx: None,
+            tick_rate: None,
+            last_tick: Instant::now(),
+            graphics: vec![],
+        }
+    }
+
+    /// set the interval in which the widgets are ticked, this is needed
+    /// for widgets that are animated or updated outside of the events
+    pub fn set_tick_rate(&mut self, tick_rate: Duration) {
+        self.tick_rate = Some(tick_rate);
+    }
+
+    /// wait for the next event, returns None when the tick rate has elapsed
+    /// without any event
+    fn next_event(&self) -> Result<Option<event::Event>> {
+        if let Some(tick_rate) = self.tick_rate {
+            let timeout = tick_rate.saturating_sub(self.last_tick.elapsed());
+            if !event::poll(timeout)? {
+                return Ok(None);
+            }
+        }
+        event::read().map(Some)
+    }
+
+    /// tick the widgets when the tick rate has elapsed since the last tick,
+    /// regardless of the events received in between
+    fn tick_when_elapsed(&mut self) {
+        if let Some(tick_rate) = self.tick_rate {
+            if self.last_tick.elapsed() >= tick_rate {
+                self.root_node.tick_widget();
+                self.last_tick = Instant::now();
+            }
         }
     }
 
@@ -99,12 +138,16 @@ impl<'a, MSG> Renderer<'a, MSG> {
             let cmds = self.root_node.draw_widget(&mut buf)?;
             buf.render(&mut self.write)?;
 
+            // the graphics are written before the commands,
+            // so it won't move the cursor shown by the widgets
+            buf.render_graphics(&mut self.write, &self.graphics)?;
+            self.graphics = buf.graphics;
             for cmd in cmds.iter() {
                 cmd.execute(&mut self.write)?;
             }
             self.write.flush()?;
 
-            if let Ok(c_event) = event::read() {
+            if let Ok(Some(c_event)) = self.next_event() {
                 if !Self::is_mouse_move(c_event) {
                     let event = Event::from_crossterm(c_event);
                     let should_break = self.process_event(&event);
@@ -135,6 +178,9 @@ impl<'a, MSG> Renderer<'a, MSG> {
                     }
                 }
             }
+            // events such as the ignored mouse moves
+            // must not hold back the animation of the widgets
+            self.tick_when_elapsed();
         }
         command::finalize(&mut self.write)?;
         Ok(())
@@ -149,7 +195,15 @@ impl<'a, MSG> Renderer<'a, MSG> {
                 //  - CTRL-q
                 //  - CTRL-d
                 //  - CTRL-z
-                if key_event.modifiers.contains(KeyModifiers::CONTROL) {
+                // unless the focused widget uses the key combination
+                let is_captured = self
+                    .focused_widget_idx
+                    .and_then(|idx| find_node::find_widget(self.root_node, idx))
+                    .map(|focused| focused.captures_key(key_event))
+                    .unwrap_or(false);
+                if key_event.modifiers.contains(KeyModifiers::CONTROL)
+                    && !is_captured
+                {
                     match key_event.code {
                         KeyCode::Char(c) => match c {
                             'c' | 'q' | 'd' | 'z' => {
@@ -159,16 +213,15 @@ impl<'a, MSG> Renderer<'a, MSG> {
                         },
                         _ => (),
                     }
-                } else {
-                    // send the keypresses to the focused widget
-                    if let Some(idx) = self.focused_widget_idx.as_ref() {
-                        let active_widget: Option<&mut dyn Widget<MSG>> =
-                            find_node::find_widget_mut(self.root_node, *idx);
-                        if let Some(focused_widget) = active_widget {
-                            let msgs =
-                                focused_widget.process_event(event.clone());
-                            self.dispatch_msg(msgs);
-                        }
+                }
+                // send the keypresses to the focused widget,
+                // including the other key combinations with CONTROL
+                if let Some(idx) = self.focused_widget_idx.as_ref() {
+                    let active_widget: Option<&mut dyn Widget<MSG>> =
+                        find_node::find_widget_mut(self.root_node, *idx);
+                    if let Some(focused_widget) = active_widget {
+                        let msgs = focused_widget.process_event(event.clone());
+                        self.dispatch_msg(msgs);
                     }
                 }
             }
@@ -190,6 +243,8 @@ impl<'a, MSG> Renderer<'a, MSG> {
             }
             Event::Resize(width, height) => {
                 self.terminal_size = (*width, *height);
+                // the terminal may have cleared the graphics
+                self.graphics.clear();
                 self.recompute_layout();
             }
             _ => (),
@@ -0,0 +1,463 @@
+//! Text which is made of spans, where each of the span has its own
+//! colors and attributes.
+use crate::buffer::Cell;
+use crate::crossterm::style::{Attribute, Attributes, Color};
+use crate::{ansi, symbol, unicode};
+use std::{fmt, ops::Range};
+
+/// A run of text with the same colors and attributes
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Span {
+    /// the text of this span
+    pub text: String,
+    /// The foreground color.
+    pub foreground_color: Option<Color>,
+    /// The background color.
+    pub background_color: Option<Color>,
+    /// List of attributes.
+    pub attributes: Attributes,
+}
+
+/// A text made of styled spans, this can be displayed in the widgets
+/// in place of a plain string.
+///
+/// It can be built from the spans or parsed from a markup
+///```ignore
+/// StyledText::from_markup("[b]bold[/b] [red]error[/red] [bg=blue]info[/]");
+/// ```
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct StyledText {
+    /// the text of all the spans
+    text: String,
+    spans: Vec<Span>,
+}
+
+impl Span {
+    /// create a new span with no style
+    pub fn new<S: ToString>(text: S) -> Self {
+        Span {
+            text: text.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// render this span as bold
+    pub fn bold(&mut self) {
+        self.attributes.set(Attribute::Bold);
+    }
+
+    /// add these attributes to the span
+    pub fn attributes(&mut self, attributes: Vec<Attribute>) {
+        for attr in attributes {
+            self.attributes.set(attr);
+        }
+    }
+
+    /// set the foreground color of this span
+    pub fn color(&mut self, color: Color) {
+        self.foreground_color = Some(color);
+    }
+
+    /// set the background color of this span
+    pub fn background(&mut self, color: Color) {
+        self.background_color = Some(color);
+    }
+
+    /// create a span of this text, with the same style as this span
+    pub(crate) fn with_text(&self, text: &str) -> Self {
+        Span {
+            text: text.to_string(),
+            ..self.clone()
+        }
+    }
+
+    /// apply the colors and attributes of this span to the cell
+    pub(crate) fn style_cell(&self, cell: &mut Cell) {
+        if let Some(color) = self.foreground_color {
+            cell.foreground_color = Some(color);
+        }
+        if let Some(color) = self.background_color {
+            cell.background_color = Some(color);
+        }
+        cell.attributes.extend(self.attributes);
+    }
+
+    /// merge the style of the other span into this span
+    fn merge_style(&mut self, other: &Span) {
+        if other.foreground_color.is_some() {
+            self.foreground_color = other.foreground_color;
+        }
+        if other.background_color.is_some() {
+            self.background_color = other.background_color;
+        }
+        self.attributes.extend(other.attributes);
+    }
+}
+
+impl StyledText {
+    /// create an empty styled text
+    pub fn new() -> Self {
+        StyledText::default()
+    }
+
+    /// parse the markup into a styled text.
+    /// The tags are enclosed in square brackets and are closed with `[/tag]`,
+    /// `[/]` closes the last opened tag.
+    /// - `b`, `i`, `u`, `s`, `dim` and `reverse` for bold, italic,
+    ///   underlined, strike through, dim and reverse.
+    /// - a color name such as `red`, `dark_blue` or `#ff8800` for the foreground color
+    /// - `bg=` followed by the color for the background color
+    ///
+    /// Use `[[` for a literal `[`, unknown tags are kept as text.
+    pub fn from_markup(markup: &str) -> Self {
+        let mut styled = StyledText::new();
+        // the opened tags together with their style
+        let mut tags: Vec<(&str, Span)> = vec![];
+        let mut text = String::new();
+        let mut rest = markup;
+        while let Some(open) = rest.find('[') {
+            text.push_str(&rest[..open]);
+            let after = &rest[open + 1..];
+            if let Some(escaped) = after.strip_prefix('[') {
+                text.push('[');
+                rest = escaped;
+                continue;
+            }
+            let tag = after.find(']').map(|close| (&after[..close], close));
+            match tag {
+                Some((tag, close)) if is_known_tag(tag, &tags) => {
+                    styled.push(current_style(&tags).with_text(&text));
+                    text.clear();
+                    match tag.strip_prefix('/') {
+                        Some("") => {
+                            tags.pop();
+                        }
+                        Some(name) => {
+                            let index = tags
+                                .iter()
+                                .rposition(|(opened, _)| *opened == name)
+                                .expect("must be opened");
+                            tags.remove(index);
+                        }
+                        None => {
+                            let style = tag_style(tag).expect("must be valid");
+                            tags.push((tag_name(tag), style));
+                        }
+                    }
+                    rest = &after[close + 1..];
+                }
+                _ => {
+                    text.push('[');
+                    rest = after;
+                }
+            }
+        }
+        text.push_str(rest);
+        styled.push(current_style(&tags).with_text(&text));
+        styled
+    }
+
+    /// parse the text which contains ANSI escape sequences, such as the colored
+    /// output of a command. The SGR sequences for the 16, 256 and true colors,
+    /// bold, dim, italic, underline, reverse and strike through are used
+    /// for the styles, the rest of the escape sequences are removed.
+    pub fn from_ansi(s: &str) -> Self {
+        ansi::parse(s)
+    }
+
+    /// convert this text into styled cells, one cell for each grapheme.
+    /// Zero width graphemes such as control characters are left out.
+    pub fn to_cells(&self) -> Vec<Cell> {
+        let mut cells = vec![];
+        for span in self.spans.iter() {
+            for (grapheme, width) in unicode::graphemes(&span.text) {
+                if width > 0 {
+                    let mut cell = Cell::new(grapheme);
+                    span.style_cell(&mut cell);
+                    cells.push(cell);
+                }
+            }
+        }
+        cells
+    }
+
+    /// add a span at the end of this text
+    pub fn push(&mut self, span: Span) {
+        if span.text.is_empty() {
+            return;
+        }
+        self.text.push_str(&span.text);
+        self.spans.push(span);
+    }
+
+    /// add a text with no style at the end of this text
+    pub fn push_str(&mut self, text: &str) {
+        self.push(Span::new(text));
+    }
+
+    /// return the spans of this text
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// return the text of all the spans, without the styles
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// return the number of cells this text occupies
+    pub fn width(&self) -> usize {
+        unicode::str_width(&self.text)
+    }
+
+    /// whether this text is empty
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// return the part of the text in this byte range, keeping the styles
+    pub(crate) fn slice(&self, range: Range<usize>) -> Self {
+        let mut sliced = StyledText::new();
+        let mut start = 0;
+        for span in self.spans.iter() {
+            let end = start + span.text.len();
+            let from = range.start.max(start);
+            let to = range.end.min(end);
+            if from < to {
+                sliced
+                    .push(span.with_text(&span.text[from - start..to - start]));
+            }
+            start = end;
+        }
+        sliced
+    }
+
+    /// split the text into lines at the new lines
+    pub(crate) fn lines(&self) -> Vec<StyledText> {
+        let mut lines = vec![];
+        let mut start = 0;
+        for line in self.text.split('\n') {
+            lines.push(self.slice(start..start + line.len()));
+            start += line.len() + 1;
+        }
+        lines
+    }
+
+    /// take the start of the text which fits in this width
+    pub(crate) fn take_width(&self, max_width: usize) -> Self {
+        self.slice(0..unicode::take_width(&self.text, max_width).len())
+    }
+
+    /// remove the whitespace at the end of the text
+    pub(crate) fn trim_end(&self) -> Self {
+        self.slice(0..self.text.trim_end().len())
+    }
+
+    /// shorten the text so it fits with an ellipsis at the end
+    pub(crate) fn with_ellipsis(&self, max_width: usize) -> Self {
+        let mut shortened =
+            self.take_width(max_width.saturating_sub(1)).trim_end();
+        shortened.push_str(&symbol::ELLIPSIS.to_string());
+        shortened
+    }
+
+    /// shorten the text to fit in this width, an ellipsis is added
+    /// at the end when the text is truncated
+    pub(crate) fn truncate(&self, max_width: usize) -> Self {
+        if self.width() <= max_width {
+            self.clone()
+        } else if max_width == 0 {
+            StyledText::new()
+        } else {
+            self.with_ellipsis(max_width)
+        }
+    }
+
+    /// spread the words of the text so it fills the width
+    pub(crate) fn justify(&self, width: usize) -> Self {
+        let words = unicode::word_ranges(&self.text);
+        let words_width: usize = words
+            .iter()
+            .map(|word| unicode::str_width(&self.text[word.clone()]))
+            .sum();
+        let gaps = words.len().saturating_sub(1);
+        if gaps == 0 || words_width + gaps > width {
+            return self.clone();
+        }
+        let mut justified = StyledText::new();
+        let mut gap_widths =
+            unicode::justify_gaps(words_width, gaps, width).into_iter();
+        for (i, word) in words.into_iter().enumerate() {
+            if i > 0 {
+                let gap = gap_widths.next().expect("must have a gap");
+                justified.push_str(&" ".repeat(gap));
+            }
+            for span in self.slice(word).spans {
+                justified.push(span);
+            }
+        }
+        justified
+    }
+}
+
+/// the style of all the opened tags combined
+fn current_style(tags: &[(&str, Span)]) -> Span {
+    let mut style = Span::default();
+    for (_, tag_style) in tags {
+        style.merge_style(tag_style);
+    }
+    style
+}
+
+/// the name of the tag used in closing it, `bg=red` is closed with `[/bg]`
+fn tag_name(tag: &str) -> &str {
+    tag.split('=').next().unwrap_or(tag)
+}
+
+/// whether the tag can be opened or is closing an opened tag
+fn is_known_tag(tag: &str, opened: &[(&str, Span)]) -> bool {
+    match tag.strip_prefix('/') {
+        Some("") => !opened.is_empty(),
+        Some(name) => opened.iter().any(|(opened, _)| *opened == name),
+        None => tag_style(tag).is_some(),
+    }
+}
+
+/// the style of the opening tag
+fn tag_style(tag: &str) -> Option<Span> {
+    let mut style = Span::default();
+    match tag {
+        "b" | "bold" => style.bold(),
+        "i" | "italic" => style.attributes(vec![Attribute::Italic]),
+        "u" | "underline" => style.attributes(vec![Attribute::Underlined]),
+        "s" | "strike" => style.attributes(vec![Attribute::CrossedOut]),
+        "dim" => style.attributes(vec![Attribute::Dim]),
+        "reverse" => style.attributes(vec![Attribute::Reverse]),
+        _ => match tag.strip_prefix("bg=") {
+            Some(color) => style.background(parse_color(color)?),
+            None => style.color(parse_color(tag)?),
+        },
+    }
+    Some(style)
+}
+
+/// parse the color name or the hex color in the form of `#rrggbb`
+pub(crate) fn parse_color(name: &str) -> Option<Color> {
+    if let Some(hex) = name.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+        let channel =
+            |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        return Some(Color::Rgb {
+            r: channel(0)?,
+            g: channel(2)?,
+            b: channel(4)?,
+        });
+    }
+    let color = match name {
+        "black" => Color::Black,
+        "dark_grey" => Color::DarkGrey,
+        "red" => Color::Red,
+        "dark_red" => Color::DarkRed,
+        "green" => Color::Green,
+        "dark_green" => Color::DarkGreen,
+        "yellow" => Color::Yellow,
+        "dark_yellow" => Color::DarkYellow,
+        "blue" => Color::Blue,
+        "dark_blue" => Color::DarkBlue,
+        "magenta" => Color::Magenta,
+        "dark_magenta" => Color::DarkMagenta,
+        "cyan" => Color::Cyan,
+        "dark_cyan" => Color::DarkCyan,
+        "white" => Color::White,
+        "grey" => Color::Grey,
+        _ => return None,
+    };
+    Some(color)
+}
+
+impl fmt::Display for StyledText {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl From<Span> for StyledText {
+    fn from(span: Span) -> Self {
+        let mut styled = StyledText::new();
+        styled.push(span);
+        styled
+    }
+}
+
+impl From<Vec<Span>> for StyledText {
+    fn from(spans: Vec<Span>) -> Self {
+        let mut styled = StyledText::new();
+        for span in spans {
+            styled.push(span);
+        }
+        styled
+    }
+}
+
+impl From<&str> for StyledText {
+    fn from(text: &str) -> Self {
+        StyledText::from(Span::new(text))
+    }
+}
+
+impl From<String> for StyledText {
+    fn from(text: String) -> Self {
+        StyledText::from(Span::new(text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_markup() {
+        let styled =
+            StyledText::from_markup("[b]bold [red]err[/b] red[/red] [[x] [y]");
+        assert_eq!(styled.as_str(), "bold err red [x] [y]");
+        let spans = styled.spans();
+        assert_eq!(spans.len(), 4);
+        assert_eq!(spans[0].text, "bold ");
+        assert!(spans[0].attributes.has(Attribute::Bold));
+        assert_eq!(spans[1].text, "err");
+        assert_eq!(spans[1].foreground_color, Some(Color::Red));
+        assert!(spans[1].attributes.has(Attribute::Bold));
+        assert_eq!(spans[2].text, " red");
+        assert!(!spans[2].attributes.has(Attribute::Bold));
+        assert_eq!(spans[3].foreground_color, None);
+    }
+
+    #[test]
+    fn background_and_hex_colors() {
+        let styled = StyledText::from_markup("[bg=#ff8800]a[/bg][bg=blue]b[/]");
+        assert_eq!(
+            styled.spans()[0].background_color,
+            Some(Color::Rgb {
+                r: 255,
+                g: 136,
+                b: 0
+            })
+        );
+        assert_eq!(styled.spans()[1].background_color, Some(Color::Blue));
+        // unknown and unmatched tags are kept as text
+        let styled = StyledText::from_markup("[foo]x[/b]");
+        assert_eq!(styled.as_str(), "[foo]x[/b]");
+    }
+
+    #[test]
+    fn slice_keeps_the_styles() {
+        let styled = StyledText::from_markup("ab[b]cd[/b]ef");
+        let sliced = styled.slice(1..5);
+        assert_eq!(sliced.as_str(), "bcde");
+        assert_eq!(sliced.spans().len(), 3);
+        assert!(sliced.spans()[1].attributes.has(Attribute::Bold));
+        assert_eq!(styled.truncate(4).as_str(), "abc…");
+    }
+}
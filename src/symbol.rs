@@ -7,6 +7,21 @@ pub mod block {
     pub const THREE_EIGHTHS: char = '▍';
     pub const ONE_QUATER: char = '▎';
     pub const ONE_EIGHTH: char = '▏';
+
+    /// return the block which fills the cell from the left by this number of eighths
+    pub fn eighths(eighths: usize) -> char {
+        match eighths {
+            0 => super::EMPTY,
+            1 => ONE_EIGHTH,
+            2 => ONE_QUATER,
+            3 => THREE_EIGHTHS,
+            4 => HALF,
+            5 => FIVE_EIGHTHS,
+            6 => THREE_QUATERS,
+            7 => SEVEN_EIGHTHS,
+            _ => FULL,
+        }
+    }
 }
 
 pub mod bar {
@@ -18,6 +33,21 @@ pub mod bar {
     pub const THREE_EIGHTHS: char = '▃';
     pub const ONE_QUATER: char = '▂';
     pub const ONE_EIGHTH: char = '▁';
+
+    /// return the bar which fills the cell from the bottom by this number of eighths
+    pub fn eighths(eighths: usize) -> char {
+        match eighths {
+            0 => super::EMPTY,
+            1 => ONE_EIGHTH,
+            2 => ONE_QUATER,
+            3 => THREE_EIGHTHS,
+            4 => HALF,
+            5 => FIVE_EIGHTHS,
+            6 => THREE_QUATERS,
+            7 => SEVEN_EIGHTHS,
+            _ => FULL,
+        }
+    }
 }
 
 pub const BOX_UNCHECKED: char = '☐';
@@ -29,5 +59,35 @@ pub const RADIO_UNCHECKED: char = '〇';
 
 pub const EMPTY: char = ' ';
 
+/// used at the end of text that is truncated
+pub const ELLIPSIS: char = '…';
+
 /// used in scrollbar thumb
 pub const MIDDLE_BLOCK: char = '▮';
+
+/// used in image for the row of pixels which fills only the top half of the cell
+pub const UPPER_HALF_BLOCK: char = '▀';
+
+/// used in tree view for the node which children are hidden
+pub const TREE_COLLAPSED: char = '▸';
+/// used in tree view for the node which children are shown
+pub const TREE_EXPANDED: char = '▾';
+
+/// used in tab box for scrolling the tabs that overflows
+pub const ARROW_LEFT: char = '◀';
+pub const ARROW_RIGHT: char = '▶';
+
+/// used in number input for incrementing and decrementing the value
+pub const ARROW_UP: char = '▲';
+pub const ARROW_DOWN: char = '▼';
+
+/// used in the close button of the tabs
+pub const CLOSE: char = '×';
+
+/// box drawing lines used as indentation guides in tree view
+pub mod guide {
+    pub const VERTICAL: char = '│';
+    pub const HORIZONTAL: char = '─';
+    pub const BRANCH: char = '├';
+    pub const LAST_BRANCH: char = '└';
+}
@@ -1,5 +1,6 @@
-pub(crate) use area_buffer::AreaBuffer;
+pub(crate) use area_buffer::{AreaBuffer, LineChange};
 pub(crate) use input_buffer::InputBuffer;
 
 mod area_buffer;
+mod history;
 mod input_buffer;
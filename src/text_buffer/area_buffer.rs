@@ -1,181 +1,622 @@
-use crate::crossterm::event::{KeyCode, KeyEvent};
-use unicode_width::UnicodeWidthChar;
+use crate::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::text_buffer::history::{ChangeKind, Edit, History};
+use crate::unicode;
+use ropey::Rope;
+use std::{borrow::Cow, collections::BTreeMap, ops::Range};
 
-/// Area buffer is a 2 dimensional text buffer
+/// the number of columns in between the tab stops
+const TAB_WIDTH: usize = 4;
+
+/// Area buffer is a 2 dimensional text buffer.
+/// The cursor x location is the index of the grapheme in the line,
+/// use `get_cursor_column` to get the cell where the cursor is displayed.
+///
+/// The content is stored in a rope, so the edits on a large text
+/// only touch the lines around the edit.
 #[derive(Default, Debug, PartialEq, Clone)]
 pub(crate) struct AreaBuffer {
-    pub(crate) content: Vec<Vec<char>>,
-    content_width: usize,
+    content: Rope,
+    /// the number of lines for each of the display width,
+    /// the last entry is the width of the widest line
+    line_widths: BTreeMap<usize, usize>,
     cursor_loc_x: usize,
     cursor_loc_y: usize,
+    /// the other end of the selection, the cursor is the moving end
+    selection_anchor: Option<(usize, usize)>,
+    history: History<(usize, usize)>,
+    /// the lines which are replaced by the edits, since they were last taken
+    line_changes: Vec<LineChange>,
+}
+
+/// The lines starting at `line` are replaced, `removed` is the number
+/// of the old lines and `inserted` is the number of the new lines.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) struct LineChange {
+    pub line: usize,
+    pub removed: usize,
+    pub inserted: usize,
+}
+
+/// whether the grapheme is part of a word, used in moving by words
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().all(|ch| ch.is_alphanumeric() || ch == '_')
 }
 
 impl AreaBuffer {
     fn add_char(&mut self, c: char) {
-        let line = self
-            .content
-            .get_mut(self.cursor_loc_y)
-            .expect("must have a line");
-        line.insert(self.cursor_loc_x, c);
-        self.cursor_loc_x += 1;
-        self.calc_content_width();
+        let mut buf = [0; 4];
+        self.insert_text(c.encode_utf8(&mut buf));
+    }
+
+    fn add_width(&mut self, width: usize) {
+        *self.line_widths.entry(width).or_insert(0) += 1;
+    }
+
+    fn remove_width(&mut self, width: usize) {
+        if let Some(count) = self.line_widths.get_mut(&width) {
+            *count -= 1;
+            if *count == 0 {
+                self.line_widths.remove(&width);
+            }
+        }
     }
 
-    fn calc_content_width(&mut self) {
-        self.content_width = self
-            .content
-            .iter()
-            .map(|line| line.len())
-            .max()
-            .unwrap_or(0);
+    /// replace the bytes of the content with the text,
+    /// only the widths of the lines which are touched are updated
+    fn splice(&mut self, offset: usize, len: usize, text: &str) {
+        let start_y = self.content.byte_to_line(offset);
+        let end_y = self.content.byte_to_line(offset + len);
+        for y in start_y..=end_y {
+            self.remove_width(unicode::str_width(&self.line(y)));
+        }
+        let start = self.content.byte_to_char(offset);
+        let end = self.content.byte_to_char(offset + len);
+        self.content.remove(start..end);
+        self.content.insert(start, text);
+        let new_end_y = start_y + text.matches('\n').count();
+        for y in start_y..=new_end_y {
+            self.add_width(unicode::str_width(&self.line(y)));
+        }
+        self.line_changes.push(LineChange {
+            line: start_y,
+            removed: end_y - start_y + 1,
+            inserted: new_end_y - start_y + 1,
+        });
     }
 
     pub(crate) fn add_line<S: ToString>(&mut self, s: S) {
-        let line = s.to_string().chars().collect();
-        self.content.push(line);
+        let end = self.content.len_bytes();
+        self.splice(end, 0, &format!("\n{}", s.to_string()));
         self.cursor_loc_y += 1;
-        self.calc_content_width();
     }
 
-    pub fn process_key_event(
+    /// take the lines which are replaced since this was last called
+    pub(crate) fn take_line_changes(&mut self) -> Vec<LineChange> {
+        std::mem::take(&mut self.line_changes)
+    }
+
+    /// return the line without the new line character
+    pub(crate) fn line(&self, y: usize) -> Cow<'_, str> {
+        let line = self.content.line(y);
+        let len = line.len_chars();
+        if len > 0 && line.char(len - 1) == '\n' {
+            line.slice(..len - 1).into()
+        } else {
+            line.into()
+        }
+    }
+
+    /// the number of graphemes in the line
+    fn line_len(&self, y: usize) -> usize {
+        if y < self.height() {
+            unicode::grapheme_count(&self.line(y))
+        } else {
+            0
+        }
+    }
+
+    fn last_line(&self) -> usize {
+        self.height().saturating_sub(1)
+    }
+
+    /// the byte offset of this location in the content
+    fn offset_of(&self, x: usize, y: usize) -> usize {
+        self.content.line_to_byte(y) + unicode::byte_index(&self.line(y), x)
+    }
+
+    /// the location of this byte offset in the content
+    fn location_of(&self, offset: usize) -> (usize, usize) {
+        let y = self.content.byte_to_line(offset);
+        let line_offset = offset - self.content.line_to_byte(y);
+        let x = unicode::grapheme_count(&self.line(y)[..line_offset]);
+        (x, y)
+    }
+
+    /// replace the text at the byte offset of the content,
+    /// this is used to revert and to redo the edits
+    fn replace_text(&mut self, offset: usize, len: usize, text: &str) {
+        self.splice(offset, len, text);
+    }
+
+    /// insert the text at the cursor, replacing the selection.
+    /// The new lines in the text starts new lines in the buffer.
+    pub(crate) fn insert_str(&mut self, s: &str) {
+        let cursor_before = (self.cursor_loc_x, self.cursor_loc_y);
+        self.insert_text(s);
+        self.commit(ChangeKind::Other, cursor_before);
+    }
+
+    fn insert_text(&mut self, s: &str) {
+        self.remove_selection();
+        let offset = self.offset_of(self.cursor_loc_x, self.cursor_loc_y);
+        self.history.record(Edit {
+            offset,
+            removed: String::new(),
+            inserted: s.to_string(),
+        });
+        self.splice(offset, 0, s);
+        // a combining character is merged into the grapheme before it
+        let (x, y) = self.location_of(offset + s.len());
+        self.cursor_loc_x = x;
+        self.cursor_loc_y = y;
+    }
+
+    /// insert spaces up to the next tab stop
+    fn insert_tab(&mut self) {
+        let (column, _) = self.get_cursor_column();
+        let spaces = TAB_WIDTH - column % TAB_WIDTH;
+        self.insert_text(&" ".repeat(spaces));
+    }
+
+    /// remove the text in between these locations,
+    /// the start must be before the end
+    fn remove_range(&mut self, start: (usize, usize), end: (usize, usize)) {
+        self.history.record(Edit {
+            offset: self.offset_of(start.0, start.1),
+            removed: self.text_between(start, end),
+            inserted: String::new(),
+        });
+        let start_offset = self.offset_of(start.0, start.1);
+        let end_offset = self.offset_of(end.0, end.1);
+        self.splice(start_offset, end_offset - start_offset, "");
+        self.cursor_loc_x = start.0;
+        self.cursor_loc_y = start.1;
+    }
+
+    /// return the start and end of the selection, in the order they appear
+    pub(crate) fn selection(&self) -> Option<((usize, usize), (usize, usize))> {
+        let anchor = self.selection_anchor?;
+        let cursor = (self.cursor_loc_x, self.cursor_loc_y);
+        if anchor == cursor {
+            return None;
+        }
+        // compare by line first, then by the location in the line
+        let (a, c) = ((anchor.1, anchor.0), (cursor.1, cursor.0));
+        if a < c {
+            Some((anchor, cursor))
+        } else {
+            Some((cursor, anchor))
+        }
+    }
+
+    /// return the selected text
+    pub(crate) fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection()?;
+        Some(self.text_between(start, end))
+    }
+
+    /// the text in between these locations, the start must be before the end
+    fn text_between(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> String {
+        let start = self.offset_of(start.0, start.1);
+        let end = self.offset_of(end.0, end.1);
+        self.content.byte_slice(start..end).to_string()
+    }
+
+    /// remove the selected text, return true if there was a selection
+    pub(crate) fn delete_selection(&mut self) -> bool {
+        let cursor_before = (self.cursor_loc_x, self.cursor_loc_y);
+        let is_deleted = self.remove_selection();
+        self.commit(ChangeKind::Other, cursor_before);
+        is_deleted
+    }
+
+    fn remove_selection(&mut self) -> bool {
+        let selection = self.selection();
+        self.selection_anchor = None;
+        match selection {
+            Some((start, end)) => {
+                self.remove_range(start, end);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// put the recorded edits into an undo step
+    fn commit(&mut self, kind: ChangeKind, cursor_before: (usize, usize)) {
+        let cursor_after = (self.cursor_loc_x, self.cursor_loc_y);
+        self.history.commit(kind, cursor_before, cursor_after);
+    }
+
+    /// undo the last change, return true if there is a change undone
+    pub(crate) fn undo(&mut self) -> bool {
+        match self.history.undo() {
+            Some(change) => {
+                for edit in change.edits.iter().rev() {
+                    self.replace_text(
+                        edit.offset,
+                        edit.inserted.len(),
+                        &edit.removed,
+                    );
+                }
+                let (x, y) = change.cursor_before;
+                self.set_cursor_loc(x, y);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// redo the last undone change, return true if there is a change redone
+    pub(crate) fn redo(&mut self) -> bool {
+        match self.history.redo() {
+            Some(change) => {
+                for edit in change.edits.iter() {
+                    self.replace_text(
+                        edit.offset,
+                        edit.removed.len(),
+                        &edit.inserted,
+                    );
+                }
+                let (x, y) = change.cursor_after;
+                self.set_cursor_loc(x, y);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// replace the content of the buffer, this also clears the undo history
+    pub(crate) fn set_content(&mut self, s: String) {
+        let mut history = std::mem::take(&mut self.history);
+        history.clear();
+        *self = AreaBuffer::from(s);
+        self.history = history;
+    }
+
+    /// set the maximum number of changes which can be undone
+    pub(crate) fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// replace the byte ranges of the lines with the texts as one undo step,
+    /// the ranges must be in the order they appear and must not overlap
+    pub(crate) fn replace_ranges(
         &mut self,
-        KeyEvent { code, modifiers: _ }: KeyEvent,
+        replacements: Vec<(usize, Range<usize>, String)>,
     ) {
-        match code {
-            KeyCode::Char(c) => {
+        let cursor_before = (self.cursor_loc_x, self.cursor_loc_y);
+        self.selection_anchor = None;
+        // the later ranges are replaced first, so the earlier offsets stay the same
+        for (line, range, text) in replacements.into_iter().rev() {
+            let offset = self.content.line_to_byte(line) + range.start;
+            let removed = self
+                .content
+                .byte_slice(offset..offset + range.len())
+                .to_string();
+            self.splice(offset, removed.len(), &text);
+            self.history.record(Edit {
+                offset,
+                removed,
+                inserted: text,
+            });
+        }
+        self.cursor_loc_y = self.cursor_loc_y.min(self.last_line());
+        self.cursor_loc_x =
+            self.cursor_loc_x.min(self.line_len(self.cursor_loc_y));
+        self.commit(ChangeKind::Other, cursor_before);
+    }
+
+    /// select the whole content
+    pub(crate) fn select_all(&mut self) {
+        self.selection_anchor = Some((0, 0));
+        self.cursor_loc_y = self.last_line();
+        self.cursor_loc_x = self.line_len(self.cursor_loc_y);
+    }
+
+    /// start the selection at the cursor when selecting,
+    /// otherwise clear the selection
+    fn update_anchor(&mut self, select: bool) {
+        self.history.seal();
+        if !select {
+            self.selection_anchor = None;
+        } else if self.selection_anchor.is_none() {
+            self.selection_anchor =
+                Some((self.cursor_loc_x, self.cursor_loc_y));
+        }
+    }
+
+    /// move the cursor up or down by a number of lines,
+    /// the cursor is placed at the end of the line when it is shorter
+    pub(crate) fn move_lines(&mut self, delta: isize, select: bool) {
+        self.update_anchor(select);
+        self.cursor_loc_y = if delta < 0 {
+            self.cursor_loc_y.saturating_sub(delta.unsigned_abs())
+        } else {
+            (self.cursor_loc_y + delta as usize).min(self.last_line())
+        };
+        self.cursor_loc_x =
+            self.cursor_loc_x.min(self.line_len(self.cursor_loc_y));
+    }
+
+    /// move the cursor to this location, extending the selection when selecting
+    pub(crate) fn move_cursor_to(&mut self, x: usize, y: usize, select: bool) {
+        self.update_anchor(select);
+        self.cursor_loc_y = y.min(self.last_line());
+        self.cursor_loc_x = x.min(self.line_len(self.cursor_loc_y));
+    }
+
+    fn move_left(&mut self) {
+        if self.cursor_loc_x > 0 {
+            self.cursor_loc_x -= 1;
+        } else if self.cursor_loc_y > 0 {
+            self.cursor_loc_y -= 1;
+            self.cursor_loc_x = self.line_len(self.cursor_loc_y);
+        }
+    }
+
+    fn move_right(&mut self) {
+        if self.cursor_loc_x < self.line_len(self.cursor_loc_y) {
+            self.cursor_loc_x += 1;
+        } else if self.cursor_loc_y < self.last_line() {
+            self.cursor_loc_y += 1;
+            self.cursor_loc_x = 0;
+        }
+    }
+
+    /// move the cursor to the start of the word before it
+    fn move_word_left(&mut self) {
+        if self.cursor_loc_x == 0 {
+            self.move_left();
+            return;
+        }
+        let line = self.line(self.cursor_loc_y);
+        let graphemes: Vec<(&str, usize)> =
+            unicode::graphemes(&line).take(self.cursor_loc_x).collect();
+        let mut x = graphemes.len();
+        while x > 0 && !is_word(graphemes[x - 1].0) {
+            x -= 1;
+        }
+        while x > 0 && is_word(graphemes[x - 1].0) {
+            x -= 1;
+        }
+        self.cursor_loc_x = x;
+    }
+
+    /// move the cursor to the end of the word after it
+    fn move_word_right(&mut self) {
+        let line_len = self.line_len(self.cursor_loc_y);
+        if self.cursor_loc_x >= line_len {
+            self.move_right();
+            return;
+        }
+        let line = self.line(self.cursor_loc_y);
+        let graphemes: Vec<(&str, usize)> = unicode::graphemes(&line).collect();
+        let mut x = self.cursor_loc_x;
+        while x < line_len && !is_word(graphemes[x].0) {
+            x += 1;
+        }
+        while x < line_len && is_word(graphemes[x].0) {
+            x += 1;
+        }
+        self.cursor_loc_x = x;
+    }
+
+    /// remove the grapheme before the cursor,
+    /// joining the line with the previous line when the cursor is at the start
+    fn backspace(&mut self) {
+        if self.remove_selection() {
+            return;
+        }
+        let end = (self.cursor_loc_x, self.cursor_loc_y);
+        self.move_left();
+        let start = (self.cursor_loc_x, self.cursor_loc_y);
+        if start != end {
+            self.remove_range(start, end);
+        }
+    }
+
+    /// remove the grapheme after the cursor,
+    /// joining the next line when the cursor is at the end
+    fn delete(&mut self) {
+        if self.remove_selection() {
+            return;
+        }
+        let start = (self.cursor_loc_x, self.cursor_loc_y);
+        self.move_right();
+        let end = (self.cursor_loc_x, self.cursor_loc_y);
+        if start != end {
+            self.remove_range(start, end);
+        }
+    }
+
+    /// process the key event, return true if the content is changed
+    pub fn process_key_event(
+        &mut self,
+        KeyEvent { code, modifiers }: KeyEvent,
+    ) -> bool {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let cursor_before = (self.cursor_loc_x, self.cursor_loc_y);
+        let kind = match code {
+            KeyCode::Char('a') if ctrl => {
+                self.select_all();
+                return false;
+            }
+            KeyCode::Char('z') | KeyCode::Char('Z') if ctrl && shift => {
+                return self.redo();
+            }
+            KeyCode::Char('z') if ctrl => return self.undo(),
+            KeyCode::Char('y') if ctrl => return self.redo(),
+            // the other key combinations with control are commands, not text
+            KeyCode::Char(c) if !ctrl => {
                 self.add_char(c);
+                ChangeKind::Typing
             }
             KeyCode::Enter => {
-                if let Some(line) = self.content.get_mut(self.cursor_loc_y) {
-                    let new_line = line.split_off(self.cursor_loc_x);
-                    self.cursor_loc_y += 1;
-                    self.cursor_loc_x = 0;
-                    self.content.insert(self.cursor_loc_y, new_line);
-                    self.calc_content_width();
-                }
+                self.insert_text("\n");
+                ChangeKind::Typing
             }
-            KeyCode::Left => {
-                if self.cursor_loc_x > 0 {
-                    self.cursor_loc_x -= 1;
-                }
+            KeyCode::Tab => {
+                self.insert_tab();
+                ChangeKind::Typing
             }
-            KeyCode::Right => {
-                if let Some(line) = self.content.get(self.cursor_loc_y) {
-                    if self.cursor_loc_x < line.len() {
-                        self.cursor_loc_x += 1;
-                    }
-                }
+            KeyCode::Backspace => {
+                self.backspace();
+                ChangeKind::Deleting
             }
-            KeyCode::Up => {
-                if self.cursor_loc_y > 0 {
-                    self.cursor_loc_y -= 1;
-                    if let Some(line) = self.content.get(self.cursor_loc_y) {
-                        if self.cursor_loc_x > line.len() {
-                            self.cursor_loc_x = line.len();
-                        }
-                    }
-                }
+            KeyCode::Delete => {
+                self.delete();
+                ChangeKind::Deleting
             }
-            KeyCode::Down => {
-                if self.cursor_loc_y < self.content.len() - 1 {
-                    self.cursor_loc_y += 1;
-                    if let Some(line) = self.content.get(self.cursor_loc_y) {
-                        if self.cursor_loc_x > line.len() {
-                            self.cursor_loc_x = line.len();
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Home
+            | KeyCode::End => {
+                // moving without shift collapses the selection
+                if !shift {
+                    if let Some((start, end)) = self.selection() {
+                        let (x, y) = match code {
+                            KeyCode::Left | KeyCode::Up => start,
+                            _ => end,
+                        };
+                        self.set_cursor_loc(x, y);
+                        if matches!(code, KeyCode::Left | KeyCode::Right) {
+                            return false;
                         }
                     }
                 }
-            }
-            KeyCode::Backspace => {
-                if let Some(line) = self.content.get_mut(self.cursor_loc_y) {
-                    if self.cursor_loc_x > 0 && line.len() > 0 {
-                        self.cursor_loc_x -= 1;
-                        line.remove(self.cursor_loc_x);
+                self.update_anchor(shift);
+                match code {
+                    KeyCode::Left if ctrl => self.move_word_left(),
+                    KeyCode::Right if ctrl => self.move_word_right(),
+                    KeyCode::Left => self.move_left(),
+                    KeyCode::Right => self.move_right(),
+                    KeyCode::Up => self.move_lines(-1, shift),
+                    KeyCode::Down => self.move_lines(1, shift),
+                    KeyCode::Home if ctrl => {
+                        self.cursor_loc_x = 0;
+                        self.cursor_loc_y = 0;
                     }
-                    self.calc_content_width();
-                }
-            }
-            KeyCode::Delete => {
-                if let Some(line) = self.content.get_mut(self.cursor_loc_y) {
-                    if self.cursor_loc_x < line.len() {
-                        line.remove(self.cursor_loc_x);
+                    KeyCode::End if ctrl => {
+                        self.cursor_loc_y = self.last_line();
+                        self.cursor_loc_x = self.line_len(self.cursor_loc_y);
+                    }
+                    KeyCode::Home => self.cursor_loc_x = 0,
+                    KeyCode::End => {
+                        self.cursor_loc_x = self.line_len(self.cursor_loc_y)
                     }
-                    self.calc_content_width();
+                    _ => (),
                 }
+                return false;
             }
-            _ => (),
+            _ => return false,
+        };
+        self.commit(kind, cursor_before);
+        // a new word or a new line starts a new undo step
+        if matches!(code, KeyCode::Char(' ') | KeyCode::Enter) {
+            self.history.seal();
         }
+        true
     }
 
+    /// set the cursor location, this clears the selection
     pub fn set_cursor_loc(&mut self, cursor_x: usize, cursor_y: usize) {
-        self.cursor_loc_x = cursor_x;
-        self.cursor_loc_y = cursor_y;
+        self.history.seal();
+        self.selection_anchor = None;
+        self.cursor_loc_y = cursor_y.min(self.last_line());
+        self.cursor_loc_x = cursor_x.min(self.line_len(self.cursor_loc_y));
     }
 
+    /// set the cursor location to the grapheme displayed at this column and line
+    pub fn set_cursor_column(&mut self, column: usize, cursor_y: usize) {
+        let cursor_y = cursor_y.min(self.last_line());
+        let cursor_x = unicode::index_at_column(&self.line(cursor_y), column);
+        self.set_cursor_loc(cursor_x, cursor_y);
+    }
+
+    /// extend the selection from the cursor up to this column and line
+    pub fn select_to_column(&mut self, column: usize, cursor_y: usize) {
+        let anchor = self
+            .selection_anchor
+            .unwrap_or((self.cursor_loc_x, self.cursor_loc_y));
+        self.set_cursor_column(column, cursor_y);
+        self.selection_anchor = Some(anchor);
+    }
+
+    #[allow(dead_code)]
     pub fn get_cursor_location(&self) -> (usize, usize) {
         (self.cursor_loc_x, self.cursor_loc_y)
     }
 
+    /// return the column and line where the cursor is displayed
+    pub fn get_cursor_column(&self) -> (usize, usize) {
+        let column = unicode::column_of(
+            &self.line(self.cursor_loc_y),
+            self.cursor_loc_x,
+        );
+        (column, self.cursor_loc_y)
+    }
+
+    /// the number of lines in this buffer
     pub fn height(&self) -> usize {
-        self.content.len()
+        self.content.len_lines()
     }
 
+    /// the display width of the widest line
     pub fn width(&self) -> usize {
-        self.content_width
+        self.line_widths.keys().next_back().copied().unwrap_or(0)
     }
 }
 
 impl From<String> for AreaBuffer {
     fn from(s: String) -> Self {
-        let mut content = vec![];
-        let mut cursor_loc_x = 0;
-        let mut cursor_loc_y = 0;
-        let mut content_width = 0;
-        for line in s.lines() {
-            cursor_loc_x = 0;
-            let mut row = vec![];
-            for ch in line.chars() {
-                row.push(ch);
-                cursor_loc_x += 1;
-                if let Some(width) = ch.width() {
-                    for _i in 1..width {
-                        row.push('\0');
-                        cursor_loc_x += 1;
-                    }
-                }
-            }
-            if content_width < row.len() {
-                content_width = row.len();
-            }
-            content.push(row);
-            cursor_loc_y += 1;
-        }
-        if cursor_loc_y > 0 {
-            cursor_loc_y = cursor_loc_y - 1;
-        }
-
-        AreaBuffer {
+        // the lines are separated with just the new line character
+        let s = if s.contains('\r') {
+            s.replace("\r\n", "\n")
+        } else {
+            s
+        };
+        let content = Rope::from_str(s.strip_suffix('\n').unwrap_or(&s));
+        let mut area_buffer = AreaBuffer {
             content,
-            content_width,
-            cursor_loc_x,
-            cursor_loc_y,
+            line_widths: BTreeMap::new(),
+            cursor_loc_x: 0,
+            cursor_loc_y: 0,
+            selection_anchor: None,
+            history: History::default(),
+            line_changes: vec![],
+        };
+        for y in 0..area_buffer.height() {
+            let width = unicode::str_width(&area_buffer.line(y));
+            area_buffer.add_width(width);
         }
+        area_buffer.cursor_loc_y = area_buffer.last_line();
+        area_buffer.cursor_loc_x =
+            area_buffer.line_len(area_buffer.cursor_loc_y);
+        area_buffer
     }
 }
 
 impl ToString for AreaBuffer {
     fn to_string(&self) -> String {
-        let mut lines = vec![];
-        for row in self.content.iter() {
-            let row_contents: Vec<String> = row
-                .iter()
-                .filter(|ch| **ch != '\0')
-                .map(ToString::to_string)
-                .collect();
-            let line = row_contents.join("").trim_end().to_string();
-            lines.push(line);
-        }
-        lines.join("\n")
+        self.content.to_string()
     }
 }
 
@@ -220,4 +661,116 @@ mod tests {
         assert_eq!(1, area_buffer.cursor_loc_y);
         assert_eq!(1, area_buffer.cursor_loc_x);
     }
+
+    #[test]
+    fn wide_characters() {
+        let s = "文字\nab".to_string();
+        let mut area_buffer = AreaBuffer::from(s);
+        assert_eq!(4, area_buffer.width());
+        assert_eq!((2, 1), area_buffer.get_cursor_location());
+        area_buffer.process_key_event(KeyCode::Up.into());
+        assert_eq!((2, 0), area_buffer.get_cursor_location());
+        assert_eq!((4, 0), area_buffer.get_cursor_column());
+        area_buffer.process_key_event(KeyCode::Char('😀').into());
+        assert_eq!(6, area_buffer.width());
+        area_buffer.set_cursor_column(3, 0);
+        assert_eq!((1, 0), area_buffer.get_cursor_location());
+        area_buffer.process_key_event(KeyCode::Delete.into());
+        assert_eq!("文😀\nab", area_buffer.to_string());
+    }
+
+    #[test]
+    fn join_lines() {
+        let mut area_buffer = AreaBuffer::from("ab\ncd".to_string());
+        area_buffer.set_cursor_loc(0, 1);
+        area_buffer.process_key_event(KeyCode::Backspace.into());
+        assert_eq!("abcd", area_buffer.to_string());
+        assert_eq!((2, 0), area_buffer.get_cursor_location());
+        area_buffer.process_key_event(KeyCode::Enter.into());
+        area_buffer.process_key_event(KeyCode::Up.into());
+        area_buffer.process_key_event(KeyCode::End.into());
+        area_buffer.process_key_event(KeyCode::Delete.into());
+        assert_eq!("abcd", area_buffer.to_string());
+        area_buffer.process_key_event(KeyCode::Home.into());
+        area_buffer.process_key_event(KeyCode::Tab.into());
+        assert_eq!("    abcd", area_buffer.to_string());
+    }
+
+    #[test]
+    fn word_movement_and_selection() {
+        let mut area_buffer = AreaBuffer::from("let foo_bar = 1;".to_string());
+        let ctrl_left = KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL);
+        area_buffer.process_key_event(ctrl_left);
+        assert_eq!((14, 0), area_buffer.get_cursor_location());
+        area_buffer.process_key_event(ctrl_left);
+        assert_eq!((4, 0), area_buffer.get_cursor_location());
+        let select_word = KeyEvent::new(
+            KeyCode::Right,
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        );
+        area_buffer.process_key_event(select_word);
+        assert_eq!(Some("foo_bar".to_string()), area_buffer.selected_text());
+        area_buffer.process_key_event(KeyCode::Char('x').into());
+        assert_eq!("let x = 1;", area_buffer.to_string());
+        assert_eq!(None, area_buffer.selection());
+    }
+
+    #[test]
+    fn multiline_selection() {
+        let mut area_buffer = AreaBuffer::from("one\ntwo\nthree".to_string());
+        area_buffer.set_cursor_loc(1, 0);
+        area_buffer.select_to_column(2, 2);
+        assert_eq!(
+            Some("ne\ntwo\nth".to_string()),
+            area_buffer.selected_text()
+        );
+        area_buffer.delete_selection();
+        assert_eq!("oree", area_buffer.to_string());
+        area_buffer.insert_str("ne\ntwo\nth");
+        assert_eq!("one\ntwo\nthree", area_buffer.to_string());
+        assert_eq!((2, 2), area_buffer.get_cursor_location());
+    }
+    #[test]
+    fn undo_and_redo() {
+        let mut area_buffer = AreaBuffer::from("one\ntwo".to_string());
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        for c in " three".chars() {
+            area_buffer.process_key_event(KeyCode::Char(c).into());
+        }
+        area_buffer.set_cursor_loc(0, 1);
+        area_buffer.process_key_event(KeyCode::Backspace.into());
+        assert_eq!("onetwo three", area_buffer.to_string());
+        area_buffer.process_key_event(ctrl('z'));
+        assert_eq!("one\ntwo three", area_buffer.to_string());
+        assert_eq!((0, 1), area_buffer.get_cursor_location());
+        area_buffer.process_key_event(ctrl('z'));
+        // the space starts the undo step of the word after it
+        assert_eq!("one\ntwo ", area_buffer.to_string());
+        area_buffer.process_key_event(ctrl('y'));
+        assert_eq!("one\ntwo three", area_buffer.to_string());
+        assert_eq!((9, 1), area_buffer.get_cursor_location());
+
+        area_buffer.select_all();
+        area_buffer.insert_str("replaced");
+        area_buffer.process_key_event(ctrl('z'));
+        assert_eq!("one\ntwo three", area_buffer.to_string());
+        assert!(area_buffer.redo());
+        assert_eq!("replaced", area_buffer.to_string());
+        assert!(!area_buffer.redo());
+    }
+    #[test]
+    fn widest_line_is_tracked() {
+        let s = "short\nthe widest line\nmid line\n".to_string();
+        let mut area_buffer = AreaBuffer::from(s);
+        assert_eq!(3, area_buffer.height());
+        assert_eq!(15, area_buffer.width());
+        area_buffer.set_cursor_loc(0, 1);
+        area_buffer.select_to_column(0, 2);
+        area_buffer.delete_selection();
+        assert_eq!("short\nmid line", area_buffer.to_string());
+        assert_eq!(8, area_buffer.width());
+        area_buffer.undo();
+        assert_eq!(15, area_buffer.width());
+        assert_eq!("the widest line", area_buffer.line(1));
+    }
 }
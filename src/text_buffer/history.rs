@@ -0,0 +1,187 @@
+use std::collections::VecDeque;
+
+/// the number of undo steps kept by default
+const DEFAULT_DEPTH: usize = 100;
+
+/// A replacement of the text at a byte offset of the content,
+/// an insertion has an empty `removed` and a deletion has an empty `inserted`.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Edit {
+    pub offset: usize,
+    pub removed: String,
+    pub inserted: String,
+}
+
+/// the kind of the change, consecutive typing or deleting are grouped
+/// into one undo step
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum ChangeKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+/// An undo step, which is made of the edits done in one action.
+/// The cursor `C` is restored when the change is undone or redone.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Change<C> {
+    pub edits: Vec<Edit>,
+    pub cursor_before: C,
+    pub cursor_after: C,
+    kind: ChangeKind,
+}
+
+/// The log of the edits done to a text buffer, used for undo and redo.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct History<C> {
+    undo_stack: VecDeque<Change<C>>,
+    redo_stack: Vec<Change<C>>,
+    /// the edits which are not yet committed into an undo step
+    pending: Vec<Edit>,
+    /// the last change can no longer be extended by the next typing
+    sealed: bool,
+    depth: usize,
+}
+
+impl<C> Default for History<C> {
+    fn default() -> Self {
+        History {
+            undo_stack: VecDeque::new(),
+            redo_stack: vec![],
+            pending: vec![],
+            sealed: true,
+            depth: DEFAULT_DEPTH,
+        }
+    }
+}
+
+impl<C: Copy> History<C> {
+    /// record an edit, it is part of the next committed change
+    pub fn record(&mut self, edit: Edit) {
+        if edit.removed != edit.inserted {
+            self.pending.push(edit);
+        }
+    }
+
+    /// put the pending edits into an undo step, this is extended instead
+    /// when it continues the typing or deleting of the last change
+    pub fn commit(
+        &mut self,
+        kind: ChangeKind,
+        cursor_before: C,
+        cursor_after: C,
+    ) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let edits = std::mem::take(&mut self.pending);
+        self.redo_stack.clear();
+        match self.undo_stack.back_mut() {
+            Some(last)
+                if !self.sealed
+                    && last.kind == kind
+                    && kind != ChangeKind::Other =>
+            {
+                last.edits.extend(edits);
+                last.cursor_after = cursor_after;
+            }
+            _ => {
+                self.undo_stack.push_back(Change {
+                    edits,
+                    cursor_before,
+                    cursor_after,
+                    kind,
+                });
+                while self.undo_stack.len() > self.depth {
+                    self.undo_stack.pop_front();
+                }
+            }
+        }
+        self.sealed = kind == ChangeKind::Other;
+    }
+
+    /// the next change starts a new undo step,
+    /// such as when the cursor is moved in between typing
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// take the last change to be undone, the edits of the change
+    /// are to be reverted in reverse order
+    pub fn undo(&mut self) -> Option<Change<C>> {
+        let change = self.undo_stack.pop_back()?;
+        self.redo_stack.push(change.clone());
+        self.sealed = true;
+        Some(change)
+    }
+
+    /// take the last undone change to be done again
+    pub fn redo(&mut self) -> Option<Change<C>> {
+        let change = self.redo_stack.pop()?;
+        self.undo_stack.push_back(change.clone());
+        self.sealed = true;
+        Some(change)
+    }
+
+    /// set the maximum number of undo steps kept
+    pub fn set_depth(&mut self, depth: usize) {
+        self.depth = depth;
+        while self.undo_stack.len() > self.depth {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// remove all the undo and redo steps
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.pending.clear();
+        self.sealed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert(offset: usize, text: &str) -> Edit {
+        Edit {
+            offset,
+            removed: String::new(),
+            inserted: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn typing_is_grouped() {
+        let mut history = History::default();
+        history.record(insert(0, "a"));
+        history.commit(ChangeKind::Typing, 0, 1);
+        history.record(insert(1, "b"));
+        history.commit(ChangeKind::Typing, 1, 2);
+        history.seal();
+        history.record(insert(2, "c"));
+        history.commit(ChangeKind::Typing, 2, 3);
+
+        let change = history.undo().expect("must have a change");
+        assert_eq!(change.edits, vec![insert(2, "c")]);
+        let change = history.undo().expect("must have a change");
+        assert_eq!(change.edits.len(), 2);
+        assert_eq!((change.cursor_before, change.cursor_after), (0, 2));
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().map(|c| c.cursor_after), Some(2));
+    }
+
+    #[test]
+    fn depth_is_limited() {
+        let mut history = History::default();
+        history.set_depth(2);
+        for i in 0..3 {
+            history.record(insert(i, "x"));
+            history.commit(ChangeKind::Other, i, i + 1);
+        }
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_some());
+        assert!(history.undo().is_none());
+    }
+}
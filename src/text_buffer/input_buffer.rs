@@ -1,18 +1,26 @@
-use crate::crossterm::event::{KeyCode, KeyEvent};
+use crate::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::text_buffer::history::{ChangeKind, Edit, History};
+use crate::unicode;
 
 /// Input buffer is a 1 dimensional text buffer.
 /// It process keystroke and create a string representation
 /// depending on each key added to it.
 /// If arrow key (ie. left, right) is pressed the cursor location will be changed
-/// 1 cell backward/forward with respect to the key being pressed.
-/// If backspace stroke is receive, the left most cell relative to the cursor
+/// 1 grapheme backward/forward with respect to the key being pressed.
+/// If backspace stroke is receive, the grapheme to the left of the cursor
 /// will be remove and all the elements on the right side will be shifted to the left.
 ///
-/// TODO: deal with character that spans more than 1 cell
+/// The cursor location is the index of the grapheme cluster, use `get_cursor_column`
+/// to get the cell where the cursor is displayed, since some characters spans 2 cells.
+///
+/// The edits are recorded, Ctrl+Z undo the last edit and Ctrl+Y or Ctrl+Shift+Z redo it.
 #[derive(Default, Debug, PartialEq, Clone)]
 pub struct InputBuffer {
     content: String,
     cursor_loc: usize,
+    history: History<usize>,
+    /// the maximum number of graphemes the content can have
+    max_length: Option<usize>,
 }
 
 impl InputBuffer {
@@ -21,6 +29,8 @@ impl InputBuffer {
         InputBuffer {
             content: String::new(),
             cursor_loc: 0,
+            history: History::default(),
+            max_length: None,
         }
     }
 
@@ -28,10 +38,65 @@ impl InputBuffer {
     /// content set to value.
     pub fn new_with_value<S: ToString>(value: S) -> Self {
         let value = value.to_string();
-        let value_len = value.len();
+        let cursor_loc = unicode::grapheme_count(&value);
         InputBuffer {
             content: value,
-            cursor_loc: value_len,
+            cursor_loc,
+            history: History::default(),
+            max_length: None,
+        }
+    }
+
+    /// replace the content of the buffer, this also clears the undo history
+    pub fn set_content<S: ToString>(&mut self, value: S) {
+        self.content = value.to_string();
+        self.truncate_to_max_length();
+        self.cursor_loc = self.grapheme_count();
+        self.history.clear();
+    }
+
+    /// replace the content as one undoable edit, the cursor is moved to the end
+    pub fn replace_content(&mut self, value: &str) {
+        let cursor_before = self.cursor_loc;
+        let removed = std::mem::replace(&mut self.content, value.to_string());
+        self.truncate_to_max_length();
+        self.cursor_loc = self.grapheme_count();
+        self.history.record(Edit {
+            offset: 0,
+            removed,
+            inserted: self.content.clone(),
+        });
+        self.history
+            .commit(ChangeKind::Other, cursor_before, self.cursor_loc);
+    }
+
+    /// set the maximum number of graphemes of the content,
+    /// the content is truncated when it is longer
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+        if self.truncate_to_max_length() {
+            self.cursor_loc = self.cursor_loc.min(self.grapheme_count());
+            self.history.clear();
+        }
+    }
+
+    /// the content has more graphemes than the maximum length
+    fn exceeds_max_length(&self) -> bool {
+        self.max_length
+            .map(|max_length| self.grapheme_count() > max_length)
+            .unwrap_or(false)
+    }
+
+    /// remove the graphemes past the maximum length,
+    /// return true if the content is truncated
+    fn truncate_to_max_length(&mut self) -> bool {
+        match self.max_length {
+            Some(max_length) if self.exceeds_max_length() => {
+                let end = unicode::byte_index(&self.content, max_length);
+                self.content.truncate(end);
+                true
+            }
+            _ => false,
         }
     }
 
@@ -40,37 +105,113 @@ impl InputBuffer {
         &self.content
     }
 
-    /// return the cursor location of the buffer
+    /// return the cursor location of the buffer, which is the index of the grapheme
     pub fn get_cursor_location(&self) -> usize {
         self.cursor_loc
     }
 
+    /// return the column of the cursor, this is where the cursor is displayed
+    pub fn get_cursor_column(&self) -> usize {
+        unicode::column_of(&self.content, self.cursor_loc)
+    }
+
+    /// the number of graphemes in the content
+    fn grapheme_count(&self) -> usize {
+        unicode::grapheme_count(&self.content)
+    }
+
+    /// the byte index of the grapheme at the cursor location
+    fn cursor_byte_index(&self) -> usize {
+        unicode::byte_index(&self.content, self.cursor_loc)
+    }
+
     /// append a character to the buffer and move the cursor location
-    /// to the right
+    /// to the right. A combining character is merged into the grapheme before it.
+    /// The character is not added when the content is already at the maximum length.
     fn add_char(&mut self, c: char) {
-        self.content.insert(self.cursor_loc, c);
-        self.cursor_loc += 1;
+        let byte_index = self.cursor_byte_index();
+        self.content.insert(byte_index, c);
+        if self.exceeds_max_length() {
+            self.content.remove(byte_index);
+            return;
+        }
+        self.history.record(Edit {
+            offset: byte_index,
+            removed: String::new(),
+            inserted: c.to_string(),
+        });
+        self.cursor_loc =
+            unicode::grapheme_count(&self.content[..byte_index + c.len_utf8()]);
+    }
+
+    /// remove the graphemes in between these grapheme indexes
+    fn remove_range(&mut self, start: usize, end: usize) {
+        let start = unicode::byte_index(&self.content, start);
+        let end = unicode::byte_index(&self.content, end);
+        let removed = self.content[start..end].to_string();
+        self.content.replace_range(start..end, "");
+        self.history.record(Edit {
+            offset: start,
+            removed,
+            inserted: String::new(),
+        });
+    }
+
+    /// undo the last change, return true if there is a change undone
+    pub fn undo(&mut self) -> bool {
+        match self.history.undo() {
+            Some(change) => {
+                for edit in change.edits.iter().rev() {
+                    let end = edit.offset + edit.inserted.len();
+                    self.content.replace_range(edit.offset..end, &edit.removed);
+                }
+                self.cursor_loc = change.cursor_before;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// redo the last undone change, return true if there is a change redone
+    pub fn redo(&mut self) -> bool {
+        match self.history.redo() {
+            Some(change) => {
+                for edit in change.edits.iter() {
+                    let end = edit.offset + edit.removed.len();
+                    self.content
+                        .replace_range(edit.offset..end, &edit.inserted);
+                }
+                self.cursor_loc = change.cursor_after;
+                true
+            }
+            None => false,
+        }
     }
 
-    /// move the cursor location to the left and remove the character
+    /// set the maximum number of changes which can be undone
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.history.set_depth(depth);
+    }
+
+    /// move the cursor location to the left and remove the grapheme
     /// on this new location
     fn backspace(&mut self) {
-        if self.content.len() > 0 && self.cursor_loc > 0 {
+        if self.cursor_loc > 0 {
             self.cursor_loc -= 1;
-            self.content.remove(self.cursor_loc);
+            self.remove_range(self.cursor_loc, self.cursor_loc + 1);
         }
     }
 
-    /// move the cursor 1 cell to the left
+    /// move the cursor 1 grapheme to the left
     fn left(&mut self) {
         if self.cursor_loc > 0 {
             self.cursor_loc -= 1;
         }
     }
 
-    /// move the cursor 1 cell to the right
+    /// move the cursor 1 grapheme to the right
     fn right(&mut self) {
-        if self.cursor_loc < self.content.len() {
+        if self.cursor_loc < self.grapheme_count() {
             self.cursor_loc += 1;
         }
     }
@@ -82,20 +223,26 @@ impl InputBuffer {
 
     /// move the cursor location to the end of the buffer
     fn end(&mut self) {
-        self.cursor_loc = self.content.len();
+        self.cursor_loc = self.grapheme_count();
     }
 
     /// set the cursor location on this buffer
     pub fn set_cursor_loc(&mut self, x: usize) {
-        if x < self.content.len() {
+        if x <= self.grapheme_count() {
             self.cursor_loc = x;
         }
     }
 
-    /// delete the first character to the right of the cursor
+    /// set the cursor location to the grapheme displayed at this column
+    pub fn set_cursor_column(&mut self, column: usize) {
+        self.history.seal();
+        self.cursor_loc = unicode::index_at_column(&self.content, column);
+    }
+
+    /// delete the first grapheme to the right of the cursor
     fn delete(&mut self) {
-        if self.cursor_loc < self.content.len() {
-            self.content.remove(self.cursor_loc);
+        if self.cursor_loc < self.grapheme_count() {
+            self.remove_range(self.cursor_loc, self.cursor_loc + 1);
         }
     }
 
@@ -109,17 +256,55 @@ impl InputBuffer {
     /// - Delete
     /// - Backspace
     /// - Char(char)
+    /// - Ctrl+Z, Ctrl+Y and Ctrl+Shift+Z for undo and redo
     pub fn process_key_event(
         &mut self,
-        KeyEvent { code, modifiers: _ }: KeyEvent,
+        KeyEvent { code, modifiers }: KeyEvent,
     ) {
-        match code {
-            KeyCode::Char(c) => {
+        let ctrl = modifiers.contains(KeyModifiers::CONTROL);
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+        let cursor_before = self.cursor_loc;
+        let kind = match code {
+            KeyCode::Char('z') | KeyCode::Char('Z') if ctrl && shift => {
+                self.redo();
+                return;
+            }
+            KeyCode::Char('z') if ctrl => {
+                self.undo();
+                return;
+            }
+            KeyCode::Char('y') if ctrl => {
+                self.redo();
+                return;
+            }
+            KeyCode::Char(c) if !ctrl => {
                 self.add_char(c);
+                ChangeKind::Typing
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                if code == KeyCode::Backspace {
+                    self.backspace();
+                } else {
+                    self.delete();
+                }
+                ChangeKind::Deleting
             }
-            KeyCode::Backspace => {
-                self.backspace();
+            _ => {
+                self.history.seal();
+                self.process_movement(code);
+                return;
             }
+        };
+        self.history.commit(kind, cursor_before, self.cursor_loc);
+        // a new word starts a new undo step
+        if code == KeyCode::Char(' ') {
+            self.history.seal();
+        }
+    }
+
+    /// move the cursor with the navigation keys
+    fn process_movement(&mut self, code: KeyCode) {
+        match code {
             KeyCode::Left => {
                 self.left();
             }
@@ -132,9 +317,6 @@ impl InputBuffer {
             KeyCode::End => {
                 self.end();
             }
-            KeyCode::Delete => {
-                self.delete();
-            }
             _ => (),
         }
     }
@@ -176,4 +358,69 @@ mod test {
         assert_eq!("abc", input1.content); // the string should be the same
         assert_eq!(3, input1.cursor_loc); // the cursor should now be on 3
     }
+
+    #[test]
+    fn wide_and_combined_characters() {
+        let mut input1 = InputBuffer::new_with_value("文字");
+        assert_eq!(input1.cursor_loc, 2);
+        assert_eq!(input1.get_cursor_column(), 4);
+        input1.left();
+        input1.add_char('😀');
+        assert_eq!("文😀字", input1.content);
+        assert_eq!(input1.get_cursor_column(), 4);
+        input1.end();
+        input1.add_char('e');
+        // the combining accent is merged with the e
+        input1.add_char('\u{301}');
+        assert_eq!(input1.cursor_loc, 4);
+        input1.backspace();
+        assert_eq!("文😀字", input1.content);
+        input1.set_cursor_column(3);
+        assert_eq!(input1.cursor_loc, 1);
+        input1.delete();
+        assert_eq!("文字", input1.content);
+    }
+    #[test]
+    fn undo_and_redo() {
+        let mut input1 = InputBuffer::new();
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        for c in "hello world".chars() {
+            input1.process_key_event(KeyCode::Char(c).into());
+        }
+        input1.process_key_event(KeyCode::Backspace.into());
+        input1.process_key_event(KeyCode::Backspace.into());
+        assert_eq!("hello wor", input1.content);
+        input1.process_key_event(ctrl('z'));
+        assert_eq!("hello world", input1.content);
+        input1.process_key_event(ctrl('z'));
+        assert_eq!("hello ", input1.content);
+        assert_eq!(6, input1.cursor_loc);
+        input1.process_key_event(ctrl('z'));
+        assert_eq!("", input1.content);
+        assert!(!input1.undo());
+        input1.process_key_event(ctrl('y'));
+        assert_eq!("hello ", input1.content);
+        input1.process_key_event(KeyEvent::new(
+            KeyCode::Char('Z'),
+            KeyModifiers::CONTROL | KeyModifiers::SHIFT,
+        ));
+        assert_eq!("hello world", input1.content);
+        assert_eq!(11, input1.cursor_loc);
+    }
+
+    #[test]
+    fn max_length() {
+        let mut input1 = InputBuffer::new_with_value("hello");
+        input1.set_max_length(Some(3));
+        assert_eq!("hel", input1.content);
+        assert_eq!(3, input1.cursor_loc);
+        input1.add_char('x');
+        assert_eq!("hel", input1.content);
+        // the combining accent does not add a grapheme
+        input1.add_char('\u{301}');
+        assert_eq!("hel\u{301}", input1.content);
+        input1.backspace();
+        input1.add_char('p');
+        assert_eq!("hep", input1.content);
+    }
 }
@@ -0,0 +1,239 @@
+//! Measure and slice text by grapheme clusters, and by the number
+//! of cells each of the grapheme occupies in the terminal.
+//! A grapheme cluster is what the user sees as one character,
+//! such as a letter with combining marks or an emoji sequence.
+use crate::symbol;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// the variation selector which requests an emoji presentation
+const EMOJI_PRESENTATION: char = '\u{fe0f}';
+
+/// return the number of cells the grapheme occupies.
+/// Emoji sequences joined together are displayed as one wide character,
+/// so a grapheme occupies at most 2 cells.
+pub(crate) fn grapheme_width(grapheme: &str) -> usize {
+    if grapheme.contains(EMOJI_PRESENTATION) {
+        2
+    } else {
+        grapheme.width().min(2)
+    }
+}
+
+/// iterate over the grapheme clusters of the text together with their width
+pub(crate) fn graphemes(s: &str) -> impl Iterator<Item = (&str, usize)> {
+    s.graphemes(true).map(|g| (g, grapheme_width(g)))
+}
+
+/// return the number of cells the text occupies
+pub(crate) fn str_width(s: &str) -> usize {
+    graphemes(s).map(|(_, width)| width).sum()
+}
+
+/// return the number of grapheme clusters in the text
+pub(crate) fn grapheme_count(s: &str) -> usize {
+    s.graphemes(true).count()
+}
+
+/// return the byte index of the grapheme at this index,
+/// the length of the text is returned when the index is at the end
+pub(crate) fn byte_index(s: &str, grapheme_index: usize) -> usize {
+    s.grapheme_indices(true)
+        .nth(grapheme_index)
+        .map(|(byte_index, _)| byte_index)
+        .unwrap_or_else(|| s.len())
+}
+
+/// return the column where the grapheme at this index starts
+pub(crate) fn column_of(s: &str, grapheme_index: usize) -> usize {
+    graphemes(s)
+        .take(grapheme_index)
+        .map(|(_, width)| width)
+        .sum()
+}
+
+/// return the index of the grapheme which occupies the column,
+/// the number of graphemes is returned when the column is past the text
+pub(crate) fn index_at_column(s: &str, column: usize) -> usize {
+    let mut x = 0;
+    for (i, (_, width)) in graphemes(s).enumerate() {
+        if column < x + width.max(1) {
+            return i;
+        }
+        x += width;
+    }
+    grapheme_count(s)
+}
+
+/// take the graphemes from the start of the text which fits in this width
+pub(crate) fn take_width(s: &str, max_width: usize) -> String {
+    let mut taken = String::new();
+    let mut width = 0;
+    for (grapheme, grapheme_width) in graphemes(s) {
+        if width + grapheme_width > max_width {
+            break;
+        }
+        taken.push_str(grapheme);
+        width += grapheme_width;
+    }
+    taken
+}
+
+/// shorten the text to fit in this width, an ellipsis is added
+/// at the end when the text is truncated
+pub(crate) fn truncate(s: &str, max_width: usize) -> String {
+    if str_width(s) <= max_width {
+        return s.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+    let mut truncated = take_width(s, max_width - 1).trim_end().to_string();
+    truncated.push(symbol::ELLIPSIS);
+    truncated
+}
+
+/// return the byte range of each of the words in the text,
+/// the words are separated by whitespace
+pub(crate) fn word_ranges(s: &str) -> Vec<Range<usize>> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, ch) in s.char_indices() {
+        match (ch.is_whitespace(), start) {
+            (true, Some(word_start)) => {
+                words.push(word_start..i);
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => (),
+        }
+    }
+    if let Some(word_start) = start {
+        words.push(word_start..s.len());
+    }
+    words
+}
+
+/// wrap the text into lines which fits in the width,
+/// the lines are broken at any grapheme.
+/// Returns the byte range of each line.
+pub(crate) fn wrap_chars(s: &str, width: usize) -> Vec<Range<usize>> {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut line_width = 0;
+    for (i, grapheme) in s.grapheme_indices(true) {
+        let gw = grapheme_width(grapheme);
+        if line_width + gw > width && i > start {
+            lines.push(start..i);
+            start = i;
+            line_width = 0;
+        }
+        line_width += gw;
+    }
+    lines.push(start..s.len());
+    lines
+}
+
+/// wrap the text into lines which fits in the width, the lines are broken
+/// in between words and the whitespace at the breaks are left out.
+/// Words which are wider than the width are broken at the graphemes.
+/// Returns the byte range of each line.
+pub(crate) fn wrap_words(s: &str, width: usize) -> Vec<Range<usize>> {
+    let mut lines = vec![];
+    let mut line: Option<Range<usize>> = None;
+    for word in word_ranges(s) {
+        if let Some(current) = line.as_mut() {
+            if str_width(&s[current.start..word.end]) <= width {
+                current.end = word.end;
+                continue;
+            }
+            lines.push(current.clone());
+        }
+        let mut pieces: Vec<Range<usize>> = wrap_chars(&s[word.clone()], width)
+            .into_iter()
+            .map(|piece| word.start + piece.start..word.start + piece.end)
+            .collect();
+        line = pieces.pop();
+        lines.extend(pieces);
+    }
+    lines.push(line.unwrap_or(0..0));
+    lines
+}
+
+/// the number of spaces in each of the gaps in between the words,
+/// so that the words fills the width. The leftmost gaps get the extra space.
+pub(crate) fn justify_gaps(
+    words_width: usize,
+    gaps: usize,
+    width: usize,
+) -> Vec<usize> {
+    let spaces = width.saturating_sub(words_width);
+    (0..gaps)
+        .map(|i| spaces / gaps + if i < spaces % gaps { 1 } else { 0 })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_of_wide_and_combined_characters() {
+        assert_eq!(str_width("abc"), 3);
+        assert_eq!(str_width("文字"), 4);
+        // e with a combining acute accent
+        assert_eq!(str_width("e\u{301}"), 1);
+        assert_eq!(grapheme_count("e\u{301}"), 1);
+        // family emoji joined with zero width joiners
+        assert_eq!(str_width("👨\u{200d}👩\u{200d}👧"), 2);
+        assert_eq!(str_width("❤\u{fe0f}"), 2);
+    }
+
+    #[test]
+    fn columns_and_indexes() {
+        let s = "a文😀b";
+        assert_eq!(column_of(s, 0), 0);
+        assert_eq!(column_of(s, 2), 3);
+        assert_eq!(column_of(s, 4), 6);
+        assert_eq!(index_at_column(s, 2), 1);
+        assert_eq!(index_at_column(s, 4), 2);
+        assert_eq!(index_at_column(s, 5), 3);
+        assert_eq!(index_at_column(s, 10), 4);
+        assert_eq!(byte_index(s, 2), 4);
+        assert_eq!(byte_index(s, 4), s.len());
+    }
+
+    #[test]
+    fn truncate_with_ellipsis() {
+        assert_eq!(truncate("hello", 5), "hello");
+        assert_eq!(truncate("hello world", 6), "hello…");
+        // the wide character does not fit before the ellipsis
+        assert_eq!(truncate("ab文字", 4), "ab…");
+        assert_eq!(truncate("文字", 0), "");
+    }
+
+    fn lines<'a>(s: &'a str, ranges: Vec<Range<usize>>) -> Vec<&'a str> {
+        ranges.into_iter().map(|range| &s[range]).collect()
+    }
+
+    #[test]
+    fn wrap_words_and_chars() {
+        let s = "the quick  brown fox";
+        assert_eq!(lines(s, wrap_words(s, 10)), vec!["the quick", "brown fox"]);
+        assert_eq!(lines(s, wrap_words(s, 12)), vec!["the quick", "brown fox"]);
+        let s = "abcdefgh ij";
+        assert_eq!(lines(s, wrap_words(s, 3)), vec!["abc", "def", "gh", "ij"]);
+        assert_eq!(lines("", wrap_words("", 3)), vec![""]);
+        let s = "文字文字";
+        assert_eq!(lines(s, wrap_chars(s, 5)), vec!["文字", "文字"]);
+        let s = "ab😀c";
+        assert_eq!(lines(s, wrap_chars(s, 3)), vec!["ab", "😀c"]);
+    }
+
+    #[test]
+    fn justify_fills_the_width() {
+        assert_eq!(justify_gaps(3, 2, 8), vec![3, 2]);
+        assert_eq!(justify_gaps(8, 0, 8), Vec::<usize>::new());
+    }
+}
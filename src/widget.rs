@@ -1,7 +1,12 @@
 use crate::crossterm;
 use crate::Event;
+use crate::Value;
 use crate::{buffer::Buffer, Cmd};
 pub use button::Button;
+pub use chart::{
+    BarChart, LineChart, LinePlot, PlotChart, PlotKind, ScatterChart,
+    ScatterPlot, Series,
+};
 pub use checkbox::Checkbox;
 use expanse::geometry::Size;
 use expanse::number::Number;
@@ -11,31 +16,52 @@ use expanse::{
     style::Style,
 };
 pub use flex_box::FlexBox;
+pub use form::Form;
+pub use gauge::Gauge;
 pub use group_box::GroupBox;
-pub use image_control::Image;
+pub(crate) use image_control::delete_kitty;
+pub use image_control::{Image, ImageFit, ImageRenderMode};
 pub use link::Link;
 pub use list_box::ListBox;
+pub use log_view::LogView;
+pub use number_input::NumberInput;
+pub use progress_bar::ProgressBar;
 pub use radio::Radio;
+pub use radio_group::RadioGroup;
 pub use slider::Slider;
+pub use sparkline::Sparkline;
 use std::fmt;
+pub use svg::Svg;
 pub use tab_box::TabBox;
 pub use text_area::TextArea;
-pub use text_input::TextInput;
-pub use text_label::TextLabel;
+pub use text_input::{HistoryStore, TextInput};
+pub use text_label::{Alignment, TextLabel, VerticalAlignment, Wrap};
+pub use tree_view::{TreeNode, TreeView};
 
 mod button;
+mod chart;
 mod checkbox;
 mod flex_box;
+mod form;
+mod gauge;
 mod group_box;
 mod image_control;
 mod link;
 mod list_box;
+mod log_view;
+mod number_input;
+mod progress;
+mod progress_bar;
 mod radio;
+mod radio_group;
 mod slider;
+mod sparkline;
+mod svg;
 mod tab_box;
 mod text_area;
 mod text_input;
 mod text_label;
+mod tree_view;
 
 /// All widgets must implement the Widget trait
 pub trait Widget<MSG>
@@ -102,6 +128,34 @@ where
     /// set the widget as focused
     fn set_focused(&mut self, _focused: bool) {}
 
+    /// whether this widget uses the key combination which the renderer
+    /// would otherwise use to quit, such as CTRL-c to copy the selected text,
+    /// or the Enter which `Form` would otherwise use to submit.
+    /// This is only asked when the widget is focused.
+    fn captures_key(&self, _key_event: &crossterm::event::KeyEvent) -> bool {
+        false
+    }
+
+    /// whether the style of this widget is changed by the layout that was
+    /// just set, such as the height of the text wrapped to the laid out width
+    fn needs_relayout(&self) -> bool {
+        false
+    }
+
+    /// whether this widget or any of its descendants needs another layout pass
+    fn needs_relayout_widget(&self) -> bool {
+        self.needs_relayout()
+            || self.children().is_some_and(|children| {
+                children.iter().any(|child| child.needs_relayout_widget())
+            })
+    }
+
+    /// the value entered into this widget, containers such as `Form`
+    /// use it to collect the values of their fields
+    fn value(&self) -> Option<Value> {
+        None
+    }
+
     /// set the size of the widget
     fn set_size(&mut self, width: Option<f32>, height: Option<f32>);
 
@@ -206,6 +260,15 @@ where
     /// calculate the layout of the nodes utilizing the styles set on each of the widget
     /// and its children widget styles
     fn compute_node_layout(&mut self, parent_size: Size<Number>) {
+        self.compute_node_layout_pass(parent_size);
+        // the style of some widgets depends on their layout, so the layout
+        // is computed once more when the new layout changed their style
+        if self.needs_relayout_widget() {
+            self.compute_node_layout_pass(parent_size);
+        }
+    }
+
+    fn compute_node_layout_pass(&mut self, parent_size: Size<Number>) {
         let mut stretch = Stretch::new();
         let stretch_node = self
             .build_stretch_node_recursive(&mut stretch)
@@ -293,6 +356,20 @@ where
         }
     }
 
+    /// this is called periodically by the renderer when a tick rate is set,
+    /// widgets with animation update their state here
+    fn tick(&mut self) {}
+
+    /// call tick on this widget and all of its descendants
+    fn tick_widget(&mut self) {
+        self.tick();
+        if let Some(children) = self.children_mut() {
+            for child in children {
+                child.as_mut().tick_widget();
+            }
+        }
+    }
+
     fn draw_widget(&self, buf: &mut Buffer) -> crossterm::Result<Vec<Cmd>> {
         let mut cmds = self.draw(buf);
         if let Some(children) = self.children() {
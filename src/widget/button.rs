@@ -1,9 +1,6 @@
 use crate::Callback;
 use crate::Event;
-use crate::{
-    buffer::{Buffer, Cell},
-    Cmd, Widget,
-};
+use crate::{buffer::Buffer, Cmd, StyledText, Widget};
 use expanse::{
     geometry::{Rect, Size},
     result::Layout,
@@ -17,7 +14,7 @@ use std::{fmt, fmt::Debug};
 #[derive(PartialEq, Clone)]
 pub struct Button<MSG> {
     layout: Option<Layout>,
-    label: String,
+    label: StyledText,
     is_rounded: bool,
     width: Option<f32>,
     height: Option<f32>,
@@ -30,7 +27,7 @@ impl<MSG> Default for Button<MSG> {
     fn default() -> Self {
         Button {
             layout: None,
-            label: String::new(),
+            label: StyledText::new(),
             is_rounded: false,
             width: None,
             height: None,
@@ -57,7 +54,7 @@ impl<MSG> Button<MSG> {
         S: ToString,
     {
         Button {
-            label: label.to_string(),
+            label: StyledText::from(label.to_string()),
             is_rounded: true,
             ..Default::default()
         }
@@ -65,7 +62,12 @@ impl<MSG> Button<MSG> {
 
     /// set the label of the button
     pub fn set_label<S: ToString>(&mut self, label: S) {
-        self.label = label.to_string();
+        self.label = StyledText::from(label.to_string());
+    }
+
+    /// set the label of the button with a text which has colors and attributes
+    pub fn set_styled_label<T: Into<StyledText>>(&mut self, label: T) {
+        self.label = label.into();
     }
 
     /// set to use a rounded border
@@ -125,8 +127,9 @@ impl<MSG> Widget<MSG> for Button<MSG> {
                 },
             },
             min_size: Size {
+                // the label and the left and right borders
+                width: Dimension::Points((self.label.width() + 2) as f32),
                 height: Dimension::Points(3.0),
-                ..Default::default()
             },
             border: Rect {
                 top: Dimension::Points(self.border_top()),
@@ -166,12 +169,10 @@ impl<MSG> Widget<MSG> for Button<MSG> {
         canvas.draw_rect((left, top), (right, bottom), border);
         buf.write_canvas(canvas);
 
-        for (t, ch) in self.label.chars().enumerate() {
-            let mut cell = Cell::new(ch);
-            if self.focused {
-                cell.bold();
-            }
-            buf.set_cell(loc_x + 1 + t, loc_y + 1, cell);
+        if self.focused {
+            buf.write_bold_styled(loc_x + 1, loc_y + 1, &self.label);
+        } else {
+            buf.write_styled(loc_x + 1, loc_y + 1, &self.label);
         }
 
         vec![]
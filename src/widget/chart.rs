@@ -0,0 +1,581 @@
+//! Charts which plot numeric series, the axes are automatically scaled
+//! to fit the data of all the series.
+use crate::buffer::{Buffer, Cell};
+use crate::crossterm::style::Color;
+use crate::unicode;
+use expanse::result::Layout;
+use ito_canvas::dot_canvas::{Context, Line, Shape};
+use unicode_width::UnicodeWidthStr;
+
+pub use bar_chart::BarChart;
+pub use plot_chart::{
+    LineChart, LinePlot, PlotChart, PlotKind, ScatterChart, ScatterPlot,
+};
+
+mod bar_chart;
+mod plot_chart;
+
+/// used in the legend to show the color of the series
+const LEGEND_SYMBOL: char = '■';
+
+/// the colors used for the series which has no color set
+const PALETTE: [Color; 6] = [
+    Color::Cyan,
+    Color::Magenta,
+    Color::Yellow,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+];
+
+/// A named series of data points plotted in a chart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    name: String,
+    points: Vec<(f64, f64)>,
+    color: Option<Color>,
+}
+
+impl Series {
+    /// create a new series with x and y points
+    pub fn new<S: ToString>(name: S, points: Vec<(f64, f64)>) -> Self {
+        Series {
+            name: name.to_string(),
+            points,
+            color: None,
+        }
+    }
+
+    /// create a new series from values, the x of each value is its index
+    pub fn from_values<S: ToString>(name: S, values: Vec<f64>) -> Self {
+        let points = values
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| (i as f64, v))
+            .collect();
+        Series::new(name, points)
+    }
+
+    /// set the color used to draw this series
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+
+    /// add a data point to this series
+    pub fn push(&mut self, x: f64, y: f64) {
+        self.points.push((x, y));
+    }
+
+    /// return the name of this series
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// return the data points of this series
+    pub fn points(&self) -> &[(f64, f64)] {
+        &self.points
+    }
+
+    fn color_or_default(&self, index: usize) -> Color {
+        self.color.unwrap_or(PALETTE[index % PALETTE.len()])
+    }
+}
+
+/// An axis with bounds and ticks rounded to nice numbers
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Axis {
+    min: f64,
+    max: f64,
+    ticks: Vec<f64>,
+    /// the number of decimal places used in the tick labels
+    precision: usize,
+}
+
+/// round the number to 1, 2, 5 or 10 times a power of 10
+fn nice_number(x: f64, round: bool) -> f64 {
+    let exp = x.log10().floor();
+    let fraction = x / 10f64.powf(exp);
+    let nice_fraction = if round {
+        if fraction < 1.5 {
+            1.0
+        } else if fraction < 3.0 {
+            2.0
+        } else if fraction < 7.0 {
+            5.0
+        } else {
+            10.0
+        }
+    } else if fraction <= 1.0 {
+        1.0
+    } else if fraction <= 2.0 {
+        2.0
+    } else if fraction <= 5.0 {
+        5.0
+    } else {
+        10.0
+    };
+    nice_fraction * 10f64.powf(exp)
+}
+
+impl Axis {
+    /// create an axis which covers the min and max with at most `max_ticks`
+    pub(crate) fn auto(min: f64, max: f64, max_ticks: usize) -> Self {
+        let (min, max) = if (max - min).abs() < f64::EPSILON {
+            (min - 1.0, max + 1.0)
+        } else {
+            (min, max)
+        };
+        let max_ticks = max_ticks.max(2);
+        let range = nice_number(max - min, false);
+        let step = nice_number(range / (max_ticks - 1) as f64, true);
+        let nice_min = (min / step).floor() * step;
+        let nice_max = (max / step).ceil() * step;
+        let count = ((nice_max - nice_min) / step).round() as usize + 1;
+        let ticks = (0..count).map(|i| nice_min + i as f64 * step).collect();
+        let precision = if step >= 1.0 {
+            0
+        } else {
+            -step.log10().floor() as usize
+        };
+        Axis {
+            min: nice_min,
+            max: nice_max,
+            ticks,
+            precision,
+        }
+    }
+
+    /// return the formatted label of this value
+    pub(crate) fn label(&self, value: f64) -> String {
+        let label = format!("{:.*}", self.precision, value);
+        // avoid displaying negative zero
+        if label.starts_with('-')
+            && label[1..].chars().all(|c| c == '0' || c == '.')
+        {
+            label[1..].to_string()
+        } else {
+            label
+        }
+    }
+
+    /// return the value normalized to 0.0..=1.0 within the bounds of this axis
+    pub(crate) fn normalize(&self, value: f64) -> f64 {
+        ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    fn label_width(&self) -> usize {
+        self.ticks
+            .iter()
+            .map(|tick| self.label(*tick).width())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The location of the plotting area of the chart, excluding the axes and the legend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Frame {
+    pub(crate) left: usize,
+    pub(crate) top: usize,
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+}
+
+impl Frame {
+    /// calculate the plotting area of the chart with the y axis labels on the left
+    /// and the x axis labels on the bottom
+    pub(crate) fn new(
+        layout: &Layout,
+        y_axis: &Axis,
+        has_legend: bool,
+    ) -> Option<Self> {
+        let loc_x = layout.location.x.round() as usize;
+        let loc_y = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        let height = layout.size.height.round() as usize;
+        let legend_height = if has_legend { 1 } else { 0 };
+        // y labels, and the y axis line
+        let left = loc_x + y_axis.label_width() + 1;
+        let top = loc_y + legend_height;
+        // the x axis line and x labels
+        let bottom = (loc_y + height).checked_sub(2)?;
+        let right = loc_x + width;
+        if left >= right || top >= bottom {
+            return None;
+        }
+        Some(Frame {
+            left,
+            top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+
+    /// the row where the y value is located
+    pub(crate) fn row_of(&self, y_axis: &Axis, value: f64) -> usize {
+        let offset = (1.0 - y_axis.normalize(value)) * (self.height - 1) as f64;
+        self.top + offset.round() as usize
+    }
+
+    /// the column where the x value is located
+    pub(crate) fn column_of(&self, x_axis: &Axis, value: f64) -> usize {
+        let offset = x_axis.normalize(value) * (self.width - 1) as f64;
+        self.left + offset.round() as usize
+    }
+
+    /// the row of the x axis line
+    fn axis_row(&self) -> usize {
+        self.top + self.height
+    }
+
+    /// draw the y axis line with its tick labels and the x axis line
+    pub(crate) fn draw_axes(&self, buf: &mut Buffer, y_axis: &Axis) {
+        let axis_x = self.left - 1;
+        for j in self.top..self.axis_row() {
+            buf.set_symbol(axis_x, j, '│');
+        }
+        buf.set_symbol(axis_x, self.axis_row(), '└');
+        for i in self.left..self.left + self.width {
+            buf.set_symbol(i, self.axis_row(), '─');
+        }
+        let mut last_row = None;
+        for tick in y_axis.ticks.iter() {
+            let row = self.row_of(y_axis, *tick);
+            if last_row == Some(row) {
+                continue;
+            }
+            last_row = Some(row);
+            let label = y_axis.label(*tick);
+            buf.set_symbol(axis_x, row, '┤');
+            buf.write_str(axis_x - label.width(), row, label);
+        }
+    }
+
+    /// draw the labels centered below the columns of the x axis,
+    /// labels that would overlap the previous label are skipped
+    pub(crate) fn draw_x_labels(
+        &self,
+        buf: &mut Buffer,
+        labels: &[(usize, String)],
+    ) {
+        let label_row = self.axis_row() + 1;
+        let right = self.left + self.width;
+        let mut next_free = 0;
+        for (column, label) in labels.iter() {
+            let label_width = label.width();
+            let start = column.saturating_sub(label_width / 2);
+            if start < next_free || start + label_width > right {
+                continue;
+            }
+            buf.set_symbol(*column, self.axis_row(), '┬');
+            buf.write_str(start, label_row, label);
+            next_free = start + label_width + 1;
+        }
+    }
+
+    /// draw the x axis with tick labels from a numeric axis
+    pub(crate) fn draw_x_axis(&self, buf: &mut Buffer, x_axis: &Axis) {
+        let labels: Vec<(usize, String)> = x_axis
+            .ticks
+            .iter()
+            .map(|tick| (self.column_of(x_axis, *tick), x_axis.label(*tick)))
+            .collect();
+        self.draw_x_labels(buf, &labels);
+    }
+}
+
+/// the number of ticks that fits in the axis length, keeping some space in between
+pub(crate) fn max_ticks(length: usize, spacing: usize) -> usize {
+    (length / spacing + 1).clamp(2, 10)
+}
+
+/// draw the name of each series with its color at the top of the chart
+pub(crate) fn draw_legend(
+    buf: &mut Buffer,
+    layout: &Layout,
+    series: &[Series],
+) {
+    let mut x = layout.location.x.round() as usize;
+    let y = layout.location.y.round() as usize;
+    for (i, s) in series.iter().enumerate() {
+        let mut cell = Cell::new(LEGEND_SYMBOL);
+        cell.color(s.color_or_default(i));
+        buf.set_cell(x, y, cell);
+        buf.write_str(x + 2, y, &s.name);
+        x += unicode::str_width(&s.name) + 4;
+    }
+}
+
+/// whether the legend is shown, which is when any of the series has a name
+pub(crate) fn has_legend(series: &[Series]) -> bool {
+    series.iter().any(|s| !s.name.is_empty())
+}
+
+/// the bounds of the x and y values of all the series
+pub(crate) fn bounds(series: &[Series]) -> Option<((f64, f64), (f64, f64))> {
+    let mut points = series.iter().flat_map(|s| s.points.iter());
+    let (x, y) = points.next()?;
+    Some(
+        points.fold(((*x, *x), (*y, *y)), |((x0, x1), (y0, y1)), (x, y)| {
+            ((x0.min(*x), x1.max(*x)), (y0.min(*y), y1.max(*y)))
+        }),
+    )
+}
+
+/// points plotted as braille dots
+struct Dots(Vec<(f32, f32)>);
+
+impl<'a> Shape<'a> for Dots {
+    fn points(&'a self) -> Box<dyn Iterator<Item = (f32, f32)> + 'a> {
+        Box::new(self.0.iter().copied())
+    }
+}
+
+/// plot the series into the frame using braille characters, which has
+/// 2x4 dots per cell. Dots of different series in the same cell are merged,
+/// the cell will have the color of the last series.
+pub(crate) fn plot_braille(
+    buf: &mut Buffer,
+    frame: &Frame,
+    x_axis: &Axis,
+    y_axis: &Axis,
+    series: &[Series],
+    draw_lines: bool,
+) {
+    let mut grid: Vec<Vec<Option<(u32, Color)>>> =
+        vec![vec![None; frame.width]; frame.height];
+    // the dot canvas uses a unit of 2 cells horizontally and 1 cell vertically
+    let canvas_width = frame.width as f32 / 2.0;
+    let canvas_height = frame.height as f32;
+    // keep the points inside of the canvas, as the max bounds are exclusive
+    let to_canvas = |(x, y): (f64, f64)| {
+        let cx = x_axis.normalize(x) as f32 * canvas_width;
+        let cy = (1.0 - y_axis.normalize(y)) as f32 * canvas_height;
+        (cx.min(canvas_width - 0.01), cy.min(canvas_height - 0.01))
+    };
+
+    for (index, s) in series.iter().enumerate() {
+        let mut context = Context::new(canvas_width, canvas_height);
+        let points: Vec<(f32, f32)> =
+            s.points.iter().copied().map(to_canvas).collect();
+        if draw_lines {
+            for pair in points.windows(2) {
+                let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+                context.draw(&Line::new(x1, y1, x2, y2));
+            }
+        }
+        context.draw(&Dots(points));
+        let color = s.color_or_default(index);
+        for (j, line) in context.to_string().lines().enumerate() {
+            for (i, ch) in line.chars().enumerate() {
+                if ch == ' ' {
+                    continue;
+                }
+                if let Some(cell) =
+                    grid.get_mut(j).and_then(|row| row.get_mut(i))
+                {
+                    let dots = ch as u32 - 0x2800;
+                    let merged = cell.map(|(d, _)| d).unwrap_or(0) | dots;
+                    *cell = Some((merged, color));
+                }
+            }
+        }
+    }
+
+    for (j, row) in grid.iter().enumerate() {
+        for (i, cell) in row.iter().enumerate() {
+            if let Some((dots, color)) = cell {
+                let ch = std::char::from_u32(0x2800 + dots)
+                    .expect("must be a braille character");
+                let mut cell = Cell::new(ch);
+                cell.color(*color);
+                buf.set_cell(frame.left + i, frame.top + j, cell);
+            }
+        }
+    }
+}
+
+/// draw the axes, legend and the plotted series,
+/// the points are connected with lines when `draw_lines` is true
+pub(crate) fn draw_plot(
+    buf: &mut Buffer,
+    layout: &Layout,
+    series: &[Series],
+    draw_lines: bool,
+) {
+    let ((x_min, x_max), (y_min, y_max)) =
+        bounds(series).unwrap_or(((0.0, 1.0), (0.0, 1.0)));
+    let width = layout.size.width.round() as usize;
+    let height = layout.size.height.round() as usize;
+    let has_legend = has_legend(series);
+    let y_axis = Axis::auto(y_min, y_max, max_ticks(height, 3));
+    let frame = match Frame::new(layout, &y_axis, has_legend) {
+        Some(frame) => frame,
+        None => return,
+    };
+    let x_axis = Axis::auto(x_min, x_max, max_ticks(width, 8));
+    if has_legend {
+        draw_legend(buf, layout, series);
+    }
+    frame.draw_axes(buf, &y_axis);
+    frame.draw_x_axis(buf, &x_axis);
+    plot_braille(buf, &frame, &x_axis, &y_axis, series, draw_lines);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Widget;
+    use expanse::{geometry::Size, number::Number};
+
+    fn draw<W: Widget<()>>(
+        widget: &mut W,
+        width: usize,
+        height: usize,
+    ) -> Vec<String> {
+        widget.compute_node_layout(Size {
+            width: Number::Defined(width as f32),
+            height: Number::Defined(height as f32),
+        });
+        let mut buf = Buffer::new(width, height);
+        widget.draw(&mut buf);
+        buf.cells
+            .iter()
+            .map(|line| line.iter().map(|cell| cell.symbol.clone()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn bar_chart_groups_the_bars_of_each_category() {
+        let mut chart = BarChart::<()>::new(vec!["Jan".into(), "Feb".into()]);
+        chart.add_series(Series::from_values("a", vec![10.0, 5.0]));
+        chart.add_series(Series::from_values("b", vec![3.0, 8.0]));
+        assert_eq!(
+            draw(&mut chart, 16, 8),
+            vec![
+                "■ a  ■ b        ",
+                "10┤ ██          ",
+                "  │ ██     ██   ",
+                " 5┤ ██   ▄▄██   ",
+                "  │ ██▄▄ ████   ",
+                " 0┤ ████ ████   ",
+                "  └───┬────┬────",
+                "     Jan  Feb   ",
+            ]
+        );
+    }
+
+    #[test]
+    fn line_chart_connects_the_points() {
+        let mut chart = LineChart::<()>::new(vec![Series::from_values(
+            "cpu",
+            vec![0.0, 10.0, 0.0],
+        )]);
+        assert_eq!(
+            draw(&mut chart, 16, 8),
+            vec![
+                "■ cpu           ",
+                "10┤     ⡰⠙⡄     ",
+                "  │   ⢀⠜  ⠈⢆    ",
+                " 5┤  ⢠⠊     ⠣⡀  ",
+                "  │ ⡰⠁       ⠑⡄ ",
+                " 0┤⡜          ⠈⢆",
+                "  └┬─────┬─────┬",
+                "   0     1     2",
+            ]
+        );
+    }
+
+    #[test]
+    fn scatter_chart_plots_only_the_points() {
+        let mut chart = ScatterChart::<()>::new(vec![Series::from_values(
+            "",
+            vec![0.0, 10.0, 0.0],
+        )]);
+        // no legend is shown when the series has no name
+        assert_eq!(
+            draw(&mut chart, 16, 8),
+            vec![
+                "10┤      ⠈      ",
+                "  │             ",
+                "  │             ",
+                " 5┤             ",
+                "  │             ",
+                " 0┤⡀           ⢀",
+                "  └┬─────┬─────┬",
+                "   0     1     2",
+            ]
+        );
+    }
+
+    #[test]
+    fn legend_has_the_color_of_each_series() {
+        let mut red = Series::from_values("red", vec![1.0, 2.0]);
+        red.set_color(Color::Red);
+        let mut chart = LineChart::<()>::new(vec![
+            red,
+            Series::from_values("default", vec![2.0, 1.0]),
+        ]);
+        let mut buf = Buffer::new(24, 8);
+        chart.compute_node_layout(Size {
+            width: Number::Defined(24.0),
+            height: Number::Defined(8.0),
+        });
+        chart.draw(&mut buf);
+        let legend: String = buf.cells[0]
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect();
+        assert_eq!(legend, "■ red  ■ default        ");
+        assert_eq!(buf.cells[0][0].foreground_color, Some(Color::Red));
+        // the second series uses the second color of the palette
+        assert_eq!(buf.cells[0][7].foreground_color, Some(PALETTE[1]));
+    }
+
+    #[test]
+    fn tick_labels_fit_in_the_chart() {
+        let mut chart = LineChart::<()>::new(vec![Series::new(
+            "",
+            vec![(0.0, 0.5), (1000.0, 1.5)],
+        )]);
+        let lines = draw(&mut chart, 20, 8);
+        // the decimal places of the y labels follow the step of the axis
+        assert!(lines[0].starts_with("1.5┤"));
+        assert!(lines[3].starts_with("1.0┤"));
+        assert!(lines[5].starts_with("0.5┤"));
+        // the last x label doesn't fit at the right edge, so it is skipped
+        assert_eq!(lines[6], "   └┬───────┬───────");
+        assert_eq!(lines[7], "    0      500      ");
+    }
+
+    #[test]
+    fn nice_axis() {
+        let axis = Axis::auto(0.3, 9.2, 5);
+        assert_eq!(axis.min, 0.0);
+        assert_eq!(axis.max, 10.0);
+        assert_eq!(axis.ticks, vec![0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+        assert_eq!(axis.label(4.0), "4");
+
+        let axis = Axis::auto(-0.04, 0.13, 4);
+        assert!((axis.min + 0.05).abs() < 1e-9);
+        assert!((axis.max - 0.15).abs() < 1e-9);
+        assert_eq!(axis.ticks.len(), 5);
+        assert_eq!(axis.label(axis.ticks[1]), "0.00");
+    }
+
+    #[test]
+    fn flat_series_has_a_range() {
+        let axis = Axis::auto(3.0, 3.0, 3);
+        assert!(axis.min < 3.0);
+        assert!(axis.max > 3.0);
+    }
+
+    #[test]
+    fn bounds_of_series() {
+        let series = vec![
+            Series::from_values("a", vec![1.0, 5.0, -2.0]),
+            Series::new("b", vec![(10.0, 3.0)]),
+        ];
+        assert_eq!(bounds(&series), Some(((0.0, 10.0), (-2.0, 5.0))));
+    }
+}
@@ -0,0 +1,229 @@
+use super::{draw_legend, has_legend, max_ticks, Axis, Frame, Series};
+use crate::crossterm::style::Color;
+use crate::{
+    buffer::{Buffer, Cell},
+    symbol::bar,
+    Cmd, Widget,
+};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use std::{fmt, marker::PhantomData};
+
+/// A vertical bar chart, where each value of the series is drawn as a bar.
+/// Bars of multiple series are grouped together by their category.
+/// Bars are drawn from zero, negative values are not displayed.
+///```ignore
+///   ■ 2019  ■ 2020
+///  10┤    ▆
+///   5┤ █▃ █▄
+///   0└─┬───┬──
+///     Jan Feb
+/// ```
+pub struct BarChart<MSG> {
+    layout: Option<Layout>,
+    categories: Vec<String>,
+    series: Vec<Series>,
+    bar_width: usize,
+    /// the space in between the group of bars
+    group_gap: usize,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    _phantom_msg: PhantomData<MSG>,
+}
+
+impl<MSG> Default for BarChart<MSG> {
+    fn default() -> Self {
+        BarChart {
+            layout: None,
+            categories: vec![],
+            series: vec![],
+            bar_width: 2,
+            group_gap: 1,
+            width: None,
+            height: None,
+            id: None,
+            _phantom_msg: PhantomData,
+        }
+    }
+}
+
+impl<MSG> BarChart<MSG> {
+    /// create a new bar chart with the labels of each category
+    pub fn new(categories: Vec<String>) -> Self {
+        BarChart {
+            categories,
+            ..Default::default()
+        }
+    }
+
+    /// add a series to this bar chart, the y of each point is
+    /// the value of the category at the same index
+    pub fn add_series(&mut self, series: Series) {
+        self.series.push(series);
+    }
+
+    /// remove all the series of this bar chart
+    pub fn clear_series(&mut self) {
+        self.series.clear();
+    }
+
+    /// set the category labels of this bar chart
+    pub fn set_categories(&mut self, categories: Vec<String>) {
+        self.categories = categories;
+    }
+
+    /// set the width of each bar in cells, default is 2
+    pub fn set_bar_width(&mut self, bar_width: usize) {
+        self.bar_width = bar_width.max(1);
+    }
+
+    /// set the space in between the group of bars, default is 1
+    pub fn set_group_gap(&mut self, group_gap: usize) {
+        self.group_gap = group_gap;
+    }
+
+    fn category_count(&self) -> usize {
+        self.series
+            .iter()
+            .map(|s| s.points().len())
+            .max()
+            .unwrap_or(0)
+            .max(self.categories.len())
+    }
+
+    fn y_axis(&self, height: usize) -> Axis {
+        let max = self
+            .series
+            .iter()
+            .flat_map(|s| s.points().iter().map(|(_, y)| *y))
+            .fold(0.0, f64::max);
+        Axis::auto(0.0, max, max_ticks(height, 3))
+    }
+
+    /// draw a single bar from the bottom of the frame
+    fn draw_bar(
+        &self,
+        buf: &mut Buffer,
+        frame: &Frame,
+        y_axis: &Axis,
+        left: usize,
+        value: f64,
+        color: Color,
+    ) {
+        let filled =
+            (y_axis.normalize(value) * (frame.height * 8) as f64).round();
+        let filled = filled as usize;
+        let right = (left + self.bar_width).min(frame.left + frame.width);
+        for j in 0..frame.height {
+            let eighths = filled.saturating_sub(j * 8).min(8);
+            if eighths == 0 {
+                break;
+            }
+            for x in left..right {
+                let mut cell = Cell::new(bar::eighths(eighths));
+                cell.color(color);
+                buf.set_cell(x, frame.top + frame.height - 1 - j, cell);
+            }
+        }
+    }
+}
+
+impl<MSG> Widget<MSG> for BarChart<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        let has_legend = has_legend(&self.series);
+        let y_axis = self.y_axis(layout.size.height.round() as usize);
+        let frame = match Frame::new(&layout, &y_axis, has_legend) {
+            Some(frame) => frame,
+            None => return vec![],
+        };
+        if has_legend {
+            draw_legend(buf, &layout, &self.series);
+        }
+        frame.draw_axes(buf, &y_axis);
+
+        let group_width = self.series.len() * self.bar_width;
+        let mut labels = vec![];
+        for category in 0..self.category_count() {
+            let group_left =
+                frame.left + 1 + category * (group_width + self.group_gap);
+            if group_left >= frame.left + frame.width {
+                break;
+            }
+            for (index, s) in self.series.iter().enumerate() {
+                if let Some((_, value)) = s.points().get(category) {
+                    self.draw_bar(
+                        buf,
+                        &frame,
+                        &y_axis,
+                        group_left + index * self.bar_width,
+                        *value,
+                        s.color_or_default(index),
+                    );
+                }
+            }
+            if let Some(label) = self.categories.get(category) {
+                labels.push((group_left + group_width / 2, label.to_string()));
+            }
+        }
+        frame.draw_x_labels(buf, &labels);
+        vec![]
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for BarChart<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BarChart")
+            .field("categories", &self.categories)
+            .field("series", &self.series)
+            .field("id", &self.id)
+            .finish()
+    }
+}
@@ -0,0 +1,172 @@
+use super::{draw_plot, Series};
+use crate::{buffer::Buffer, Cmd, Widget};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use std::{fmt, marker::PhantomData};
+
+/// A line chart where the points of each series are connected with lines.
+/// The lines are drawn with braille characters which has 2x4 dots per cell.
+///```ignore
+///   ■ cpu
+///  10┤   ⡠⠊⠉⠢⡀
+///   5┤ ⡠⠊     ⠈⠢⡀
+///   0└─┬────┬────┬
+///      0    5   10
+/// ```
+pub type LineChart<MSG> = PlotChart<MSG, LinePlot>;
+
+/// A scatter chart where each point of the series is plotted as a dot.
+/// The dots are drawn with braille characters which has 2x4 dots per cell.
+///```ignore
+///   ■ samples
+///  10┤  ⠁ ⠈   ⠂
+///   5┤ ⠂  ⠐ ⠁
+///   0└─┬────┬────┬
+///      0    5   10
+/// ```
+pub type ScatterChart<MSG> = PlotChart<MSG, ScatterPlot>;
+
+/// How the points of the series are plotted in a `PlotChart`
+pub trait PlotKind {
+    /// the name of the chart, used in the debug output
+    const NAME: &'static str;
+    /// whether the points of each series are connected with lines
+    const DRAW_LINES: bool;
+}
+
+/// The points of each series are connected with lines
+#[derive(Debug)]
+pub struct LinePlot;
+
+/// Each point of the series is plotted as a dot
+#[derive(Debug)]
+pub struct ScatterPlot;
+
+impl PlotKind for LinePlot {
+    const NAME: &'static str = "LineChart";
+    const DRAW_LINES: bool = true;
+}
+
+impl PlotKind for ScatterPlot {
+    const NAME: &'static str = "ScatterChart";
+    const DRAW_LINES: bool = false;
+}
+
+/// A chart which plots the x and y points of the series,
+/// use the `LineChart` or `ScatterChart`
+pub struct PlotChart<MSG, K> {
+    layout: Option<Layout>,
+    series: Vec<Series>,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    _phantom_msg: PhantomData<MSG>,
+    _phantom_kind: PhantomData<K>,
+}
+
+impl<MSG, K> Default for PlotChart<MSG, K> {
+    fn default() -> Self {
+        PlotChart {
+            layout: None,
+            series: vec![],
+            width: None,
+            height: None,
+            id: None,
+            _phantom_msg: PhantomData,
+            _phantom_kind: PhantomData,
+        }
+    }
+}
+
+impl<MSG, K> PlotChart<MSG, K> {
+    /// create a new chart with the series
+    pub fn new(series: Vec<Series>) -> Self {
+        PlotChart {
+            series,
+            ..Default::default()
+        }
+    }
+
+    /// add a series to this chart
+    pub fn add_series(&mut self, series: Series) {
+        self.series.push(series);
+    }
+
+    /// remove all the series of this chart
+    pub fn clear_series(&mut self) {
+        self.series.clear();
+    }
+
+    /// return a mutable reference to the series at this index,
+    /// use this to push new data points to the series
+    pub fn series_mut(&mut self, index: usize) -> Option<&mut Series> {
+        self.series.get_mut(index)
+    }
+}
+
+impl<MSG, K> Widget<MSG> for PlotChart<MSG, K>
+where
+    MSG: fmt::Debug + 'static,
+    K: PlotKind + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        draw_plot(buf, &layout, &self.series, K::DRAW_LINES);
+        vec![]
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG, K> fmt::Debug for PlotChart<MSG, K>
+where
+    K: PlotKind,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct(K::NAME)
+            .field("series", &self.series)
+            .field("id", &self.id)
+            .finish()
+    }
+}
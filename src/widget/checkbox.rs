@@ -1,5 +1,6 @@
 use crate::Event;
-use crate::{buffer::Buffer, symbol, Callback, Cmd, Widget};
+use crate::Value;
+use crate::{buffer::Buffer, symbol, unicode, Callback, Cmd, Widget};
 use expanse::{
     geometry::Size,
     result::Layout,
@@ -77,7 +78,9 @@ impl<MSG: 'static> Widget<MSG> for Checkbox<MSG> {
         Style {
             position_type: PositionType::Relative,
             size: Size {
-                width: Dimension::Points((self.label.len() + 3) as f32),
+                width: Dimension::Points(
+                    (unicode::str_width(&self.label) + 3) as f32,
+                ),
                 height: Dimension::Points(1.0),
             },
             min_size: Size {
@@ -100,12 +103,14 @@ impl<MSG: 'static> Widget<MSG> for Checkbox<MSG> {
         };
         buf.set_symbol(loc_x, loc_y, box_symbol);
 
-        for (t, ch) in self.label.chars().enumerate() {
-            buf.set_symbol(loc_x + 3 + t, loc_y, ch);
-        }
+        buf.write_str(loc_x + 3, loc_y, &self.label);
         vec![]
     }
 
+    fn value(&self) -> Option<Value> {
+        Some(Value::Bool(self.is_checked))
+    }
+
     fn set_size(&mut self, _width: Option<f32>, _height: Option<f32>) {}
 
     fn process_event(&mut self, event: Event) -> Vec<MSG> {
@@ -0,0 +1,585 @@
+use crate::crossterm::{
+    event::{KeyCode, KeyEvent},
+    style::Color,
+};
+use crate::{
+    buffer::Buffer, unicode, Button, Callback, Cmd, Event, Span, Value, Widget,
+};
+use expanse::{
+    geometry::Size,
+    number::Number,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use std::{collections::BTreeMap, fmt};
+
+/// the default number of columns used to display the errors
+const ERROR_WIDTH: usize = 20;
+/// the color of the error messages
+const ERROR_COLOR: Color = Color::Red;
+
+/// A field of the form, the widget is displayed after the label
+#[derive(Debug)]
+struct Field<MSG> {
+    id: String,
+    label: String,
+    widget: Box<dyn Widget<MSG>>,
+    validators: Vec<Callback<Value, Result<(), String>>>,
+    error: Option<String>,
+}
+
+/// A form which lays out the fields in rows, the labels in the first column,
+/// the fields in the second and the validation errors in the third.
+/// The values of the fields are collected by their id and sent to
+/// `on_submit` when Enter is pressed or the submit button is clicked.
+/// The Enter is sent to the focused field first, so it can commit its value,
+/// and the form is not submitted when the field uses the Enter itself,
+/// such as a new line in a `TextArea`.
+///
+/// The fields are owned by the form, the keys are sent to the focused field
+/// and Tab and BackTab move the focus to the next and previous field.
+#[derive(Debug)]
+pub struct Form<MSG> {
+    layout: Option<Layout>,
+    fields: Vec<Field<MSG>>,
+    submit_button: Option<Button<MSG>>,
+    /// the index of the focused field, the submit button is after the fields
+    focused_index: usize,
+    focused: bool,
+    error_width: usize,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    on_submit: Vec<Callback<BTreeMap<String, Value>, MSG>>,
+}
+
+impl<MSG> Default for Form<MSG> {
+    fn default() -> Self {
+        Form {
+            layout: None,
+            fields: vec![],
+            submit_button: None,
+            focused_index: 0,
+            focused: false,
+            error_width: ERROR_WIDTH,
+            width: None,
+            height: None,
+            id: None,
+            on_submit: vec![],
+        }
+    }
+}
+
+impl<MSG> Form<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    /// create a new empty form
+    pub fn new() -> Self {
+        Form::default()
+    }
+
+    /// add a field with the label, the id of the widget is set to `id`
+    /// and its value is collected with this id
+    pub fn add_field<W>(&mut self, id: &str, label: &str, mut widget: W)
+    where
+        W: Widget<MSG> + 'static,
+    {
+        widget.set_id(id);
+        self.fields.push(Field {
+            id: id.to_string(),
+            label: label.to_string(),
+            widget: Box::new(widget),
+            validators: vec![],
+            error: None,
+        });
+    }
+
+    /// add a validator to the field with this id, the returned error message
+    /// is displayed next to the field
+    pub fn add_validator<F>(&mut self, id: &str, f: F)
+    where
+        F: FnMut(Value) -> Result<(), String> + 'static,
+    {
+        if let Some(field) = self.fields.iter_mut().find(|field| field.id == id)
+        {
+            field.validators.push(f.into());
+        }
+    }
+
+    /// display a submit button with this label below the fields
+    pub fn set_submit_button(&mut self, label: &str) {
+        let mut button = Button::new(label);
+        button
+            .set_size(Some((unicode::str_width(label) + 2) as f32), Some(3.0));
+        self.submit_button = Some(button);
+    }
+
+    /// set the number of columns used to display the errors, the default is 20
+    pub fn set_error_width(&mut self, error_width: usize) {
+        self.error_width = error_width;
+    }
+
+    /// attach a callback which is called with the values of the fields
+    /// when the form is submitted and all the fields are valid
+    pub fn on_submit<F>(&mut self, f: F)
+    where
+        F: FnMut(BTreeMap<String, Value>) -> MSG + 'static,
+    {
+        self.on_submit.push(f.into());
+    }
+
+    /// the values of the fields keyed by their id
+    pub fn values(&self) -> BTreeMap<String, Value> {
+        self.fields
+            .iter()
+            .filter_map(|field| {
+                field.widget.value().map(|value| (field.id.clone(), value))
+            })
+            .collect()
+    }
+
+    /// return a reference to the widget of the field with this id
+    pub fn field(&self, id: &str) -> Option<&dyn Widget<MSG>> {
+        self.fields
+            .iter()
+            .find(|field| field.id == id)
+            .map(|field| field.widget.as_ref())
+    }
+
+    /// return a mutable reference to the widget of the field with this id
+    pub fn field_mut(&mut self, id: &str) -> Option<&mut Box<dyn Widget<MSG>>> {
+        self.fields
+            .iter_mut()
+            .find(|field| field.id == id)
+            .map(|field| &mut field.widget)
+    }
+
+    /// the error message of the field with this id
+    pub fn error(&self, id: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|field| field.id == id)
+            .and_then(|field| field.error.as_deref())
+    }
+
+    /// run the validators of all the fields, return true if all are valid
+    pub fn validate(&mut self) -> bool {
+        self.fields.iter_mut().for_each(Self::validate_field);
+        self.fields.iter().all(|field| field.error.is_none())
+    }
+
+    /// the error is the message of the first validator which fails
+    fn validate_field(field: &mut Field<MSG>) {
+        let value = field.widget.value();
+        field.error = match value {
+            Some(value) => field
+                .validators
+                .iter_mut()
+                .find_map(|validator| validator.emit(value.clone()).err()),
+            None => None,
+        };
+    }
+
+    /// validate the fields and call the on_submit callbacks when all are valid
+    pub fn submit(&mut self) -> Vec<MSG> {
+        if !self.validate() {
+            return vec![];
+        }
+        let values = self.values();
+        self.on_submit
+            .iter_mut()
+            .map(|cb| cb.emit(values.clone()))
+            .collect()
+    }
+
+    /// the number of widgets which can be focused, including the submit button
+    fn focusable_count(&self) -> usize {
+        self.fields.len() + self.submit_button.iter().count()
+    }
+
+    /// move the focus to the widget at this index
+    fn focus(&mut self, index: usize) {
+        self.focused_index = index;
+        let focused = self.focused;
+        for (i, field) in self.fields.iter_mut().enumerate() {
+            field.widget.set_focused(focused && i == index);
+        }
+        if let Some(button) = &mut self.submit_button {
+            button.set_focused(focused && index == self.fields.len());
+        }
+    }
+
+    fn process_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        let count = self.focusable_count();
+        match key_event.code {
+            KeyCode::Tab if count > 0 => {
+                self.focus((self.focused_index + 1) % count);
+                vec![]
+            }
+            KeyCode::BackTab if count > 0 => {
+                self.focus((self.focused_index + count - 1) % count);
+                vec![]
+            }
+            KeyCode::Enter => {
+                let is_captured = self
+                    .fields
+                    .get(self.focused_index)
+                    .is_some_and(|field| field.widget.captures_key(&key_event));
+                let mut msgs = self.process_field_key(key_event);
+                if !is_captured {
+                    msgs.extend(self.submit());
+                }
+                msgs
+            }
+            _ => self.process_field_key(key_event),
+        }
+    }
+
+    /// send the key to the focused field
+    fn process_field_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        match self.fields.get_mut(self.focused_index) {
+            Some(field) => {
+                let msgs = field.widget.process_event(key_event.into());
+                // the error is cleared as soon as the value is fixed
+                if field.error.is_some() {
+                    Self::validate_field(field);
+                }
+                msgs
+            }
+            None => vec![],
+        }
+    }
+
+    fn process_mouse(&mut self, event: Event) -> Vec<MSG> {
+        let (x, y) = match event.extract_location() {
+            Some((x, y)) => (x as f32, y as f32),
+            None => return vec![],
+        };
+        let is_hit = |widget: &dyn Widget<MSG>| {
+            widget.layout().is_some_and(|layout| {
+                x >= layout.location.x
+                    && x < layout.location.x + layout.size.width
+                    && y >= layout.location.y
+                    && y < layout.location.y + layout.size.height
+            })
+        };
+        let hit_field = self
+            .fields
+            .iter()
+            .position(|field| is_hit(field.widget.as_ref()));
+        let hit_button = self
+            .submit_button
+            .as_ref()
+            .is_some_and(|button| is_hit(button));
+        if let Some(index) = hit_field {
+            if event.is_mouse_click() {
+                self.focus(index);
+            }
+            self.fields[index].widget.process_event(event)
+        } else if hit_button && event.is_mouse_click() {
+            self.focus(self.fields.len());
+            self.submit()
+        } else {
+            vec![]
+        }
+    }
+
+    /// the number of columns used by the labels
+    fn label_width(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|field| unicode::str_width(&field.label))
+            .max()
+            .map_or(0, |width| width + 1)
+    }
+
+    /// the height of the widget taken from its style,
+    /// the widget with no fixed height takes 1 row
+    fn widget_height(widget: &dyn Widget<MSG>) -> f32 {
+        let style = widget.style();
+        let points = |dimension| match dimension {
+            Dimension::Points(points) => points,
+            _ => 0.0,
+        };
+        points(style.size.height)
+            .max(points(style.min_size.height))
+            .max(1.0)
+    }
+
+    /// the total height of the fields and the submit button
+    fn content_height(&self) -> f32 {
+        let fields_height: f32 = self
+            .fields
+            .iter()
+            .map(|field| Self::widget_height(field.widget.as_ref()))
+            .sum();
+        let button_height = self
+            .submit_button
+            .as_ref()
+            .map_or(0.0, |button| Self::widget_height(button));
+        fields_height + button_height
+    }
+
+    /// compute the layout of the widget placed at this location
+    fn layout_widget(
+        widget: &mut dyn Widget<MSG>,
+        location: (f32, f32),
+        size: (f32, f32),
+    ) {
+        let mut stretch = expanse::node::Stretch::new();
+        let node = widget
+            .build_stretch_node_recursive(&mut stretch)
+            .expect("must have built a style node");
+        stretch
+            .compute_layout(
+                node,
+                Size {
+                    width: Number::Defined(size.0),
+                    height: Number::Defined(size.1),
+                },
+            )
+            .expect("must compute the layout");
+        widget.set_node_layout_from_stretch_node(
+            node,
+            &stretch,
+            location,
+            (0.0, 0.0),
+        );
+    }
+
+    /// place the fields in rows below each other, after the column of the labels
+    fn layout_fields(&mut self, layout: &Layout) {
+        let left = layout.location.x.round() + self.label_width() as f32;
+        let width = (layout.size.width.round()
+            - self.label_width() as f32
+            - self.error_width as f32)
+            .max(1.0);
+        let mut top = layout.location.y.round();
+        for field in self.fields.iter_mut() {
+            let height = Self::widget_height(field.widget.as_ref());
+            Self::layout_widget(
+                field.widget.as_mut(),
+                (left, top),
+                (width, height),
+            );
+            top += height;
+        }
+        if let Some(button) = &mut self.submit_button {
+            let height = Self::widget_height(button);
+            Self::layout_widget(button, (left, top), (width, height));
+        }
+    }
+
+    /// the row in the middle of the widget where the label and error is written
+    /// and the column after the widget
+    fn middle_row(widget: &dyn Widget<MSG>) -> Option<(usize, usize)> {
+        widget.layout().map(|layout| {
+            let top = layout.location.y.round() as usize;
+            let height = (layout.size.height.round() as usize).max(1);
+            let right = (layout.location.x + layout.size.width).round();
+            (top + (height - 1) / 2, right as usize)
+        })
+    }
+}
+
+impl<MSG> Widget<MSG> for Form<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout_fields(&layout);
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Points(self.content_height())
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        let left = layout.location.x.round() as usize;
+        let mut cmds = vec![];
+        for field in self.fields.iter() {
+            if let Some((row, right)) = Self::middle_row(field.widget.as_ref())
+            {
+                buf.write_str(left, row, &field.label);
+                if let Some(error) = &field.error {
+                    let mut span = Span::new(error);
+                    span.color(ERROR_COLOR);
+                    buf.write_styled_truncated(
+                        right + 1,
+                        row,
+                        &span.into(),
+                        self.error_width.saturating_sub(1),
+                    );
+                }
+            }
+            if let Ok(more_cmds) = field.widget.draw_widget(buf) {
+                cmds.extend(more_cmds);
+            }
+        }
+        if let Some(button) = &self.submit_button {
+            cmds.extend(button.draw(buf));
+        }
+        cmds
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+        self.focus(self.focused_index);
+    }
+
+    fn captures_key(&self, key_event: &KeyEvent) -> bool {
+        self.fields
+            .get(self.focused_index)
+            .is_some_and(|field| field.widget.captures_key(key_event))
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn process_event(&mut self, event: Event) -> Vec<MSG> {
+        match event {
+            Event::Key(ke) => self.process_key(ke),
+            Event::Mouse(_) => self.process_mouse(event),
+            _ => vec![],
+        }
+    }
+
+    fn tick(&mut self) {
+        for field in self.fields.iter_mut() {
+            field.widget.tick_widget();
+        }
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{NumberInput, TextInput};
+
+    fn form() -> Form<BTreeMap<String, Value>> {
+        let mut form = Form::new();
+        form.add_field("name", "Name", TextInput::new(""));
+        form.add_field("port", "Port", NumberInput::new(8080.0));
+        form.add_validator("name", |value| match value.as_str() {
+            Some("") => Err("required".to_string()),
+            _ => Ok(()),
+        });
+        form.set_submit_button("Save");
+        form.on_submit(|values| values);
+        form.compute_node_layout(Size {
+            width: Number::Defined(40.0),
+            height: Number::Defined(12.0),
+        });
+        form
+    }
+
+    #[test]
+    fn fields_are_aligned() {
+        let form = form();
+        assert_eq!(form.style().size.height, Dimension::Points(9.0));
+        let port = form.field("port").expect("must have a field");
+        let layout = port.layout().expect("must have a layout");
+        assert_eq!((layout.location.x, layout.location.y), (5.0, 3.0));
+        assert_eq!(layout.size.width, 15.0);
+
+        let mut buf = Buffer::new(40, 12);
+        form.draw(&mut buf);
+        let row: String = buf.cells[4][0..4]
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect();
+        assert_eq!(row, "Port");
+    }
+
+    #[test]
+    fn submit_when_valid() {
+        let mut form = form();
+        let msgs = form.process_event(KeyEvent::from(KeyCode::Enter).into());
+        assert!(msgs.is_empty());
+        assert_eq!(form.error("name"), Some("required"));
+
+        form.set_focused(true);
+        form.process_event(KeyEvent::from(KeyCode::Char('a')).into());
+        assert_eq!(form.error("name"), None);
+        form.process_event(KeyEvent::from(KeyCode::Tab).into());
+        form.process_event(KeyEvent::from(KeyCode::Up).into());
+        let msgs = form.process_event(KeyEvent::from(KeyCode::Enter).into());
+        let values = msgs.into_iter().next().expect("must be submitted");
+        assert_eq!(values["name"], Value::String("a".to_string()));
+        assert_eq!(values["port"], Value::F64(8081.0));
+    }
+
+    #[test]
+    fn enter_commits_the_field_before_submitting() {
+        let mut form: Form<BTreeMap<String, Value>> = Form::new();
+        let mut port = NumberInput::new(8080.0);
+        port.set_range(1.0, 65535.0);
+        form.add_field("port", "Port", port);
+        form.on_submit(|values| values);
+        form.compute_node_layout(Size {
+            width: Number::Defined(40.0),
+            height: Number::Defined(6.0),
+        });
+        form.set_focused(true);
+        // typed over the range, it is clamped when Enter is pressed
+        form.process_event(KeyEvent::from(KeyCode::Char('0')).into());
+        let msgs = form.process_event(KeyEvent::from(KeyCode::Enter).into());
+        let values = msgs.into_iter().next().expect("must be submitted");
+        assert_eq!(values["port"], Value::F64(65535.0));
+
+        let mut buf = Buffer::new(40, 6);
+        form.draw(&mut buf);
+        let row: String = buf.cells[1][6..11]
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect();
+        assert_eq!(row, "65535");
+    }
+
+    #[test]
+    fn enter_in_text_area_is_a_new_line() {
+        let mut form: Form<BTreeMap<String, Value>> = Form::new();
+        form.add_field("notes", "Notes", crate::TextArea::new("a"));
+        form.on_submit(|values| values);
+        form.compute_node_layout(Size {
+            width: Number::Defined(40.0),
+            height: Number::Defined(6.0),
+        });
+        form.set_focused(true);
+        let msgs = form.process_event(KeyEvent::from(KeyCode::Enter).into());
+        assert!(msgs.is_empty());
+        assert_eq!(form.values()["notes"], Value::String("a\n".to_string()));
+    }
+}
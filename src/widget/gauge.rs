@@ -0,0 +1,255 @@
+use super::progress::Progress;
+use crate::crossterm::style::Color;
+use crate::{
+    buffer::{Buffer, Cell},
+    symbol::bar,
+    Cmd, Widget,
+};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use std::{fmt, marker::PhantomData};
+
+/// A vertical gauge with value from 0.0 to 1.0, filled from the bottom
+/// The gauge is filled with eighth bars, so it can show sub-cell level
+///```ignore
+///      ▄▄▄
+///      ███
+///      42%
+///      ███
+/// ```
+pub struct Gauge<MSG> {
+    layout: Option<Layout>,
+    progress: Progress,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    _phantom_msg: PhantomData<MSG>,
+}
+
+impl<MSG> Default for Gauge<MSG> {
+    fn default() -> Self {
+        Gauge {
+            layout: None,
+            progress: Progress::default(),
+            width: None,
+            height: None,
+            id: None,
+            _phantom_msg: PhantomData,
+        }
+    }
+}
+
+impl<MSG> Gauge<MSG> {
+    /// create a new gauge with value
+    pub fn new(value: f32) -> Self {
+        let mut gauge = Gauge::default();
+        gauge.set_value(value);
+        gauge
+    }
+
+    /// set the value of this gauge, clamped to 0.0..=1.0
+    pub fn set_value(&mut self, value: f32) {
+        self.progress.set_value(value);
+    }
+
+    /// return the value of this gauge
+    pub fn get_value(&self) -> f32 {
+        self.progress.value()
+    }
+
+    /// set the label which will be displayed at the middle of the gauge
+    pub fn set_label<S: ToString>(&mut self, label: S) {
+        self.progress.set_label(label);
+    }
+
+    /// set whether to display the percentage at the middle of the gauge
+    pub fn show_percentage(&mut self, show_percentage: bool) {
+        self.progress.show_percentage(show_percentage);
+    }
+
+    /// set the indeterminate mode, where the gauge is animated up and down
+    /// since the level is unknown.
+    /// The renderer needs a tick rate for the animation to run.
+    pub fn set_indeterminate(&mut self, is_indeterminate: bool) {
+        self.progress.set_indeterminate(is_indeterminate);
+    }
+
+    /// set the color of the filled gauge
+    pub fn set_color(&mut self, color: Color) {
+        self.progress.set_color(color);
+    }
+
+    /// return the number of filled eighths for each of the rows, from top to bottom
+    fn row_eighths(&self, height: usize) -> Vec<usize> {
+        let mut eighths = self.progress.eighths(height);
+        eighths.reverse();
+        eighths
+    }
+
+    /// create the rows of cells of the gauge, with the text overlaid at the middle row
+    fn create_cells(&self, width: usize, height: usize) -> Vec<Vec<Cell>> {
+        let middle = height / 2;
+        self.row_eighths(height)
+            .into_iter()
+            .enumerate()
+            .map(|(j, eighths)| {
+                let mut cell = Cell::new(bar::eighths(eighths));
+                if let Some(color) = self.progress.color() {
+                    cell.color(color);
+                }
+                let mut cells = vec![cell; width];
+                if j == middle {
+                    self.progress
+                        .overlay_text(&mut cells, &vec![eighths; width]);
+                }
+                cells
+            })
+            .collect()
+    }
+}
+
+impl<MSG> Widget<MSG> for Gauge<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Points(4.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+            },
+            min_size: Size {
+                width: Dimension::Points(1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        let loc_x = layout.location.x.round() as usize;
+        let loc_y = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        let height = layout.size.height.round() as usize;
+
+        for (j, line) in
+            self.create_cells(width, height).into_iter().enumerate()
+        {
+            for (i, cell) in line.into_iter().enumerate() {
+                buf.set_cell(loc_x + i, loc_y + j, cell);
+            }
+        }
+        vec![]
+    }
+
+    fn tick(&mut self) {
+        self.progress.tick();
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for Gauge<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Gauge")
+            .field("value", &self.progress.value())
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossterm::style::Attribute;
+    use expanse::number::Number;
+
+    fn symbols(line: &[Cell]) -> String {
+        line.iter().map(|cell| cell.symbol.clone()).collect()
+    }
+
+    #[test]
+    fn vertical_sub_cell_fill() {
+        let gauge = Gauge::<()>::new(0.35);
+        // 0.35 * 4 rows * 8 = 11.2 eighths, filled from the bottom
+        assert_eq!(gauge.row_eighths(4), vec![0, 0, 3, 8]);
+        let rows: Vec<String> = gauge
+            .create_cells(2, 4)
+            .iter()
+            .map(|line| symbols(line))
+            .collect();
+        assert_eq!(rows, vec!["  ", "  ", "▃▃", "██"]);
+    }
+
+    #[test]
+    fn text_is_drawn_at_the_middle_row() {
+        let mut gauge = Gauge::<()>::new(0.5);
+        gauge.show_percentage(true);
+        gauge.set_size(Some(5.0), Some(4.0));
+        gauge.compute_node_layout(Size {
+            width: Number::Defined(5.0),
+            height: Number::Defined(4.0),
+        });
+        let mut buf = Buffer::new(5, 4);
+        gauge.draw(&mut buf);
+        let rows: Vec<String> =
+            buf.cells.iter().map(|line| symbols(line)).collect();
+        assert_eq!(rows, vec!["     ", "     ", "█50%█", "█████"]);
+        // the text on the filled row is inverted
+        assert!(buf.cells[2][1].attributes.has(Attribute::Reverse));
+
+        // the text on the empty row is not inverted
+        let mut gauge = Gauge::<()>::new(0.0);
+        gauge.set_label("low");
+        let cells = gauge.create_cells(5, 4);
+        assert_eq!(symbols(&cells[2]), " low ");
+        assert!(!cells[2][1].attributes.has(Attribute::Reverse));
+    }
+
+    #[test]
+    fn indeterminate_bounces_up_and_down() {
+        let mut gauge = Gauge::<()>::new(0.0);
+        gauge.set_indeterminate(true);
+        gauge.show_percentage(true);
+        // the percentage is not shown, since the level is unknown
+        assert_eq!(gauge.progress.text(), None);
+        assert_eq!(gauge.row_eighths(8), vec![0, 0, 0, 0, 0, 0, 8, 8]);
+        for _ in 0..7 {
+            Widget::<()>::tick(&mut gauge);
+        }
+        // travelled to the top and one step back
+        assert_eq!(gauge.row_eighths(8), vec![0, 8, 8, 0, 0, 0, 0, 0]);
+    }
+}
@@ -8,7 +8,7 @@ use ito_canvas::unicode_canvas::{Border, Canvas};
 use std::fmt;
 
 /// Group elements together
-/// Radio buttons in the same group will have an exclusive behavior
+/// Use a `RadioGroup` for radio buttons which have an exclusive selection
 #[derive(Default, Debug)]
 pub struct GroupBox<MSG> {
     layout: Option<Layout>,
@@ -67,9 +67,7 @@ impl<MSG> GroupBox<MSG> {
         let loc_x = layout.location.x.round() as usize;
         let loc_y = layout.location.y.round() as usize;
         if let Some(label) = &self.label {
-            for (t, ch) in label.chars().enumerate() {
-                buf.set_symbol(loc_x + 3 + t, loc_y, ch);
-            }
+            buf.write_str(loc_x + 3, loc_y, label);
         }
     }
 
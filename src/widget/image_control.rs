@@ -1,18 +1,153 @@
 use crate::crossterm::style::Color;
-use crate::symbol::bar;
+use crate::symbol::{self, bar};
 use crate::{buffer::Buffer, buffer::Cell, Cmd, Widget};
+use crate::{Alignment, VerticalAlignment};
+use dither::{dither, luminance, nearest_ansi16, nearest_ansi256};
 use expanse::result::Layout;
 use expanse::{
     geometry::Size,
     style::{Dimension, PositionType, Style},
 };
-use image::{self, DynamicImage, GenericImageView};
-use std::{fmt, marker::PhantomData};
+pub(crate) use graphics::delete_kitty;
+use graphics::{encode_kitty, encode_sixel, next_image_id};
+use image::{
+    self, imageops::FilterType, DynamicImage, GenericImageView, ImageError,
+};
+use std::{cell::RefCell, env, fmt, marker::PhantomData, rc::Rc};
+
+mod dither;
+mod graphics;
+
+/// the width of the terminal cell divided by its height
+const CELL_ASPECT_RATIO: f32 = 0.5;
+
+/// the characters used in ascii art, from the darkest to the brightest
+const ASCII_RAMP: [char; 10] =
+    [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+/// the first braille character, which has no dots
+const BRAILLE_BLANK: u32 = 0x2800;
+
+/// the bit of each dot of the braille character, in 2 columns and 4 rows
+const BRAILLE_DOTS: [[u32; 2]; 4] =
+    [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// the assumed height of the terminal cell in pixels, used for the size
+/// of the image sent with the terminal graphics
+const GRAPHICS_CELL_HEIGHT: usize = 20;
+
+/// the image scaled to this number of columns and rows
+type ScaledImage = ((usize, usize), Scaled);
+
+/// the image scaled to the size of the widget
+enum Scaled {
+    /// the cells of the image, the cells which are not covered
+    /// by the image are `None`
+    Cells(Vec<Vec<Option<Cell>>>),
+    /// the image is drawn by the terminal
+    Graphics(Graphics),
+}
+
+/// the escape sequence of the terminal graphics, and the cells it covers
+/// relative to the widget
+struct Graphics {
+    column: usize,
+    row: usize,
+    columns: usize,
+    rows: usize,
+    /// the id of the image when it is drawn with kitty
+    image_id: Option<u32>,
+    data: Rc<Vec<u8>>,
+}
+
+/// How the image is scaled to the size of the widget
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ImageFit {
+    /// scale the image to fit inside the widget, keeping the aspect ratio
+    #[default]
+    Contain,
+    /// scale the image to fill the widget, keeping the aspect ratio,
+    /// the parts of the image which overflow are cropped
+    Cover,
+    /// scale the image to the size of the widget, ignoring the aspect ratio
+    Stretch,
+    /// the image is not scaled, it is cropped when it is bigger than the widget
+    None,
+}
+
+/// How the pixels of the image are displayed in the cells
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageRenderMode {
+    /// 2 pixels per cell with half blocks, in 24-bit colors
+    TrueColor,
+    /// 2 pixels per cell with half blocks, dithered to the 256 colors
+    Ansi256,
+    /// 2 pixels per cell with half blocks, dithered to the 16 colors
+    Ansi16,
+    /// 8 black or white pixels per cell with braille dots
+    Braille,
+    /// 1 pixel per cell with characters of increasing density
+    Ascii,
+    /// the pixels are drawn by the terminal with the sixel graphics,
+    /// it is never detected and only supported by some terminals
+    Sixel,
+    /// the pixels are drawn by the terminal with the kitty graphics protocol,
+    /// it is never detected and only supported by some terminals
+    Kitty,
+}
+
+impl ImageRenderMode {
+    /// detect the colors supported by the terminal from the
+    /// `COLORTERM` and `TERM` environment variables
+    pub fn detect() -> Self {
+        Self::from_env(
+            env::var("COLORTERM").ok().as_deref(),
+            env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        match (colorterm, term) {
+            (Some("truecolor"), _) | (Some("24bit"), _) => Self::TrueColor,
+            (_, Some(term)) if term.contains("256color") => Self::Ansi256,
+            (_, Some("dumb")) => Self::Ascii,
+            _ => Self::Ansi16,
+        }
+    }
+
+    /// the number of pixels in the width and height of a cell
+    fn pixels_per_cell(&self, cell_aspect_ratio: f32) -> (usize, usize) {
+        match self {
+            Self::TrueColor | Self::Ansi256 | Self::Ansi16 => (1, 2),
+            Self::Braille => (2, 4),
+            Self::Ascii => (1, 1),
+            Self::Sixel | Self::Kitty => {
+                let width = GRAPHICS_CELL_HEIGHT as f32 * cell_aspect_ratio;
+                ((width.round() as usize).max(1), GRAPHICS_CELL_HEIGHT)
+            }
+        }
+    }
+}
 
 /// Image widget, supported formats: jpg, png
+///
+/// The image is displayed with half blocks, braille dots or ascii characters,
+/// depending on the colors supported by the terminal, or with the terminal
+/// graphics when it is set, see `ImageRenderMode`.
 pub struct Image<MSG> {
     layout: Option<Layout>,
     image: DynamicImage,
+    fit: ImageFit,
+    /// detected from the terminal when not set
+    render_mode: Option<ImageRenderMode>,
+    alignment: Alignment,
+    vertical_alignment: VerticalAlignment,
+    /// the width of the terminal cell divided by its height
+    cell_aspect_ratio: f32,
+    /// the image of the last drawn size, so it is not scaled on every draw
+    scaled: RefCell<Option<ScaledImage>>,
+    /// identifies the image in the kitty graphics protocol
+    image_id: u32,
     /// the width of cells used for this image
     width: Option<f32>,
     /// the height of unit cells, will be divided by 2 when used for computing
@@ -23,12 +158,24 @@ pub struct Image<MSG> {
 }
 
 impl<MSG> Image<MSG> {
-    /// create a new image widget
-    pub fn new(bytes: Vec<u8>) -> Self {
+    /// create a new image widget from the bytes of the image file,
+    /// returns an error when the bytes is not a supported image
+    pub fn new(bytes: Vec<u8>) -> Result<Self, ImageError> {
+        Ok(Self::from_image(image::load_from_memory(&bytes)?))
+    }
+
+    /// create a new image widget from the decoded image
+    pub(super) fn from_image(image: DynamicImage) -> Self {
         Image {
             layout: None,
-            image: image::load_from_memory(&bytes)
-                .expect("unable to load from memory"),
+            image,
+            fit: ImageFit::default(),
+            render_mode: None,
+            alignment: Alignment::Center,
+            vertical_alignment: VerticalAlignment::Middle,
+            cell_aspect_ratio: CELL_ASPECT_RATIO,
+            scaled: RefCell::new(None),
+            image_id: next_image_id(),
             width: None,
             height: None,
             id: None,
@@ -36,41 +183,312 @@ impl<MSG> Image<MSG> {
         }
     }
 
-    fn create_cells(&self, width: f32, _height: f32) -> Vec<Vec<Cell>> {
-        let width_multiplier = 1.0 / 2.0;
-        let height_multiplier = 1.0 / 2.0;
-        let w = width * width_multiplier;
-        let h = width * height_multiplier;
-        let img = self.image.thumbnail(w as u32, h as u32);
-        let (img_width, img_height) = img.dimensions();
-        let rgb = img.to_rgb();
-        (0..img_height as usize - 1)
-            .step_by(2)
-            .enumerate()
-            .map(|(_y, j)| {
-                (0..img_width as usize)
-                    .map(|i| {
-                        let mut cell = Cell::new(bar::HALF);
-                        let top_pixel = rgb.get_pixel(i as u32, j as u32);
-                        let bottom_pixel =
-                            rgb.get_pixel(i as u32, (j + 1) as u32);
-                        let top_color = Color::Rgb {
-                            r: top_pixel[0],
-                            g: top_pixel[1],
-                            b: top_pixel[2],
-                        };
-                        let bottom_color = Color::Rgb {
-                            r: bottom_pixel[0],
-                            g: bottom_pixel[1],
-                            b: bottom_pixel[2],
-                        };
-                        cell.background(top_color);
-                        cell.color(bottom_color);
-                        cell
+    /// replace the displayed image
+    pub(super) fn set_image(&mut self, image: DynamicImage) {
+        self.image = image;
+        self.scaled.replace(None);
+    }
+
+    /// set how the image is scaled to the size of the widget, default is `Contain`
+    pub fn set_fit(&mut self, fit: ImageFit) {
+        self.fit = fit;
+        self.scaled.replace(None);
+    }
+
+    /// set how the pixels are displayed,
+    /// overriding the mode detected from the terminal
+    pub fn set_render_mode(&mut self, render_mode: ImageRenderMode) {
+        self.render_mode = Some(render_mode);
+        self.scaled.replace(None);
+    }
+
+    fn render_mode(&self) -> ImageRenderMode {
+        self.render_mode.unwrap_or_else(ImageRenderMode::detect)
+    }
+
+    fn pixels_per_cell(&self) -> (usize, usize) {
+        self.render_mode().pixels_per_cell(self.cell_aspect_ratio)
+    }
+
+    /// the size of this number of columns and rows in square pixels,
+    /// an image of this size fills the cells without being scaled
+    pub(super) fn pixel_area(&self, columns: usize, rows: usize) -> (f32, f32) {
+        let (_, cell_height) = self.pixels_per_cell();
+        let width =
+            columns as f32 * self.cell_aspect_ratio * cell_height as f32;
+        (width, (rows * cell_height) as f32)
+    }
+
+    /// set the horizontal alignment of the image inside the widget,
+    /// default is `Center`. `Justify` is the same as `Left`.
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = alignment;
+        self.scaled.replace(None);
+    }
+
+    /// set the vertical alignment of the image inside the widget, default is `Middle`
+    pub fn set_vertical_alignment(
+        &mut self,
+        vertical_alignment: VerticalAlignment,
+    ) {
+        self.vertical_alignment = vertical_alignment;
+        self.scaled.replace(None);
+    }
+
+    /// set the width of the terminal cell divided by its height, default is 0.5.
+    /// The ratio is ignored when it is not positive, or is not finite
+    pub fn set_cell_aspect_ratio(&mut self, cell_aspect_ratio: f32) {
+        if !cell_aspect_ratio.is_finite() || cell_aspect_ratio <= 0.0 {
+            return;
+        }
+        self.cell_aspect_ratio = cell_aspect_ratio;
+        self.scaled.replace(None);
+    }
+
+    /// the size of the scaled image in pixels, the number of pixels
+    /// in a cell depends on the render mode
+    fn scaled_size(&self, columns: usize, rows: usize) -> (u32, u32) {
+        let (image_width, image_height) = self.image.dimensions();
+        let (cell_width, cell_height) = self.pixels_per_cell();
+        // the width of a pixel relative to its height
+        let pixel_aspect_ratio =
+            self.cell_aspect_ratio * cell_height as f32 / cell_width as f32;
+        let natural_width = image_width as f32 / pixel_aspect_ratio;
+        let natural_height = image_height as f32;
+        let width = (columns * cell_width) as f32;
+        let height = (rows * cell_height) as f32;
+        let scale_x = width / natural_width;
+        let scale_y = height / natural_height;
+        let (width, height) = match self.fit {
+            ImageFit::Stretch => (width, height),
+            ImageFit::None => (natural_width, natural_height),
+            ImageFit::Contain => {
+                let scale = scale_x.min(scale_y);
+                (natural_width * scale, natural_height * scale)
+            }
+            ImageFit::Cover => {
+                let scale = scale_x.max(scale_y);
+                (natural_width * scale, natural_height * scale)
+            }
+        };
+        (
+            (width.round() as u32).max(1),
+            (height.round() as u32).max(1),
+        )
+    }
+
+    /// the location of the scaled image relative to the widget in pixels,
+    /// negative when the image is cropped
+    fn image_offset(
+        &self,
+        columns: usize,
+        rows: usize,
+        (width, height): (u32, u32),
+    ) -> (i64, i64) {
+        let (cell_width, cell_height) = self.pixels_per_cell();
+        let free_width = (columns * cell_width) as i64 - width as i64;
+        let free_height = (rows * cell_height) as i64 - height as i64;
+        let offset_x = match self.alignment {
+            Alignment::Left | Alignment::Justify => 0,
+            Alignment::Center => free_width / 2,
+            Alignment::Right => free_width,
+        };
+        let offset_y = match self.vertical_alignment {
+            VerticalAlignment::Top => 0,
+            VerticalAlignment::Middle => free_height / 2,
+            VerticalAlignment::Bottom => free_height,
+        };
+        (offset_x, offset_y)
+    }
+
+    fn create_scaled(&self, columns: usize, rows: usize) -> Scaled {
+        match self.create_graphics(columns, rows) {
+            Some(graphics) => Scaled::Graphics(graphics),
+            None => Scaled::Cells(self.create_cells(columns, rows)),
+        }
+    }
+
+    /// the terminal graphics of the image which fills this number of
+    /// columns and rows, `None` when the image is drawn with cells
+    fn create_graphics(&self, columns: usize, rows: usize) -> Option<Graphics> {
+        let render_mode = self.render_mode();
+        if !matches!(
+            render_mode,
+            ImageRenderMode::Sixel | ImageRenderMode::Kitty
+        ) {
+            return None;
+        }
+        let (cell_width, cell_height) = self.pixels_per_cell();
+        let (width, height) = self.scaled_size(columns, rows);
+        let (offset_x, offset_y) =
+            self.image_offset(columns, rows, (width, height));
+        // the graphics starts at a cell, the image is shifted to the cell
+        let column = (offset_x.max(0) as usize / cell_width).min(columns - 1);
+        let row = (offset_y.max(0) as usize / cell_height).min(rows - 1);
+        let crop_x = (-offset_x).max(0) as u32;
+        let crop_y = (-offset_y).max(0) as u32;
+        let visible_width =
+            (width - crop_x).min(((columns - column) * cell_width) as u32);
+        let visible_height =
+            (height - crop_y).min(((rows - row) * cell_height) as u32);
+        let rgb = self
+            .image
+            .resize_exact(width, height, FilterType::Triangle)
+            .crop_imm(crop_x, crop_y, visible_width, visible_height)
+            .to_rgb8();
+        let columns = (visible_width as usize).div_ceil(cell_width);
+        let rows = (visible_height as usize).div_ceil(cell_height);
+        let (image_id, data) = match render_mode {
+            ImageRenderMode::Sixel => (None, encode_sixel(&rgb)),
+            _ => (
+                Some(self.image_id),
+                encode_kitty(&rgb, self.image_id, columns, rows),
+            ),
+        };
+        Some(Graphics {
+            column,
+            row,
+            columns,
+            rows,
+            image_id,
+            data: Rc::new(data),
+        })
+    }
+
+    /// the cells of the image which fills this number of columns and rows,
+    /// the cells which are not covered by the image are `None`
+    fn create_cells(
+        &self,
+        columns: usize,
+        rows: usize,
+    ) -> Vec<Vec<Option<Cell>>> {
+        let render_mode = self.render_mode();
+        let (cell_width, cell_height) = self.pixels_per_cell();
+        let (width, height) = self.scaled_size(columns, rows);
+        let rgb = self
+            .image
+            .resize_exact(width, height, FilterType::Triangle)
+            .to_rgb8();
+        let (offset_x, offset_y) =
+            self.image_offset(columns, rows, (width, height));
+        let cell_at = |grid_cell: &dyn Fn(i64, i64) -> Option<Cell>| {
+            (0..rows)
+                .map(|y| {
+                    (0..columns)
+                        .map(|x| {
+                            grid_cell(
+                                (x * cell_width) as i64 - offset_x,
+                                (y * cell_height) as i64 - offset_y,
+                            )
+                        })
+                        .collect()
+                })
+                .collect()
+        };
+        match render_mode {
+            ImageRenderMode::TrueColor => {
+                let colors: Vec<Vec<Color>> = rgb
+                    .rows()
+                    .map(|row| {
+                        row.map(|pixel| {
+                            let [r, g, b] = pixel.0;
+                            Color::Rgb { r, g, b }
+                        })
+                        .collect()
                     })
-                    .collect()
-            })
-            .collect()
+                    .collect();
+                cell_at(&|x, y| half_block(&colors, x, y))
+            }
+            ImageRenderMode::Ansi256 => {
+                let colors = dither(&rgb, nearest_ansi256);
+                cell_at(&|x, y| half_block(&colors, x, y))
+            }
+            ImageRenderMode::Ansi16 => {
+                let colors = dither(&rgb, nearest_ansi16);
+                cell_at(&|x, y| half_block(&colors, x, y))
+            }
+            ImageRenderMode::Braille => {
+                let dots = dither(&rgb, |pixel| {
+                    if luminance(pixel) > 127.5 {
+                        (true, [255; 3])
+                    } else {
+                        (false, [0; 3])
+                    }
+                });
+                cell_at(&|x, y| braille(&dots, x, y))
+            }
+            ImageRenderMode::Ascii => {
+                let characters = dither(&rgb, |pixel| {
+                    let level = luminance(pixel) / 255.0;
+                    let index = (level * (ASCII_RAMP.len() - 1) as f32).round();
+                    (
+                        ASCII_RAMP[index as usize],
+                        [pixel[0] as u8, pixel[1] as u8, pixel[2] as u8],
+                    )
+                });
+                cell_at(&|x, y| pixel_at(&characters, x, y).map(Cell::new))
+            }
+            ImageRenderMode::Sixel | ImageRenderMode::Kitty => {
+                unreachable!("terminal graphics are not drawn in cells")
+            }
+        }
+    }
+}
+
+/// the value of the pixel, `None` when the location is outside the image
+fn pixel_at<T: Copy>(pixels: &[Vec<T>], x: i64, y: i64) -> Option<T> {
+    if x < 0 || y < 0 {
+        return None;
+    }
+    pixels
+        .get(y as usize)
+        .and_then(|row| row.get(x as usize))
+        .copied()
+}
+
+/// the cell which displays the pixel at this location and the pixel below it
+fn half_block(colors: &[Vec<Color>], x: i64, y: i64) -> Option<Cell> {
+    match (pixel_at(colors, x, y), pixel_at(colors, x, y + 1)) {
+        (Some(top), Some(bottom)) => {
+            let mut cell = Cell::new(bar::HALF);
+            cell.background(top);
+            cell.color(bottom);
+            Some(cell)
+        }
+        (Some(top), None) => {
+            let mut cell = Cell::new(symbol::UPPER_HALF_BLOCK);
+            cell.color(top);
+            Some(cell)
+        }
+        (None, Some(bottom)) => {
+            let mut cell = Cell::new(bar::HALF);
+            cell.color(bottom);
+            Some(cell)
+        }
+        (None, None) => None,
+    }
+}
+
+/// the braille character of the 2x4 pixels starting at this location
+fn braille(dots: &[Vec<bool>], x: i64, y: i64) -> Option<Cell> {
+    let mut bits = 0;
+    let mut is_covered = false;
+    for (j, row) in BRAILLE_DOTS.iter().enumerate() {
+        for (i, bit) in row.iter().enumerate() {
+            if let Some(dot) = pixel_at(dots, x + i as i64, y + j as i64) {
+                is_covered = true;
+                if dot {
+                    bits |= bit;
+                }
+            }
+        }
+    }
+    if is_covered {
+        let ch = std::char::from_u32(BRAILLE_BLANK + bits)
+            .expect("must be a braille character");
+        Some(Cell::new(ch))
+    } else {
+        None
     }
 }
 
@@ -103,28 +521,49 @@ where
         }
     }
 
-    /// draw this button to the buffer, with the given computed layout
+    /// draw this image to the buffer, with the given computed layout
     fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
         let layout = self.layout().expect("must have a layout");
-        let loc_x = layout.location.x;
-        let loc_y = layout.location.y;
-        let width = layout.size.width;
-        let height = layout.size.height;
-
-        let bottom = loc_y + height - 1.0;
-        let _right = loc_x + width - 1.0;
-
-        let cells = self.create_cells(width, height);
-        for (y, line) in cells.iter().enumerate() {
-            for (i, cell) in line.iter().enumerate() {
-                let line_y = loc_y as usize + y;
-                let cell_x = loc_x as usize + i;
-                if line_y < bottom as usize {
-                    buf.set_cell(cell_x, line_y, cell.clone());
+        let loc_x = layout.location.x.round() as usize;
+        let loc_y = layout.location.y.round() as usize;
+        let columns = layout.size.width.round() as usize;
+        let rows = layout.size.height.round() as usize;
+        if columns == 0 || rows == 0 {
+            return vec![];
+        }
+
+        let mut cache = self.scaled.borrow_mut();
+        let is_cached =
+            matches!(&*cache, Some((size, _)) if *size == (columns, rows));
+        if !is_cached {
+            *cache = Some(((columns, rows), self.create_scaled(columns, rows)));
+        }
+        let (_, scaled) = cache.as_ref().expect("must have the scaled image");
+        match scaled {
+            Scaled::Cells(cells) => {
+                for (y, line) in cells.iter().enumerate() {
+                    for (x, cell) in line.iter().enumerate() {
+                        if let Some(cell) = cell {
+                            buf.set_cell(loc_x + x, loc_y + y, cell.clone());
+                        }
+                    }
                 }
+                vec![]
+            }
+            Scaled::Graphics(graphics) => {
+                let x = loc_x + graphics.column;
+                let y = loc_y + graphics.row;
+                buf.set_graphics(
+                    x,
+                    y,
+                    graphics.columns,
+                    graphics.rows,
+                    graphics.image_id,
+                    Rc::clone(&graphics.data),
+                );
+                vec![]
             }
         }
-        vec![]
     }
 
     fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
@@ -143,6 +582,211 @@ where
 
 impl<MSG> fmt::Debug for Image<MSG> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Image")
+        f.debug_struct("Image")
+            .field("dimensions", &self.image.dimensions())
+            .field("fit", &self.fit)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(width: u32, height: u32) -> Image<()> {
+        filled_image(width, height, [0, 0, 0])
+    }
+
+    fn filled_image(width: u32, height: u32, rgb: [u8; 3]) -> Image<()> {
+        let mut bytes = vec![];
+        let pixels =
+            image::RgbImage::from_pixel(width, height, image::Rgb(rgb));
+        DynamicImage::ImageRgb8(pixels)
+            .write_to(&mut bytes, image::ImageOutputFormat::Png)
+            .expect("must encode the image");
+        let mut image = Image::new(bytes).expect("must load the image");
+        image.set_render_mode(ImageRenderMode::TrueColor);
+        image
+    }
+
+    fn symbols(cells: &[Vec<Option<Cell>>], y: usize) -> String {
+        cells[y]
+            .iter()
+            .map(|cell| cell.as_ref().map_or(" ", |cell| cell.symbol.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn invalid_bytes_is_an_error() {
+        assert!(Image::<()>::new(vec![1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn fit_modes_keep_aspect_ratio() {
+        let mut image = image(4, 2);
+        assert_eq!(image.scaled_size(10, 5), (10, 5));
+        image.set_fit(ImageFit::Cover);
+        assert_eq!(image.scaled_size(10, 10), (40, 20));
+        image.set_fit(ImageFit::Stretch);
+        assert_eq!(image.scaled_size(10, 5), (10, 10));
+        image.set_fit(ImageFit::None);
+        image.set_cell_aspect_ratio(1.0);
+        assert_eq!(image.scaled_size(10, 5), (2, 2));
+        // invalid ratio is ignored
+        image.set_cell_aspect_ratio(0.0);
+        image.set_cell_aspect_ratio(f32::NAN);
+        assert_eq!(image.scaled_size(10, 5), (2, 2));
+    }
+
+    #[test]
+    fn image_is_aligned_in_the_box() {
+        let mut image = image(4, 2);
+        // the image is 5 pixels tall, centered in the 10 pixels
+        let cells = image.create_cells(10, 5);
+        assert_eq!(symbols(&cells, 0), "          ");
+        assert_eq!(symbols(&cells, 1), "▄▄▄▄▄▄▄▄▄▄");
+        assert_eq!(symbols(&cells, 3), "▀▀▀▀▀▀▀▀▀▀");
+
+        image.set_fit(ImageFit::None);
+        image.set_alignment(Alignment::Right);
+        image.set_vertical_alignment(VerticalAlignment::Top);
+        let cells = image.create_cells(6, 2);
+        assert_eq!(symbols(&cells, 0), "  ▄▄▄▄");
+        assert_eq!(symbols(&cells, 1), "      ");
+    }
+
+    #[test]
+    fn one_pixel_tall_image() {
+        let mut image = image(3, 1);
+        image.set_fit(ImageFit::None);
+        image.set_vertical_alignment(VerticalAlignment::Top);
+        let cells = image.create_cells(5, 1);
+        assert_eq!(symbols(&cells, 0), " ▀▀▀ ");
+    }
+
+    #[test]
+    fn render_mode_is_detected_from_the_terminal() {
+        use ImageRenderMode::*;
+        let detect = ImageRenderMode::from_env;
+        assert_eq!(detect(Some("truecolor"), Some("xterm")), TrueColor);
+        assert_eq!(detect(Some("24bit"), None), TrueColor);
+        assert_eq!(detect(None, Some("xterm-256color")), Ansi256);
+        assert_eq!(detect(None, Some("xterm")), Ansi16);
+        assert_eq!(detect(None, Some("dumb")), Ascii);
+    }
+
+    #[test]
+    fn braille_has_more_pixels_per_cell() {
+        let mut image = filled_image(4, 4, [255, 255, 255]);
+        image.set_render_mode(ImageRenderMode::Braille);
+        image.set_fit(ImageFit::None);
+        image.set_alignment(Alignment::Left);
+        image.set_vertical_alignment(VerticalAlignment::Top);
+        // a braille pixel is as wide as it is tall, 2 pixels per column
+        assert_eq!(image.scaled_size(10, 5), (4, 4));
+        let cells = image.create_cells(5, 2);
+        assert_eq!(symbols(&cells, 0), "⣿⣿   ");
+        assert_eq!(symbols(&cells, 1), "     ");
+
+        let mut image = filled_image(4, 4, [0, 0, 0]);
+        image.set_render_mode(ImageRenderMode::Braille);
+        // the blank dots still cover the background of the widget
+        let cells = image.create_cells(4, 1);
+        assert_eq!(symbols(&cells, 0), " \u{2800}\u{2800} ");
+    }
+
+    #[test]
+    fn ascii_uses_the_luminance() {
+        let mut image = filled_image(2, 1, [255, 255, 255]);
+        image.set_render_mode(ImageRenderMode::Ascii);
+        image.set_fit(ImageFit::Stretch);
+        let cells = image.create_cells(3, 1);
+        assert_eq!(symbols(&cells, 0), "@@@");
+
+        let mut image = filled_image(2, 1, [0, 0, 0]);
+        image.set_render_mode(ImageRenderMode::Ascii);
+        image.set_fit(ImageFit::Stretch);
+        let cells = image.create_cells(3, 1);
+        assert_eq!(symbols(&cells, 0), "   ");
+    }
+
+    #[test]
+    fn colors_are_quantized() {
+        let mut image = filled_image(2, 2, [250, 0, 0]);
+        image.set_fit(ImageFit::Stretch);
+        image.set_render_mode(ImageRenderMode::Ansi16);
+        let cells = image.create_cells(2, 1);
+        let cell = cells[0][0].as_ref().expect("must have a cell");
+        assert_eq!(cell.foreground_color, Some(Color::Red));
+
+        image.set_render_mode(ImageRenderMode::Ansi256);
+        let cells = image.create_cells(2, 1);
+        let cell = cells[0][0].as_ref().expect("must have a cell");
+        assert_eq!(cell.foreground_color, Some(Color::AnsiValue(196)));
+    }
+
+    #[test]
+    fn graphics_reserve_the_cells() {
+        use expanse::number::Number;
+
+        let mut image = image(4, 2);
+        image.set_render_mode(ImageRenderMode::Kitty);
+        image.set_size(Some(10.0), Some(5.0));
+        image.compute_node_layout(Size {
+            width: Number::Defined(10.0),
+            height: Number::Defined(5.0),
+        });
+        let mut buf = Buffer::new(10, 5);
+        image.draw(&mut buf);
+        // the image is 100x50 pixels, centered in the 100x100 pixels
+        let graphics = buf.graphics.clone();
+        assert_eq!(graphics.len(), 1);
+        assert_eq!((graphics[0].x, graphics[0].y), (0, 1));
+        let data = &graphics[0].data;
+        assert!(data.starts_with(b"\x1b_Ga=d,d=I,"));
+        assert!(String::from_utf8_lossy(data).contains(",s=100,v=50,"));
+        let reserved: Vec<bool> = buf
+            .cells
+            .iter()
+            .map(|line| line.iter().all(|cell| cell.is_filler()))
+            .collect();
+        assert_eq!(reserved, vec![false, true, true, true, false]);
+
+        // the same data is drawn again, so the renderer won't write it
+        let mut buf = Buffer::new(10, 5);
+        image.draw(&mut buf);
+        assert_eq!(buf.graphics, graphics);
+    }
+
+    #[test]
+    fn hidden_kitty_image_is_deleted() {
+        use expanse::number::Number;
+
+        let mut image = image(4, 2);
+        image.set_render_mode(ImageRenderMode::Kitty);
+        image.compute_node_layout(Size {
+            width: Number::Defined(10.0),
+            height: Number::Defined(5.0),
+        });
+        let mut buf = Buffer::new(10, 5);
+        image.draw(&mut buf);
+        let last = buf.graphics.clone();
+
+        // the unchanged image is not written again
+        let mut written = vec![];
+        buf.render_graphics(&mut written, &last)
+            .expect("must write");
+        assert!(written.is_empty());
+
+        // the image is no longer drawn, such as in a hidden tab
+        let mut written = vec![];
+        Buffer::new(10, 5)
+            .render_graphics(&mut written, &last)
+            .expect("must write");
+        assert_eq!(
+            String::from_utf8(written).expect("must be utf8"),
+            delete_kitty(image.image_id)
+        );
     }
 }
@@ -0,0 +1,162 @@
+//! Reduce the colors of the image to the palette of the terminal,
+//! the error of each pixel is spread to its neighbors with Floyd-Steinberg
+//! dithering so the gradients still look smooth.
+use crate::crossterm::style::Color;
+use image::RgbImage;
+
+/// the levels of each channel in the 6x6x6 color cube of the 256 colors
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// the 16 colors with their usual rgb values
+const ANSI16: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::DarkRed, [128, 0, 0]),
+    (Color::DarkGreen, [0, 128, 0]),
+    (Color::DarkYellow, [128, 128, 0]),
+    (Color::DarkBlue, [0, 0, 128]),
+    (Color::DarkMagenta, [128, 0, 128]),
+    (Color::DarkCyan, [0, 128, 128]),
+    (Color::Grey, [192, 192, 192]),
+    (Color::DarkGrey, [128, 128, 128]),
+    (Color::Red, [255, 0, 0]),
+    (Color::Green, [0, 255, 0]),
+    (Color::Yellow, [255, 255, 0]),
+    (Color::Blue, [0, 0, 255]),
+    (Color::Magenta, [255, 0, 255]),
+    (Color::Cyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+fn distance(a: [f32; 3], b: [u8; 3]) -> f32 {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| (a - *b as f32).powi(2))
+        .sum()
+}
+
+/// the perceived brightness of the color, from 0.0 to 255.0
+pub(super) fn luminance([r, g, b]: [f32; 3]) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+/// the nearest of the 16 colors
+pub(super) fn nearest_ansi16(rgb: [f32; 3]) -> (Color, [u8; 3]) {
+    ANSI16
+        .iter()
+        .copied()
+        .min_by(|(_, a), (_, b)| {
+            distance(rgb, *a)
+                .partial_cmp(&distance(rgb, *b))
+                .expect("must not be NaN")
+        })
+        .expect("must have colors")
+}
+
+/// the nearest of the 256 colors, only the color cube and the grays are used
+/// since the first 16 colors are often changed by the terminal theme
+pub(super) fn nearest_ansi256(rgb: [f32; 3]) -> (Color, [u8; 3]) {
+    let level = |channel: f32| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|i| (CUBE_LEVELS[*i] as f32 - channel).abs() as u32)
+            .expect("must have levels")
+    };
+    let (r, g, b) = (level(rgb[0]), level(rgb[1]), level(rgb[2]));
+    let cube = [CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]];
+    let cube_index = 16 + 36 * r + 6 * g + b;
+
+    let average = (rgb[0] + rgb[1] + rgb[2]) / 3.0;
+    let gray_step = ((average - 8.0) / 10.0).round().clamp(0.0, 23.0) as u8;
+    let gray_value = 8 + 10 * gray_step;
+    let gray = [gray_value; 3];
+
+    if distance(rgb, gray) < distance(rgb, cube) {
+        (Color::AnsiValue(232 + gray_step), gray)
+    } else {
+        (Color::AnsiValue(cube_index as u8), cube)
+    }
+}
+
+/// add the weighted error to the pixel at this location, if it is inside the image
+fn spread(
+    pixels: &mut [Vec<[f32; 3]>],
+    (x, y): (usize, usize),
+    error: [f32; 3],
+    weight: f32,
+) {
+    if let Some(pixel) = pixels.get_mut(y).and_then(|row| row.get_mut(x)) {
+        for (channel, error) in pixel.iter_mut().zip(error.iter()) {
+            *channel = (*channel + error * weight).clamp(0.0, 255.0);
+        }
+    }
+}
+
+/// map each pixel to the nearest value, the difference of the pixel
+/// and the rgb of the chosen value is spread to the pixels not yet visited
+pub(super) fn dither<T, F>(image: &RgbImage, nearest: F) -> Vec<Vec<T>>
+where
+    F: Fn([f32; 3]) -> (T, [u8; 3]),
+{
+    let (width, height) = (image.width() as usize, image.height() as usize);
+    let mut pixels: Vec<Vec<[f32; 3]>> = (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    let pixel = image.get_pixel(x as u32, y as u32);
+                    [pixel[0] as f32, pixel[1] as f32, pixel[2] as f32]
+                })
+                .collect()
+        })
+        .collect();
+    let mut values = Vec::with_capacity(height);
+    for y in 0..height {
+        let mut row = Vec::with_capacity(width);
+        for x in 0..width {
+            let pixel = pixels[y][x];
+            let (value, chosen) = nearest(pixel);
+            let error = [
+                pixel[0] - chosen[0] as f32,
+                pixel[1] - chosen[1] as f32,
+                pixel[2] - chosen[2] as f32,
+            ];
+            spread(&mut pixels, (x + 1, y), error, 7.0 / 16.0);
+            if x > 0 {
+                spread(&mut pixels, (x - 1, y + 1), error, 3.0 / 16.0);
+            }
+            spread(&mut pixels, (x, y + 1), error, 5.0 / 16.0);
+            spread(&mut pixels, (x + 1, y + 1), error, 1.0 / 16.0);
+            row.push(value);
+        }
+        values.push(row);
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    #[test]
+    fn nearest_colors() {
+        assert_eq!(nearest_ansi16([250.0, 10.0, 10.0]).0, Color::Red);
+        assert_eq!(nearest_ansi256([255.0, 0.0, 0.0]).0, Color::AnsiValue(196));
+        assert_eq!(
+            nearest_ansi256([120.0, 121.0, 119.0]).0,
+            Color::AnsiValue(243)
+        );
+    }
+
+    #[test]
+    fn gray_is_dithered_into_black_and_white() {
+        let image = RgbImage::from_pixel(4, 4, Rgb([128, 128, 128]));
+        let white = dither(&image, |rgb| {
+            if luminance(rgb) > 127.5 {
+                (true, [255; 3])
+            } else {
+                (false, [0; 3])
+            }
+        });
+        let count = white.iter().flatten().filter(|white| **white).count();
+        assert_eq!(count, 8);
+    }
+}
@@ -0,0 +1,179 @@
+//! Encode the image into the escape sequences of the terminal graphics
+//! protocols, the pixels are then drawn by the terminal itself.
+use super::dither::{dither, nearest_ansi256};
+use crate::clipboard::base64;
+use crate::crossterm::style::Color;
+use image::RgbImage;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// the maximum length of the base64 data in each chunk of the kitty graphics
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// the number of pixel rows in each band of the sixel data
+const SIXEL_BAND_HEIGHT: usize = 6;
+
+/// the id of the next image, used by kitty to replace the image
+static NEXT_IMAGE_ID: AtomicU32 = AtomicU32::new(1);
+
+/// a new unique id of an image
+pub(super) fn next_image_id() -> u32 {
+    NEXT_IMAGE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// the sixel graphics of the image, the colors are reduced to the 256 colors
+/// which are used as the color registers
+pub(super) fn encode_sixel(image: &RgbImage) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let pixels = dither(image, |pixel| match nearest_ansi256(pixel) {
+        (Color::AnsiValue(register), rgb) => ((register, rgb), rgb),
+        _ => unreachable!("must be one of the 256 colors"),
+    });
+    let mut sixel = format!("\x1bPq\"1;1;{};{}", width, height);
+
+    let palette: BTreeMap<u8, [u8; 3]> =
+        pixels.iter().flatten().copied().collect();
+    for (register, rgb) in palette.iter() {
+        let [r, g, b] =
+            rgb.map(|channel| (channel as f32 * 100.0 / 255.0).round() as u8);
+        sixel += &format!("#{};2;{};{};{}", register, r, g, b);
+    }
+
+    let bands: Vec<String> = pixels
+        .chunks(SIXEL_BAND_HEIGHT)
+        .map(|band| {
+            let registers: BTreeSet<u8> = band
+                .iter()
+                .flatten()
+                .map(|(register, _)| *register)
+                .collect();
+            // each color of the band is drawn over the same row,
+            // `$` moves back to the start of the band
+            let colors: Vec<String> = registers
+                .iter()
+                .map(|register| {
+                    let sixels = (0..width as usize).map(|x| {
+                        let bits = band
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, row)| row[x].0 == *register)
+                            .fold(0, |bits, (k, _)| bits | 1 << k);
+                        (63 + bits) as u8 as char
+                    });
+                    format!("#{}{}", register, run_length(sixels))
+                })
+                .collect();
+            colors.join("$")
+        })
+        .collect();
+    sixel += &bands.join("-");
+    sixel += "\x1b\\";
+    sixel.into_bytes()
+}
+
+/// the kitty graphics which deletes the image with this id
+pub(crate) fn delete_kitty(image_id: u32) -> String {
+    format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", image_id)
+}
+
+/// repeated sixels are written as `!<count><sixel>`
+fn run_length(sixels: impl Iterator<Item = char>) -> String {
+    let mut runs: Vec<(char, usize)> = vec![];
+    for ch in sixels {
+        match runs.last_mut() {
+            Some((last, count)) if *last == ch => *count += 1,
+            _ => runs.push((ch, 1)),
+        }
+    }
+    runs.into_iter()
+        .map(|(ch, count)| {
+            if count > 3 {
+                format!("!{}{}", count, ch)
+            } else {
+                ch.to_string().repeat(count)
+            }
+        })
+        .collect()
+}
+
+/// the kitty graphics of the image, scaled by the terminal to fill the
+/// columns and rows. The previous placement of the same image is deleted
+/// first, so the image is replaced when it is moved or changed.
+pub(super) fn encode_kitty(
+    image: &RgbImage,
+    image_id: u32,
+    columns: usize,
+    rows: usize,
+) -> Vec<u8> {
+    let (width, height) = image.dimensions();
+    let data = base64(image.as_raw());
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect();
+
+    let mut kitty = delete_kitty(image_id);
+    for (n, chunk) in chunks.iter().enumerate() {
+        // whether more chunks follows this chunk
+        let more = if n + 1 < chunks.len() { 1 } else { 0 };
+        if n == 0 {
+            kitty += &format!(
+                "\x1b_Ga=T,f=24,s={},v={},i={},c={},r={},C=1,q=2,m={};",
+                width, height, image_id, columns, rows, more
+            );
+        } else {
+            kitty += &format!("\x1b_Gm={};", more);
+        }
+        kitty += std::str::from_utf8(chunk).expect("must be base64");
+        kitty += "\x1b\\";
+    }
+    kitty.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgb;
+
+    fn sixel(image: &RgbImage) -> String {
+        String::from_utf8(encode_sixel(image)).expect("must be utf8")
+    }
+
+    #[test]
+    fn sixel_fixtures() {
+        let red = RgbImage::from_pixel(2, 1, Rgb([255, 0, 0]));
+        assert_eq!(sixel(&red), "\x1bPq\"1;1;2;1#196;2;100;0;0#196@@\x1b\\");
+
+        let red = RgbImage::from_pixel(5, 1, Rgb([255, 0, 0]));
+        assert_eq!(sixel(&red), "\x1bPq\"1;1;5;1#196;2;100;0;0#196!5@\x1b\\");
+
+        // red over blue, each color is drawn over the same band
+        let mut image = RgbImage::from_pixel(1, 2, Rgb([255, 0, 0]));
+        image.put_pixel(0, 1, Rgb([0, 0, 255]));
+        assert_eq!(
+            sixel(&image),
+            "\x1bPq\"1;1;1;2#21;2;0;0;100#196;2;100;0;0#21A$#196@\x1b\\"
+        );
+
+        // the 7th row is in the next band
+        let image = RgbImage::from_pixel(1, 7, Rgb([255, 0, 0]));
+        assert_eq!(
+            sixel(&image),
+            "\x1bPq\"1;1;1;7#196;2;100;0;0#196~-#196@\x1b\\"
+        );
+    }
+
+    #[test]
+    fn kitty_fixtures() {
+        let red = RgbImage::from_pixel(1, 1, Rgb([255, 0, 0]));
+        assert_eq!(
+            String::from_utf8(encode_kitty(&red, 7, 1, 1)).unwrap(),
+            "\x1b_Ga=d,d=I,i=7,q=2\x1b\\\
+             \x1b_Ga=T,f=24,s=1,v=1,i=7,c=1,r=1,C=1,q=2,m=0;/wAA\x1b\\"
+        );
+
+        // 7500 bytes of data is 10000 bytes in base64, sent in 3 chunks
+        let image = RgbImage::from_pixel(50, 50, Rgb([0, 0, 0]));
+        let kitty = String::from_utf8(encode_kitty(&image, 1, 5, 3)).unwrap();
+        assert!(kitty.contains("s=50,v=50,i=1,c=5,r=3,C=1,q=2,m=1;"));
+        assert_eq!(kitty.matches("\x1b_Gm=1;").count(), 1);
+        assert_eq!(kitty.matches("\x1b_Gm=0;").count(), 1);
+    }
+}
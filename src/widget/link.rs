@@ -192,13 +192,11 @@ impl<MSG> Widget<MSG> for Link {
         }
 
         let _inner_width = self.inner_width(&layout);
-        for (t, ch) in self.get_label().chars().enumerate() {
-            buf.set_symbol(
-                (left + self.border_left() + t as f32) as usize,
-                (top + self.border_top()) as usize,
-                ch,
-            );
-        }
+        buf.write_str(
+            (left + self.border_left()) as usize,
+            (top + self.border_top()) as usize,
+            self.get_label(),
+        );
 
         vec![]
     }
@@ -1,4 +1,4 @@
-use crate::{buffer::Buffer, Callback, Cmd, Event, Widget};
+use crate::{buffer::Buffer, Callback, Cmd, Event, StyledText, Widget};
 use expanse::{
     geometry::Size,
     result::Layout,
@@ -11,7 +11,7 @@ use std::fmt;
 #[derive(Default, Debug)]
 pub struct ListBox<MSG> {
     layout: Option<Layout>,
-    list: Vec<String>,
+    list: Vec<StyledText>,
     width: Option<f32>,
     height: Option<f32>,
     flex_direction: FlexDirection,
@@ -71,6 +71,11 @@ impl<MSG> ListBox<MSG> {
 
     /// set the list of this listbox;
     pub fn set_list(&mut self, list: Vec<String>) {
+        self.list = list.into_iter().map(StyledText::from).collect();
+    }
+
+    /// set the list of this listbox, where the items have colors and attributes
+    pub fn set_styled_list(&mut self, list: Vec<StyledText>) {
         self.list = list;
     }
 
@@ -95,10 +100,11 @@ impl<MSG> ListBox<MSG> {
             let item_bottom = item_top + 2.0;
             if item_bottom < bottom {
                 let mut canvas = Canvas::new();
-                buf.write_str(
+                buf.write_styled_truncated(
                     (item_left + 1.0) as usize,
                     (item_top + 1.0) as usize,
                     li,
+                    (item_right - item_left).max(0.0) as usize,
                 );
                 if self.use_divider {
                     canvas.draw_horizontal_line(
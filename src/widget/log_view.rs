@@ -0,0 +1,358 @@
+use crate::crossterm::event::{KeyCode, KeyEvent};
+use crate::{buffer::Buffer, Cmd, Event, StyledText, Widget};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use ito_canvas::unicode_canvas::{Border, Canvas};
+use std::{
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+/// the number of lines kept by default
+const DEFAULT_MAX_LINES: usize = 1000;
+
+/// the number of lines scrolled with the mouse wheel
+const SCROLL_LINES: usize = 3;
+
+/// A scrolling view of log lines, such as the output of a child process.
+/// The ANSI colors in the lines are displayed as styles.
+/// The view follows the newest line until it is scrolled up,
+/// scrolling back to the bottom follows the newest line again.
+pub struct LogView<MSG> {
+    layout: Option<Layout>,
+    lines: VecDeque<StyledText>,
+    /// the oldest lines are dropped when this number is exceeded
+    max_lines: usize,
+    /// the first visible line, used when not following the newest line
+    scroll_top: usize,
+    /// keep the newest line visible
+    follow: bool,
+    /// text sent from other threads, they are added to the lines on tick
+    channel: Option<(Sender<String>, Receiver<String>)>,
+    focused: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    _phantom_msg: PhantomData<MSG>,
+}
+
+impl<MSG> Default for LogView<MSG> {
+    fn default() -> Self {
+        LogView {
+            layout: None,
+            lines: VecDeque::new(),
+            max_lines: DEFAULT_MAX_LINES,
+            scroll_top: 0,
+            follow: true,
+            channel: None,
+            focused: false,
+            width: None,
+            height: None,
+            id: None,
+            _phantom_msg: PhantomData,
+        }
+    }
+}
+
+impl<MSG> LogView<MSG> {
+    /// create a new empty log view
+    pub fn new() -> Self {
+        LogView::default()
+    }
+
+    /// add the text which may contain ANSI escape sequences,
+    /// each of the new line in the text starts a new line in the log
+    pub fn push<S: AsRef<str>>(&mut self, text: S) {
+        for line in StyledText::from_ansi(text.as_ref()).lines() {
+            self.push_styled(line);
+        }
+    }
+
+    /// add a line which is already styled
+    pub fn push_styled(&mut self, line: StyledText) {
+        self.lines.push_back(line);
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+            self.scroll_top = self.scroll_top.saturating_sub(1);
+        }
+    }
+
+    /// remove all the lines
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.scroll_top = 0;
+        self.follow = true;
+    }
+
+    /// return the lines of this log view, from the oldest to the newest
+    pub fn lines(&self) -> &VecDeque<StyledText> {
+        &self.lines
+    }
+
+    /// set the maximum number of lines kept in the log
+    pub fn set_max_lines(&mut self, max_lines: usize) {
+        self.max_lines = max_lines;
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    /// return a sender which can be used to stream the text from other threads,
+    /// the text is added when the renderer ticks, so the renderer needs a tick rate.
+    pub fn sender(&mut self) -> Sender<String> {
+        let (sender, _) = self.channel.get_or_insert_with(mpsc::channel);
+        sender.clone()
+    }
+
+    /// whether the view is following the newest line
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    fn inner_height(&self) -> usize {
+        self.layout
+            .map(|layout| layout.size.height.round() as usize)
+            .unwrap_or(0)
+            .saturating_sub(2)
+    }
+
+    /// the first line which can be scrolled to, where the last line
+    /// is at the bottom of the view
+    fn max_scroll_top(&self, inner_height: usize) -> usize {
+        self.lines.len().saturating_sub(inner_height)
+    }
+
+    /// the first visible line
+    fn visible_top(&self, inner_height: usize) -> usize {
+        let max_scroll_top = self.max_scroll_top(inner_height);
+        if self.follow {
+            max_scroll_top
+        } else {
+            self.scroll_top.min(max_scroll_top)
+        }
+    }
+
+    /// scroll the view to this line, following the newest line
+    /// when scrolled to the bottom
+    fn scroll_to(&mut self, top: usize) {
+        let max_scroll_top = self.max_scroll_top(self.inner_height());
+        self.scroll_top = top.min(max_scroll_top);
+        self.follow = self.scroll_top == max_scroll_top;
+    }
+
+    fn scroll_up(&mut self, lines: usize) {
+        let top = self.visible_top(self.inner_height());
+        self.scroll_to(top.saturating_sub(lines));
+    }
+
+    fn scroll_down(&mut self, lines: usize) {
+        let top = self.visible_top(self.inner_height());
+        self.scroll_to(top + lines);
+    }
+
+    fn process_key(&mut self, key_event: KeyEvent) {
+        let page = self.inner_height().max(1);
+        match key_event.code {
+            KeyCode::Up => self.scroll_up(1),
+            KeyCode::Down => self.scroll_down(1),
+            KeyCode::PageUp => self.scroll_up(page),
+            KeyCode::PageDown => self.scroll_down(page),
+            KeyCode::Home => self.scroll_to(0),
+            KeyCode::End => self.scroll_to(self.lines.len()),
+            _ => (),
+        }
+    }
+
+    fn draw_border(&self, buf: &mut Buffer, layout: &Layout) {
+        let left = layout.location.x.round() as usize;
+        let top = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        let height = layout.size.height.round() as usize;
+        if width < 2 || height < 2 {
+            return;
+        }
+        let right = left + width - 1;
+        let bottom = top + height - 1;
+        let border = Border {
+            use_thick_border: self.focused,
+            has_top: true,
+            has_bottom: true,
+            has_left: true,
+            has_right: true,
+            is_top_left_rounded: false,
+            is_top_right_rounded: false,
+            is_bottom_left_rounded: false,
+            is_bottom_right_rounded: false,
+        };
+        let mut canvas = Canvas::new();
+        canvas.draw_rect((left, top), (right, bottom), border);
+        buf.write_canvas(canvas);
+    }
+}
+
+impl<MSG> Widget<MSG> for LogView<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        self.draw_border(buf, &layout);
+        let inner_left = layout.location.x.round() as usize + 1;
+        let inner_top = layout.location.y.round() as usize + 1;
+        let inner_width =
+            (layout.size.width.round() as usize).saturating_sub(2);
+        let inner_height = self.inner_height();
+        let top = self.visible_top(inner_height);
+        for (j, line) in
+            self.lines.iter().skip(top).take(inner_height).enumerate()
+        {
+            buf.write_styled(
+                inner_left,
+                inner_top + j,
+                &line.take_width(inner_width),
+            );
+        }
+        vec![]
+    }
+
+    fn tick(&mut self) {
+        let received: Vec<String> = match &self.channel {
+            Some((_, receiver)) => receiver.try_iter().collect(),
+            None => return,
+        };
+        for text in received {
+            self.push(text);
+        }
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn process_event(&mut self, event: Event) -> Vec<MSG> {
+        match event {
+            Event::Key(ke) => self.process_key(ke),
+            _ if event.is_scrollup() => self.scroll_up(SCROLL_LINES),
+            _ if event.is_scrolldown() => self.scroll_down(SCROLL_LINES),
+            _ => (),
+        }
+        vec![]
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for LogView<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LogView")
+            .field("lines", &self.lines.len())
+            .field("follow", &self.follow)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossterm::style::Color;
+    use expanse::number::Number;
+
+    fn key(code: KeyCode) -> Event {
+        Event::from(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn ansi_lines_are_styled() {
+        let mut log = LogView::<()>::new();
+        log.push("\u{1b}[32mok\u{1b}[0m\n\u{1b}[31merror");
+        assert_eq!(log.lines().len(), 2);
+        assert_eq!(log.lines()[0].as_str(), "ok");
+        assert_eq!(
+            log.lines()[1].spans()[0].foreground_color,
+            Some(Color::DarkRed)
+        );
+        log.set_max_lines(1);
+        assert_eq!(log.lines()[0].as_str(), "error");
+    }
+
+    #[test]
+    fn follows_until_scrolled_up() {
+        let mut log = LogView::<()>::new();
+        log.set_size(None, Some(5.0));
+        log.compute_node_layout(Size {
+            width: Number::Defined(20.0),
+            height: Number::Defined(5.0),
+        });
+        for i in 0..10 {
+            log.push(i.to_string());
+        }
+        // 3 lines are visible inside the border
+        assert_eq!(log.visible_top(3), 7);
+        log.process_event(key(KeyCode::Up));
+        assert!(!log.is_following());
+        assert_eq!(log.visible_top(3), 6);
+        log.push("10");
+        assert_eq!(log.visible_top(3), 6);
+        log.process_event(key(KeyCode::End));
+        assert!(log.is_following());
+        assert_eq!(log.visible_top(3), 8);
+    }
+
+    #[test]
+    fn zero_size_is_not_drawn() {
+        let mut log = LogView::<()>::new();
+        log.push("hidden");
+        log.set_size(Some(0.0), Some(0.0));
+        log.compute_node_layout(Size {
+            width: Number::Defined(10.0),
+            height: Number::Defined(5.0),
+        });
+        let mut buf = Buffer::new(10, 5);
+        log.draw(&mut buf);
+        assert!(buf.cells.iter().flatten().all(|cell| cell.is_blank()));
+    }
+}
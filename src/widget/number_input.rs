@@ -0,0 +1,507 @@
+use crate::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::event::InputEvent;
+use crate::Callback;
+use crate::Event;
+use crate::Value;
+use crate::{
+    buffer::Buffer, cmd::Cmd, symbol, text_buffer::InputBuffer, Widget,
+};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use ito_canvas::unicode_canvas::{Border, Canvas};
+use std::fmt;
+
+/// the number of steps to move on page up and page down
+const PAGE_STEPS: f64 = 10.0;
+
+/// the number of columns used by the up and down arrows
+const ARROWS_WIDTH: usize = 2;
+
+/// A one line input which accepts only numbers, the value is changed
+/// by typing or by steps with Up/Down keys, the mouse wheel and the arrows.
+#[derive(Debug)]
+pub struct NumberInput<MSG> {
+    layout: Option<Layout>,
+    input_buffer: InputBuffer,
+    value: f64,
+    min: f64,
+    max: f64,
+    step: f64,
+    /// the number of decimal places, derived from the step when not set
+    precision: Option<usize>,
+    /// only whole numbers are accepted
+    is_integer: bool,
+    is_rounded: bool,
+    has_border: bool,
+    focused: bool,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    on_input: Vec<Callback<Event, MSG>>,
+}
+
+impl<MSG> Default for NumberInput<MSG> {
+    fn default() -> Self {
+        NumberInput {
+            layout: None,
+            input_buffer: InputBuffer::new_with_value("0"),
+            value: 0.0,
+            min: f64::NEG_INFINITY,
+            max: f64::INFINITY,
+            step: 1.0,
+            precision: None,
+            is_integer: false,
+            is_rounded: false,
+            has_border: true,
+            focused: false,
+            width: None,
+            height: None,
+            id: None,
+            on_input: vec![],
+        }
+    }
+}
+
+impl<MSG> NumberInput<MSG> {
+    /// create a new number input with value
+    pub fn new(value: f64) -> Self {
+        let mut number_input = NumberInput::default();
+        number_input.set_value(value);
+        number_input
+    }
+
+    /// set the value, the value is clamped to the range
+    pub fn set_value(&mut self, value: f64) {
+        self.value = self.normalize(value);
+        self.format_value();
+    }
+
+    /// return the value of this number input
+    pub fn get_value(&self) -> f64 {
+        self.value
+    }
+
+    /// set the minimum and maximum value, the default is unbounded.
+    /// The range is ignored when min is greater than max, or either is NaN
+    pub fn set_range(&mut self, min: f64, max: f64) {
+        if min.is_nan() || max.is_nan() || min > max {
+            return;
+        }
+        self.min = min;
+        self.max = max;
+        self.set_value(self.value);
+    }
+
+    /// set the amount the value changes with the arrows, the default is 1.0.
+    /// The step is ignored when it is not positive, or is not finite
+    pub fn set_step(&mut self, step: f64) {
+        if !step.is_finite() || step <= 0.0 {
+            return;
+        }
+        self.step = step;
+        self.format_value();
+    }
+
+    /// set the number of decimal places used in displaying the value
+    pub fn set_precision(&mut self, precision: usize) {
+        self.precision = Some(precision);
+        self.set_value(self.value);
+    }
+
+    /// set whether only whole numbers are accepted
+    pub fn set_integer(&mut self, is_integer: bool) {
+        self.is_integer = is_integer;
+        self.set_value(self.value);
+    }
+
+    /// set whether to use rounded corner when drawing the border
+    pub fn set_rounded(&mut self, rounded: bool) {
+        self.is_rounded = rounded;
+    }
+
+    /// attach a callback which is called when the value is changed by the user,
+    /// the value is sent as `Value::F64`
+    pub fn on_input<F>(&mut self, f: F)
+    where
+        F: FnMut(Event) -> MSG + 'static,
+    {
+        self.on_input.push(f.into());
+    }
+
+    /// clamp the value to the range, whole numbers are rounded
+    fn normalize(&self, value: f64) -> f64 {
+        let value = if self.is_integer {
+            value.round()
+        } else {
+            value
+        };
+        value.clamp(self.min, self.max)
+    }
+
+    /// the number of decimal places used in displaying the value
+    fn precision(&self) -> usize {
+        match self.precision {
+            _ if self.is_integer => 0,
+            Some(precision) => precision,
+            // the decimal places of the step, so 0.25 is displayed with 2
+            None => self
+                .step
+                .to_string()
+                .split_once('.')
+                .map_or(0, |(_, decimals)| decimals.len()),
+        }
+    }
+
+    /// the value formatted with the precision
+    fn value_label(&self) -> String {
+        format!("{:.*}", self.precision(), self.value)
+    }
+
+    /// replace the text with the formatted value
+    fn format_value(&mut self) {
+        self.input_buffer.set_content(self.value_label());
+    }
+
+    /// set the value and return the messages of the on_input callbacks
+    /// when the value has changed
+    fn change_value(&mut self, value: f64) -> Vec<MSG> {
+        let value = self.normalize(value);
+        let is_changed = (value - self.value).abs() >= f64::EPSILON;
+        self.value = value;
+        // the text is formatted even when the value is the same
+        self.format_value();
+        if is_changed {
+            self.emit_input()
+        } else {
+            vec![]
+        }
+    }
+
+    fn emit_input(&mut self) -> Vec<MSG> {
+        let event = Event::from(InputEvent::from(Value::F64(self.value)));
+        self.on_input
+            .iter_mut()
+            .map(|cb| cb.emit(event.clone()))
+            .collect()
+    }
+
+    /// return true if the character can be typed at the cursor
+    fn accepts_char(&self, c: char) -> bool {
+        let text = self.input_buffer.get_content();
+        let cursor = self.input_buffer.get_cursor_location();
+        match c {
+            '0'..='9' => true,
+            '-' => cursor == 0 && !text.starts_with('-') && self.min < 0.0,
+            '.' => !self.is_integer && !text.contains('.'),
+            _ => false,
+        }
+    }
+
+    /// type into the text, the value is changed when the text is
+    /// a number within the range
+    fn process_text_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        if let KeyCode::Char(c) = key_event.code {
+            let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+            if !ctrl && !self.accepts_char(c) {
+                return vec![];
+            }
+        }
+        self.input_buffer.process_key_event(key_event);
+        match self.input_buffer.get_content().parse::<f64>() {
+            Ok(value)
+                if value >= self.min
+                    && value <= self.max
+                    && (value - self.value).abs() >= f64::EPSILON =>
+            {
+                self.value = value;
+                self.emit_input()
+            }
+            _ => vec![],
+        }
+    }
+
+    /// set the value from the text, clamped to the range,
+    /// the text which is not a number is reverted to the value
+    fn commit_text(&mut self) -> Vec<MSG> {
+        // the displayed value is not rounded again
+        if self.input_buffer.get_content() == self.value_label() {
+            return vec![];
+        }
+        let value = self
+            .input_buffer
+            .get_content()
+            .parse::<f64>()
+            .unwrap_or(self.value);
+        self.change_value(value)
+    }
+
+    fn process_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        match key_event.code {
+            KeyCode::Up => self.change_value(self.value + self.step),
+            KeyCode::Down => self.change_value(self.value - self.step),
+            KeyCode::PageUp => {
+                self.change_value(self.value + self.step * PAGE_STEPS)
+            }
+            KeyCode::PageDown => {
+                self.change_value(self.value - self.step * PAGE_STEPS)
+            }
+            KeyCode::Enter => self.commit_text(),
+            _ => self.process_text_key(key_event),
+        }
+    }
+
+    fn process_mouse(&mut self, event: &Event) -> Vec<MSG> {
+        let layout = self.layout.expect("must have a layout");
+        if event.is_mouse_click() {
+            let (x, y) =
+                event.extract_location().expect("must have a location");
+            let left = layout.location.x.round() as usize + self.border();
+            let top = layout.location.y.round() as usize + self.border();
+            let arrows_left = left + self.text_width(&layout);
+            let (x, y) = (x as usize, y as usize);
+            if y != top {
+                vec![]
+            } else if x == arrows_left {
+                self.change_value(self.value + self.step)
+            } else if x == arrows_left + 1 {
+                self.change_value(self.value - self.step)
+            } else {
+                self.input_buffer.set_cursor_column(x.saturating_sub(left));
+                vec![]
+            }
+        } else if event.is_scrollup() {
+            self.change_value(self.value + self.step)
+        } else if event.is_scrolldown() {
+            self.change_value(self.value - self.step)
+        } else {
+            vec![]
+        }
+    }
+
+    fn border(&self) -> usize {
+        if self.has_border {
+            1
+        } else {
+            0
+        }
+    }
+
+    /// the width where the text is displayed, the arrows are at the right of it
+    fn text_width(&self, layout: &Layout) -> usize {
+        (layout.size.width.round() as usize)
+            .saturating_sub(self.border() * 2 + ARROWS_WIDTH)
+    }
+}
+
+impl<MSG> Widget<MSG> for NumberInput<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+    fn style(&self) -> Style {
+        let size = Size {
+            width: if let Some(width) = self.width {
+                Dimension::Points(width)
+            } else {
+                Dimension::Percent(1.0)
+            },
+            height: if let Some(height) = self.height {
+                Dimension::Points(height)
+            } else {
+                Dimension::Points(3.0)
+            },
+        };
+        Style {
+            position_type: PositionType::Relative,
+            size,
+            min_size: size,
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        let left = layout.location.x.round() as usize;
+        let top = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        let height = layout.size.height.round() as usize;
+
+        if self.has_border && width > 0 && height > 0 {
+            let border = Border {
+                use_thick_border: self.focused,
+                has_top: true,
+                has_bottom: true,
+                has_left: true,
+                has_right: true,
+                is_top_left_rounded: self.is_rounded,
+                is_top_right_rounded: self.is_rounded,
+                is_bottom_left_rounded: self.is_rounded,
+                is_bottom_right_rounded: self.is_rounded,
+            };
+            let mut canvas = Canvas::new();
+            canvas.draw_rect(
+                (left, top),
+                (left + width - 1, top + height - 1),
+                border,
+            );
+            buf.write_canvas(canvas);
+        }
+
+        let text_left = left + self.border();
+        let text_top = top + self.border();
+        let text_width = self.text_width(&layout);
+        buf.write_str_truncated(
+            text_left,
+            text_top,
+            self.input_buffer.get_content(),
+            text_width,
+        );
+        buf.set_symbol(text_left + text_width, text_top, symbol::ARROW_UP);
+        buf.set_symbol(
+            text_left + text_width + 1,
+            text_top,
+            symbol::ARROW_DOWN,
+        );
+
+        if self.focused {
+            let cursor_x =
+                self.input_buffer.get_cursor_column().min(text_width);
+            vec![Cmd::ShowCursor, Cmd::MoveTo(text_left + cursor_x, text_top)]
+        } else {
+            vec![]
+        }
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        // the text which is not a valid number is reverted when leaving
+        if self.focused && !focused {
+            self.format_value();
+        }
+        self.focused = focused;
+    }
+
+    fn value(&self) -> Option<Value> {
+        Some(Value::F64(self.value))
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn process_event(&mut self, event: Event) -> Vec<MSG> {
+        match event {
+            Event::Key(ke) => self.process_key(ke),
+            Event::Mouse(_) => self.process_mouse(&event),
+            _ => vec![],
+        }
+    }
+
+    fn captures_key(&self, key_event: &KeyEvent) -> bool {
+        // undo instead of quitting
+        key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(key_event.code, KeyCode::Char('z') | KeyCode::Char('Z'))
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expanse::number::Number;
+
+    #[test]
+    fn typing_and_steps() {
+        let mut number_input = NumberInput::new(8080.0);
+        number_input.set_range(0.0, 65535.0);
+        number_input.on_input(|event| match event {
+            Event::InputEvent(ie) => ie.value.as_f64(),
+            _ => None,
+        });
+        number_input.process_key(KeyCode::Backspace.into());
+        let msgs = number_input.process_key(KeyCode::Char('1').into());
+        assert_eq!(msgs, vec![Some(8081.0)]);
+        // letters and the minus sign are not accepted
+        number_input.process_key(KeyCode::Char('x').into());
+        number_input.process_key(KeyCode::Home.into());
+        number_input.process_key(KeyCode::Char('-').into());
+        assert_eq!(number_input.input_buffer.get_content(), "8081");
+
+        let msgs = number_input.process_key(KeyCode::Up.into());
+        assert_eq!(msgs, vec![Some(8082.0)]);
+        number_input.process_key(KeyCode::End.into());
+        number_input.process_key(KeyCode::Char('9').into());
+        // out of range, the value is kept until Enter clamps it
+        assert_eq!(number_input.get_value(), 8082.0);
+        let msgs = number_input.process_key(KeyCode::Enter.into());
+        assert_eq!(msgs, vec![Some(65535.0)]);
+        assert_eq!(number_input.input_buffer.get_content(), "65535");
+
+        // invalid range and step are ignored
+        number_input.set_range(10.0, 1.0);
+        number_input.set_range(f64::NAN, 1.0);
+        number_input.set_step(-1.0);
+        number_input.set_step(f64::NAN);
+        let msgs = number_input.process_key(KeyCode::Down.into());
+        assert_eq!(msgs, vec![Some(65534.0)]);
+    }
+
+    #[test]
+    fn precision_and_arrows() {
+        let mut number_input = NumberInput::<()>::new(0.5);
+        number_input.set_step(0.25);
+        number_input.set_precision(2);
+        number_input.set_size(Some(10.0), Some(3.0));
+        number_input.compute_node_layout(Size {
+            width: Number::Defined(10.0),
+            height: Number::Defined(3.0),
+        });
+        let mut buf = Buffer::new(10, 3);
+        number_input.draw(&mut buf);
+        let row: String = buf.cells[1][1..9]
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect();
+        assert_eq!(row, "0.50  ▲▼");
+        number_input.process_key(KeyCode::Down.into());
+        number_input.process_key(KeyCode::Down.into());
+        number_input.process_key(KeyCode::Down.into());
+        assert_eq!(number_input.input_buffer.get_content(), "-0.25");
+    }
+
+    #[test]
+    fn precision_follows_the_step() {
+        let mut number_input: NumberInput<f64> = NumberInput::new(0.75);
+        number_input.on_input(|event| match event {
+            Event::InputEvent(input) => {
+                input.value.as_f64().expect("must be a number")
+            }
+            _ => unreachable!(),
+        });
+        number_input.set_step(0.25);
+        assert_eq!(number_input.value_label(), "0.75");
+        let msgs = number_input.process_key(KeyCode::Enter.into());
+        assert!(msgs.is_empty());
+        assert_eq!(number_input.get_value(), 0.75);
+
+        number_input.set_step(2.5);
+        assert_eq!(number_input.value_label(), "0.8");
+        number_input.set_step(10.0);
+        assert_eq!(number_input.value_label(), "1");
+    }
+}
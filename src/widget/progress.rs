@@ -0,0 +1,144 @@
+use crate::crossterm::style::{Attribute, Color};
+use crate::{buffer::Cell, unicode};
+
+/// The state shared by the `ProgressBar` and the `Gauge`, a value from 0.0
+/// to 1.0 which is displayed with eighths of a cell along the length
+#[derive(Debug, Default)]
+pub(crate) struct Progress {
+    value: f32,
+    label: Option<String>,
+    show_percentage: bool,
+    is_indeterminate: bool,
+    /// the number of ticks received, used for animating the indeterminate mode
+    tick_count: usize,
+    color: Option<Color>,
+}
+
+impl Progress {
+    /// set the value, clamped to 0.0..=1.0, NaN is set as 0.0
+    pub fn set_value(&mut self, value: f32) {
+        self.value = if value.is_nan() {
+            0.0
+        } else {
+            value.clamp(0.0, 1.0)
+        };
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    pub fn set_label<S: ToString>(&mut self, label: S) {
+        self.label = Some(label.to_string());
+    }
+
+    pub fn show_percentage(&mut self, show_percentage: bool) {
+        self.show_percentage = show_percentage;
+    }
+
+    pub fn set_indeterminate(&mut self, is_indeterminate: bool) {
+        self.is_indeterminate = is_indeterminate;
+        self.tick_count = 0;
+    }
+
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+
+    pub fn color(&self) -> Option<Color> {
+        self.color
+    }
+
+    /// advance the animation of the indeterminate mode
+    pub fn tick(&mut self) {
+        if self.is_indeterminate {
+            self.tick_count = self.tick_count.wrapping_add(1);
+        }
+    }
+
+    /// the text that is displayed on top of the filled cells
+    pub fn text(&self) -> Option<String> {
+        let percentage = if self.show_percentage && !self.is_indeterminate {
+            Some(format!("{:.0}%", self.value * 100.0))
+        } else {
+            None
+        };
+        match (&self.label, percentage) {
+            (Some(label), Some(percentage)) => {
+                Some(format!("{} {}", label, percentage))
+            }
+            (Some(label), None) => Some(label.to_string()),
+            (None, Some(percentage)) => Some(percentage),
+            (None, None) => None,
+        }
+    }
+
+    /// return the number of filled eighths for each of the cells,
+    /// starting from the cell where the filling starts
+    pub fn eighths(&self, length: usize) -> Vec<usize> {
+        if self.is_indeterminate {
+            let segment = (length / 4).max(1).min(length);
+            let start = bounce(self.tick_count, length - segment);
+            (0..length)
+                .map(|i| {
+                    if i >= start && i < start + segment {
+                        8
+                    } else {
+                        0
+                    }
+                })
+                .collect()
+        } else {
+            let filled = (self.value * (length * 8) as f32).round() as usize;
+            (0..length)
+                .map(|i| filled.saturating_sub(i * 8).min(8))
+                .collect()
+        }
+    }
+
+    /// write the text at the center of the cells, the part of the text on top
+    /// of the filled cells are drawn with inverted colors
+    pub fn overlay_text(&self, cells: &mut [Cell], eighths: &[usize]) {
+        let text = match self.text() {
+            Some(text) => text,
+            None => return,
+        };
+        let text_width = unicode::str_width(&text);
+        let mut x = cells.len().saturating_sub(text_width) / 2;
+        for (grapheme, ch_width) in unicode::graphemes(&text) {
+            if ch_width == 0 {
+                continue;
+            }
+            if x + ch_width > cells.len() {
+                break;
+            }
+            let mut cell = Cell::new(grapheme);
+            if eighths[x] >= 4 {
+                if let Some(color) = self.color {
+                    cell.color(color);
+                }
+                cell.attributes(vec![Attribute::Reverse]);
+            }
+            cells[x] = cell;
+            for filler in cells.iter_mut().skip(x + 1).take(ch_width - 1) {
+                *filler = Cell::new('\0');
+            }
+            x += ch_width;
+        }
+    }
+}
+
+/// return a position that goes back and forth from 0 to `travel`
+/// as the tick count increases
+fn bounce(tick_count: usize, travel: usize) -> usize {
+    if travel == 0 {
+        return 0;
+    }
+    let period = 2 * travel;
+    let t = tick_count % period;
+    if t <= travel {
+        t
+    } else {
+        period - t
+    }
+}
@@ -0,0 +1,238 @@
+use super::progress::Progress;
+use crate::crossterm::style::Color;
+use crate::{
+    buffer::{Buffer, Cell},
+    symbol::block,
+    Cmd, Widget,
+};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use std::{fmt, marker::PhantomData};
+
+/// A horizontal progress bar with value from 0.0 to 1.0
+/// The bar is filled with eighth blocks, so it can show sub-cell progress
+///```ignore
+///   ██████████▍      42%
+/// ```
+pub struct ProgressBar<MSG> {
+    layout: Option<Layout>,
+    progress: Progress,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    _phantom_msg: PhantomData<MSG>,
+}
+
+impl<MSG> Default for ProgressBar<MSG> {
+    fn default() -> Self {
+        ProgressBar {
+            layout: None,
+            progress: Progress::default(),
+            width: None,
+            height: None,
+            id: None,
+            _phantom_msg: PhantomData,
+        }
+    }
+}
+
+impl<MSG> ProgressBar<MSG> {
+    /// create a new progress bar with value
+    pub fn new(value: f32) -> Self {
+        let mut progress_bar = ProgressBar::default();
+        progress_bar.set_value(value);
+        progress_bar
+    }
+
+    /// set the value of this progress bar, clamped to 0.0..=1.0
+    pub fn set_value(&mut self, value: f32) {
+        self.progress.set_value(value);
+    }
+
+    /// return the value of this progress bar
+    pub fn get_value(&self) -> f32 {
+        self.progress.value()
+    }
+
+    /// set the label which will be displayed at the center of the bar
+    pub fn set_label<S: ToString>(&mut self, label: S) {
+        self.progress.set_label(label);
+    }
+
+    /// set whether to display the percentage at the center of the bar
+    pub fn show_percentage(&mut self, show_percentage: bool) {
+        self.progress.show_percentage(show_percentage);
+    }
+
+    /// set the indeterminate mode, where the bar is animated back and forth
+    /// since the progress is unknown.
+    /// The renderer needs a tick rate for the animation to run.
+    pub fn set_indeterminate(&mut self, is_indeterminate: bool) {
+        self.progress.set_indeterminate(is_indeterminate);
+    }
+
+    /// set the color of the filled bar
+    pub fn set_color(&mut self, color: Color) {
+        self.progress.set_color(color);
+    }
+
+    /// create the cells of the bar without the text
+    fn bar_cells(&self, eighths: &[usize]) -> Vec<Cell> {
+        eighths
+            .iter()
+            .map(|eighths| {
+                let mut cell = Cell::new(block::eighths(*eighths));
+                if let Some(color) = self.progress.color() {
+                    cell.color(color);
+                }
+                cell
+            })
+            .collect()
+    }
+
+    /// create the cells of the bar, with the text overlaid at the center
+    fn create_cells(&self, width: usize) -> Vec<Cell> {
+        let eighths = self.progress.eighths(width);
+        let mut cells = self.bar_cells(&eighths);
+        self.progress.overlay_text(&mut cells, &eighths);
+        cells
+    }
+}
+
+impl<MSG> Widget<MSG> for ProgressBar<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Points(1.0)
+                },
+            },
+            min_size: Size {
+                height: Dimension::Points(1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        let loc_x = layout.location.x.round() as usize;
+        let loc_y = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        let height = layout.size.height.round() as usize;
+
+        let plain_cells = self.bar_cells(&self.progress.eighths(width));
+        let text_cells = self.create_cells(width);
+        // the text is only drawn at the middle row
+        let middle = height / 2;
+        for j in 0..height {
+            let cells = if j == middle {
+                &text_cells
+            } else {
+                &plain_cells
+            };
+            for (i, cell) in cells.iter().enumerate() {
+                buf.set_cell(loc_x + i, loc_y + j, cell.clone());
+            }
+        }
+        vec![]
+    }
+
+    fn tick(&mut self) {
+        self.progress.tick();
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for ProgressBar<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ProgressBar")
+            .field("value", &self.progress.value())
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossterm::style::Attribute;
+
+    #[test]
+    fn sub_cell_fill() {
+        let progress_bar = ProgressBar::<()>::new(0.35);
+        // 0.35 * 4 cells * 8 = 11.2 eighths
+        assert_eq!(progress_bar.progress.eighths(4), vec![8, 3, 0, 0]);
+        let symbols: String = progress_bar
+            .create_cells(4)
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect();
+        assert_eq!(symbols, "█▍  ");
+    }
+
+    #[test]
+    fn percentage_is_inverted_on_filled_part() {
+        let mut progress_bar = ProgressBar::<()>::new(0.5);
+        progress_bar.show_percentage(true);
+        let cells = progress_bar.create_cells(9);
+        let symbols: String =
+            cells.iter().map(|cell| cell.symbol.clone()).collect();
+        assert_eq!(symbols, "███50%   ");
+        assert!(cells[3].attributes.has(Attribute::Reverse));
+        assert!(!cells[5].attributes.has(Attribute::Reverse));
+    }
+
+    #[test]
+    fn indeterminate_bounces() {
+        let mut progress_bar = ProgressBar::<()>::new(0.0);
+        progress_bar.set_indeterminate(true);
+        assert_eq!(
+            progress_bar.progress.eighths(8),
+            vec![8, 8, 0, 0, 0, 0, 0, 0]
+        );
+        for _ in 0..7 {
+            Widget::<()>::tick(&mut progress_bar);
+        }
+        // travelled to the end and one step back
+        assert_eq!(
+            progress_bar.progress.eighths(8),
+            vec![0, 0, 0, 0, 0, 8, 8, 0]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::Event;
-use crate::{buffer::Buffer, symbol, Callback, Cmd, Widget};
+use crate::{buffer::Buffer, symbol, unicode, Callback, Cmd, Widget};
 use expanse::{
     geometry::Size,
     result::Layout,
@@ -42,6 +42,12 @@ impl<MSG> Radio<MSG> {
         self.is_checked = checked;
     }
 
+    /// return the checked status
+    pub fn is_checked(&self) -> bool {
+        self.is_checked
+    }
+
+    /// attach a callback which is called when this radio is checked by the user
     pub fn on_input<F>(&mut self, f: F)
     where
         F: FnMut(Event) -> MSG + 'static,
@@ -61,7 +67,9 @@ impl<MSG: 'static> Widget<MSG> for Radio<MSG> {
         Style {
             position_type: PositionType::Relative,
             size: Size {
-                width: Dimension::Points((self.label.len() + 3) as f32),
+                width: Dimension::Points(
+                    (unicode::str_width(&self.label) + 3) as f32,
+                ),
                 height: Dimension::Points(1.0),
             },
             min_size: Size {
@@ -84,18 +92,21 @@ impl<MSG: 'static> Widget<MSG> for Radio<MSG> {
         };
         buf.set_symbol(loc_x, loc_y, box_symbol);
 
-        for (t, ch) in self.label.chars().enumerate() {
-            buf.set_symbol(loc_x + 3 + x_offset + t, loc_y, ch);
-        }
+        buf.write_str(loc_x + 3 + x_offset, loc_y, &self.label);
         vec![]
     }
 
     fn set_size(&mut self, _width: Option<f32>, _height: Option<f32>) {}
 
     fn process_event(&mut self, event: Event) -> Vec<MSG> {
-        if event.is_mouse_click() {
-            self.is_checked = !self.is_checked;
-            vec![]
+        // a checked radio can not be unchecked by clicking it again,
+        // use a `RadioGroup` for an exclusive selection of radio buttons
+        if event.is_mouse_click() && !self.is_checked {
+            self.is_checked = true;
+            self.on_input
+                .iter_mut()
+                .map(|cb| cb.emit(event.clone()))
+                .collect()
         } else {
             vec![]
         }
@@ -0,0 +1,312 @@
+use crate::crossterm::event::{KeyCode, KeyEvent};
+use crate::event::InputEvent;
+use crate::{
+    buffer::Buffer, symbol, unicode, Callback, Cmd, Event, Value, Widget,
+};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use std::fmt;
+
+/// the space in between the radio symbol and the label
+const LABEL_OFFSET: usize = 3;
+/// the space in between the options when laid out horizontally
+const OPTION_GAP: usize = 2;
+
+/// A group of radio buttons where exactly one of the options is selected.
+/// The selection can be changed by clicking on the option or with the arrow keys
+/// when the group is focused.
+///```ignore
+///   ⦿  Small
+///   〇 Medium
+///   〇 Large
+/// ```
+pub struct RadioGroup<MSG> {
+    layout: Option<Layout>,
+    options: Vec<String>,
+    selected: Option<usize>,
+    is_horizontal: bool,
+    focused: bool,
+    id: Option<String>,
+    on_change: Vec<Callback<Event, MSG>>,
+}
+
+impl<MSG> RadioGroup<MSG> {
+    /// create a new radio group with the labels of the options,
+    /// the first option is selected
+    pub fn new<S: ToString>(options: Vec<S>) -> Self {
+        let options: Vec<String> =
+            options.into_iter().map(|o| o.to_string()).collect();
+        let selected = if options.is_empty() { None } else { Some(0) };
+        RadioGroup {
+            layout: None,
+            options,
+            selected,
+            is_horizontal: false,
+            focused: false,
+            id: None,
+            on_change: vec![],
+        }
+    }
+
+    /// add an option to this radio group, the option is selected
+    /// if it is the first option
+    pub fn add_option<S: ToString>(&mut self, label: S) {
+        self.options.push(label.to_string());
+        if self.selected.is_none() {
+            self.selected = Some(0);
+        }
+    }
+
+    /// return the labels of the options
+    pub fn options(&self) -> &[String] {
+        &self.options
+    }
+
+    /// set the selected option, this does not trigger the on_change callback.
+    /// An index outside of the options is ignored
+    pub fn set_selected(&mut self, index: usize) {
+        if index < self.options.len() {
+            self.selected = Some(index);
+        }
+    }
+
+    /// return the index of the selected option
+    pub fn selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// return the label of the selected option
+    pub fn selected_label(&self) -> Option<&str> {
+        self.selected.map(|index| self.options[index].as_str())
+    }
+
+    /// lay out the options in a row, default is in a column
+    pub fn horizontal(&mut self) {
+        self.is_horizontal = true;
+    }
+
+    /// lay out the options in a column
+    pub fn vertical(&mut self) {
+        self.is_horizontal = false;
+    }
+
+    /// attach a callback which is called when the user selects a different option,
+    /// the label of the selected option is sent as the value of the input event
+    pub fn on_change<F>(&mut self, f: F)
+    where
+        F: FnMut(Event) -> MSG + 'static,
+    {
+        self.on_change.push(f.into());
+    }
+
+    /// select the option and return the messages of the on_change callbacks
+    /// when the selection has changed
+    fn select(&mut self, index: usize) -> Vec<MSG> {
+        if self.selected == Some(index) || index >= self.options.len() {
+            return vec![];
+        }
+        self.selected = Some(index);
+        let value = Value::from(self.options[index].clone());
+        let event = Event::from(InputEvent::from(value));
+        self.on_change
+            .iter_mut()
+            .map(|cb| cb.emit(event.clone()))
+            .collect()
+    }
+
+    fn option_width(label: &str) -> usize {
+        LABEL_OFFSET + unicode::str_width(label)
+    }
+
+    /// the location of each of the options relative to the widget
+    fn option_locations(&self) -> Vec<(usize, usize)> {
+        let mut x = 0;
+        self.options
+            .iter()
+            .enumerate()
+            .map(|(i, label)| {
+                if self.is_horizontal {
+                    let loc = (x, 0);
+                    x += Self::option_width(label) + OPTION_GAP;
+                    loc
+                } else {
+                    (0, i)
+                }
+            })
+            .collect()
+    }
+
+    /// return the index of the option at this location relative to the widget
+    fn option_at(&self, x: usize, y: usize) -> Option<usize> {
+        self.option_locations()
+            .into_iter()
+            .zip(self.options.iter())
+            .position(|((ox, oy), label)| {
+                y == oy && x >= ox && x < ox + Self::option_width(label)
+            })
+    }
+
+    fn process_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        if self.options.is_empty() {
+            return vec![];
+        }
+        let last = self.options.len() - 1;
+        match (key_event.code, self.selected) {
+            (KeyCode::Up, Some(index)) | (KeyCode::Left, Some(index)) => {
+                self.select(index.saturating_sub(1))
+            }
+            (KeyCode::Down, Some(index)) | (KeyCode::Right, Some(index)) => {
+                self.select((index + 1).min(last))
+            }
+            (KeyCode::Home, _) => self.select(0),
+            (KeyCode::End, _) => self.select(last),
+            _ => vec![],
+        }
+    }
+}
+
+impl<MSG: 'static> Widget<MSG> for RadioGroup<MSG> {
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        let widths = self.options.iter().map(|label| Self::option_width(label));
+        let (width, height) = if self.is_horizontal {
+            let total: usize = widths.map(|w| w + OPTION_GAP).sum();
+            (total.saturating_sub(OPTION_GAP), 1)
+        } else {
+            (widths.max().unwrap_or(0), self.options.len())
+        };
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: Dimension::Points(width as f32),
+                height: Dimension::Points(height.max(1) as f32),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        let loc_x = layout.location.x.round() as usize;
+        let loc_y = layout.location.y.round() as usize;
+        for (i, ((x, y), label)) in self
+            .option_locations()
+            .into_iter()
+            .zip(self.options.iter())
+            .enumerate()
+        {
+            let is_selected = self.selected == Some(i);
+            let radio_symbol = if is_selected {
+                symbol::RADIO_CHECKED
+            } else {
+                symbol::RADIO_UNCHECKED
+            };
+            buf.set_symbol(loc_x + x, loc_y + y, radio_symbol);
+            let label_x = loc_x + x + LABEL_OFFSET;
+            if self.focused && is_selected {
+                buf.write_bold_str(label_x, loc_y + y, label);
+            } else {
+                buf.write_str(label_x, loc_y + y, label);
+            }
+        }
+        vec![]
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_size(&mut self, _width: Option<f32>, _height: Option<f32>) {}
+
+    fn process_event(&mut self, event: Event) -> Vec<MSG> {
+        match event {
+            Event::Key(ke) => self.process_key(ke),
+            _ if event.is_mouse_click() => {
+                let layout = self.layout.expect("must have a layout");
+                let (x, y) =
+                    event.extract_location().expect("must have a location");
+                let x = (x as usize)
+                    .checked_sub(layout.location.x.round() as usize);
+                let y = (y as usize)
+                    .checked_sub(layout.location.y.round() as usize);
+                match (x, y) {
+                    (Some(x), Some(y)) => match self.option_at(x, y) {
+                        Some(index) => self.select(index),
+                        None => vec![],
+                    },
+                    _ => vec![],
+                }
+            }
+            _ => vec![],
+        }
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for RadioGroup<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RadioGroup")
+            .field("options", &self.options)
+            .field("selected", &self.selected)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> Event {
+        Event::from(KeyEvent::from(code))
+    }
+
+    #[test]
+    fn exactly_one_is_selected() {
+        let mut group = RadioGroup::new(vec!["Small", "Medium", "Large"]);
+        group.on_change(|event| match event {
+            Event::InputEvent(ie) => ie.value.to_string(),
+            _ => String::new(),
+        });
+        assert_eq!(group.selected(), Some(0));
+        assert!(group.process_event(key(KeyCode::Up)).is_empty());
+        assert_eq!(group.process_event(key(KeyCode::Down)), vec!["Medium"]);
+        assert_eq!(group.process_event(key(KeyCode::End)), vec!["Large"]);
+        assert!(group.process_event(key(KeyCode::Down)).is_empty());
+        assert_eq!(group.selected_label(), Some("Large"));
+
+        group.set_selected(1);
+        assert_eq!(group.selected(), Some(1));
+        group.set_selected(3);
+        assert_eq!(group.selected(), Some(1));
+    }
+
+    #[test]
+    fn option_at_horizontal_location() {
+        let mut group = RadioGroup::<()>::new(vec!["ab", "cd"]);
+        assert_eq!(group.option_at(0, 1), Some(1));
+        group.horizontal();
+        // "⦿  ab  〇 cd"
+        assert_eq!(group.option_at(4, 0), Some(0));
+        assert_eq!(group.option_at(5, 0), None);
+        assert_eq!(group.option_at(7, 0), Some(1));
+        assert_eq!(group.option_at(7, 1), None);
+    }
+}
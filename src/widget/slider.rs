@@ -1,5 +1,8 @@
+use crate::crossterm::event::{KeyCode, KeyEvent};
+use crate::event::InputEvent;
 use crate::Callback;
 use crate::Event;
+use crate::Value;
 use crate::{buffer::Buffer, cmd::Cmd, symbol, Widget};
 use expanse::result::Layout;
 use expanse::{
@@ -8,13 +11,28 @@ use expanse::{
 };
 use ito_canvas::unicode_canvas::Canvas;
 use std::fmt;
+use unicode_width::UnicodeWidthStr;
 
-/// A slider with value from 0.0 to 1.0
+/// the number of steps to move on page up and page down
+const PAGE_STEPS: f64 = 10.0;
+
+/// the fraction of the range to move when no step is set
+const DEFAULT_STEP_FRACTION: f64 = 0.01;
+
+/// A slider with value from min to max, default is 0.0 to 1.0
 #[derive(Debug)]
 pub struct Slider<MSG> {
     layout: Option<Layout>,
-    value: f32,
+    value: f64,
+    min: f64,
+    max: f64,
+    /// the value will snap to the multiple of step starting from min
+    step: Option<f64>,
+    is_vertical: bool,
+    /// display the value at the end of the track
+    show_value: bool,
     width: Option<f32>,
+    height: Option<f32>,
     id: Option<String>,
     use_thick_track: bool,
     on_input: Vec<Callback<Event, MSG>>,
@@ -25,7 +43,13 @@ impl<MSG> Default for Slider<MSG> {
         Slider {
             layout: None,
             value: 0.0,
+            min: 0.0,
+            max: 1.0,
+            step: None,
+            is_vertical: false,
+            show_value: false,
             width: None,
+            height: None,
             id: None,
             use_thick_track: false,
             on_input: vec![],
@@ -35,22 +59,215 @@ impl<MSG> Default for Slider<MSG> {
 
 impl<MSG> Slider<MSG> {
     /// create a new slider with value
-    pub fn new(value: f32) -> Self {
-        Slider {
-            value,
-            ..Default::default()
+    pub fn new(value: f64) -> Self {
+        let mut slider = Slider::default();
+        slider.set_value(value);
+        slider
+    }
+
+    /// set the value of this slider, the value is clamped to the range
+    /// and snapped to the step
+    pub fn set_value(&mut self, value: f64) {
+        self.value = self.normalize(value);
+    }
+
+    /// return the value of this slider
+    pub fn get_value(&self) -> f64 {
+        self.value
+    }
+
+    /// set the minimum and maximum value of this slider, default is 0.0 to 1.0.
+    /// The range is ignored when min is not less than max, or is not finite
+    pub fn set_range(&mut self, min: f64, max: f64) {
+        if !min.is_finite() || !max.is_finite() || min >= max {
+            return;
         }
+        self.min = min;
+        self.max = max;
+        self.value = self.normalize(self.value);
     }
 
-    /// set the value of this slider
-    pub fn set_value(&mut self, value: f32) {
-        self.value = value;
+    /// set the step of this slider, the value will be a multiple of step from min.
+    /// The step is ignored when it is not positive, or is not finite
+    pub fn set_step(&mut self, step: f64) {
+        if !step.is_finite() || step <= 0.0 {
+            return;
+        }
+        self.step = Some(step);
+        self.value = self.normalize(self.value);
     }
 
     /// set the use thick track, default is false
     pub fn use_thick_track(&mut self, use_thick: bool) {
         self.use_thick_track = use_thick;
     }
+
+    /// set the orientation of this slider to vertical,
+    /// the max value is at the top
+    pub fn set_vertical(&mut self, is_vertical: bool) {
+        self.is_vertical = is_vertical;
+    }
+
+    /// set whether to display the value at the end of the track
+    pub fn show_value(&mut self, show_value: bool) {
+        self.show_value = show_value;
+    }
+
+    /// attach a callback which is called when the value is changed by the user,
+    /// the value is sent as `Value::F64`
+    pub fn on_input<F>(&mut self, f: F)
+    where
+        F: FnMut(Event) -> MSG + 'static,
+    {
+        self.on_input.push(f.into());
+    }
+
+    /// clamp the value to the range and snap it to the step
+    fn normalize(&self, value: f64) -> f64 {
+        let value = match self.step {
+            Some(step) => self.min + ((value - self.min) / step).round() * step,
+            None => value,
+        };
+        value.clamp(self.min, self.max)
+    }
+
+    /// the amount the value changes with the arrow keys
+    fn step_size(&self) -> f64 {
+        self.step
+            .unwrap_or((self.max - self.min) * DEFAULT_STEP_FRACTION)
+    }
+
+    /// the position of the value in the range, from 0.0 to 1.0
+    fn fraction(&self) -> f64 {
+        (self.value - self.min) / (self.max - self.min)
+    }
+
+    /// the number of decimal places used in displaying the value
+    fn precision(&self) -> usize {
+        match self.step {
+            // the decimal places of the step, so 0.25 is displayed with 2
+            Some(step) => step
+                .to_string()
+                .split_once('.')
+                .map_or(0, |(_, decimals)| decimals.len()),
+            None => {
+                let step = self.step_size();
+                if step >= 1.0 {
+                    0
+                } else {
+                    (-step.log10().floor()) as usize
+                }
+            }
+        }
+    }
+
+    fn value_label(&self) -> String {
+        format!("{:.*}", self.precision(), self.value)
+    }
+
+    /// the widest label, used for reserving the space of the label
+    fn label_width(&self) -> usize {
+        if self.show_value {
+            let min = format!("{:.*}", self.precision(), self.min);
+            let max = format!("{:.*}", self.precision(), self.max);
+            min.width().max(max.width())
+        } else {
+            0
+        }
+    }
+
+    /// the length of the track in cells
+    fn track_length(&self, layout: &Layout) -> usize {
+        let length = if self.is_vertical {
+            layout.size.height.round() as usize
+        } else {
+            layout.size.width.round() as usize
+        };
+        let label_length = match (self.show_value, self.is_vertical) {
+            (false, _) => 0,
+            (true, true) => 1,
+            (true, false) => self.label_width() + 1,
+        };
+        length.saturating_sub(label_length).max(1)
+    }
+
+    /// the offset of the thumb from the start of the track
+    fn thumb_offset(&self, track_length: usize) -> usize {
+        let fraction = if self.is_vertical {
+            1.0 - self.fraction()
+        } else {
+            self.fraction()
+        };
+        (fraction * (track_length - 1) as f64).round() as usize
+    }
+
+    /// set the value and return the messages of the on_input callbacks
+    /// when the value has changed
+    fn change_value(&mut self, value: f64) -> Vec<MSG> {
+        let value = self.normalize(value);
+        if (value - self.value).abs() < f64::EPSILON {
+            return vec![];
+        }
+        self.value = value;
+        let event = Event::from(InputEvent::from(Value::F64(value)));
+        self.on_input
+            .iter_mut()
+            .map(|cb| cb.emit(event.clone()))
+            .collect()
+    }
+
+    fn process_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        let step = self.step_size();
+        match key_event.code {
+            KeyCode::Left | KeyCode::Down => {
+                self.change_value(self.value - step)
+            }
+            KeyCode::Right | KeyCode::Up => {
+                self.change_value(self.value + step)
+            }
+            KeyCode::PageDown => {
+                self.change_value(self.value - step * PAGE_STEPS)
+            }
+            KeyCode::PageUp => {
+                self.change_value(self.value + step * PAGE_STEPS)
+            }
+            KeyCode::Home => self.change_value(self.min),
+            KeyCode::End => self.change_value(self.max),
+            _ => vec![],
+        }
+    }
+
+    fn process_mouse(&mut self, event: &Event) -> Vec<MSG> {
+        let layout = self.layout.expect("must have a layout");
+        if event.is_mouse_click() || event.is_mouse_drag() {
+            let (x, y) =
+                event.extract_location().expect("must have a location");
+            let track_length = self.track_length(&layout);
+            let cursor_loc = if self.is_vertical {
+                y as f64 - layout.location.y.round() as f64
+            } else {
+                x as f64 - layout.location.x.round() as f64
+            };
+            let fraction = if track_length > 1 {
+                cursor_loc / (track_length - 1) as f64
+            } else {
+                0.0
+            };
+            let fraction = if self.is_vertical {
+                1.0 - fraction
+            } else {
+                fraction
+            };
+            // the value is clamped, dragging past the edge gives min or max
+            self.change_value(self.min + fraction * (self.max - self.min))
+        } else if event.is_scrollup() {
+            self.change_value(self.value + self.step_size())
+        } else if event.is_scrolldown() {
+            self.change_value(self.value - self.step_size())
+        } else {
+            vec![]
+        }
+    }
 }
 
 impl<MSG> Widget<MSG> for Slider<MSG>
@@ -64,17 +281,36 @@ where
         self.layout = Some(layout);
     }
     fn style(&self) -> Style {
-        Style {
-            position_type: PositionType::Relative,
-            size: Size {
-                width: Dimension::Percent(1.0),
-                height: Dimension::Points(1.0),
-            },
-            min_size: Size {
-                width: Dimension::Percent(1.0),
-                height: Dimension::Points(1.0),
-            },
-            ..Default::default()
+        if self.is_vertical {
+            Style {
+                position_type: PositionType::Relative,
+                size: Size {
+                    width: Dimension::Points(self.label_width().max(1) as f32),
+                    height: if let Some(height) = self.height {
+                        Dimension::Points(height)
+                    } else {
+                        Dimension::Percent(1.0)
+                    },
+                },
+                ..Default::default()
+            }
+        } else {
+            Style {
+                position_type: PositionType::Relative,
+                size: Size {
+                    width: if let Some(width) = self.width {
+                        Dimension::Points(width)
+                    } else {
+                        Dimension::Percent(1.0)
+                    },
+                    height: Dimension::Points(1.0),
+                },
+                min_size: Size {
+                    width: Dimension::Percent(1.0),
+                    height: Dimension::Points(1.0),
+                },
+                ..Default::default()
+            }
         }
     }
 
@@ -82,37 +318,53 @@ where
         let layout = self.layout.expect("must have a layout");
         let loc_x = layout.location.x.round() as usize;
         let loc_y = layout.location.y.round() as usize;
-        let width = layout.size.width.round() as usize;
-        let _height = layout.size.height.round() as usize;
+        let track_length = self.track_length(&layout);
+        let thumb_offset = self.thumb_offset(track_length);
         let mut canvas = Canvas::new();
-        let right = loc_x + width - 2;
-        canvas.draw_horizontal_line(
-            (loc_x + 1, loc_y),
-            (right, loc_y),
-            self.use_thick_track,
-        );
-        buf.write_canvas(canvas);
-        let slider_loc = (self.value * width as f32) as usize;
-        buf.set_symbol(loc_x + slider_loc, loc_y, symbol::MIDDLE_BLOCK);
+        if self.is_vertical {
+            canvas.draw_vertical_line(
+                (loc_x, loc_y),
+                (loc_x, loc_y + track_length - 1),
+                self.use_thick_track,
+            );
+            buf.write_canvas(canvas);
+            buf.set_symbol(loc_x, loc_y + thumb_offset, symbol::MIDDLE_BLOCK);
+            if self.show_value {
+                buf.write_str(loc_x, loc_y + track_length, self.value_label());
+            }
+        } else {
+            canvas.draw_horizontal_line(
+                (loc_x, loc_y),
+                (loc_x + track_length - 1, loc_y),
+                self.use_thick_track,
+            );
+            buf.write_canvas(canvas);
+            buf.set_symbol(loc_x + thumb_offset, loc_y, symbol::MIDDLE_BLOCK);
+            if self.show_value {
+                let label = self.value_label();
+                // right align the label
+                let label_x = loc_x + track_length + 1 + self.label_width()
+                    - label.width();
+                buf.write_str(label_x, loc_y, label);
+            }
+        }
         vec![]
     }
 
-    fn set_size(&mut self, width: Option<f32>, _height: Option<f32>) {
+    fn value(&self) -> Option<Value> {
+        Some(Value::F64(self.value))
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
         self.width = width;
+        self.height = height;
     }
 
     fn process_event(&mut self, event: Event) -> Vec<MSG> {
-        let layout = self.layout.expect("must have a layout");
-        if event.is_mouse_click() || event.is_mouse_drag() {
-            let (x, _y) =
-                event.extract_location().expect("must have a location");
-            let cursor_loc = x as i32 - layout.location.x.round() as i32;
-            let width = layout.size.width;
-            let value = cursor_loc as f32 / width;
-            self.value = value;
-            vec![]
-        } else {
-            vec![]
+        match event {
+            Event::Key(ke) => self.process_key(ke),
+            Event::Mouse(_) => self.process_mouse(&event),
+            _ => vec![],
         }
     }
 
@@ -124,3 +376,77 @@ where
         &self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_and_snap_to_step() {
+        let mut slider = Slider::<()>::new(0.0);
+        slider.set_range(-10.0, 10.0);
+        slider.set_step(2.5);
+        slider.set_value(3.9);
+        assert_eq!(slider.get_value(), 5.0);
+        slider.set_value(42.0);
+        assert_eq!(slider.get_value(), 10.0);
+        slider.set_value(-42.0);
+        assert_eq!(slider.get_value(), -10.0);
+        assert_eq!(slider.value_label(), "-10.0");
+
+        // invalid range and step are ignored
+        slider.set_range(5.0, 5.0);
+        slider.set_range(f64::NAN, 1.0);
+        slider.set_step(0.0);
+        slider.set_step(f64::NAN);
+        slider.set_value(3.9);
+        assert_eq!(slider.get_value(), 5.0);
+    }
+
+    #[test]
+    fn precision_follows_the_step() {
+        let mut slider = Slider::<()>::new(0.0);
+        slider.set_range(0.0, 10.0);
+        slider.set_step(2.5);
+        slider.set_value(2.5);
+        assert_eq!(slider.value_label(), "2.5");
+
+        slider.set_range(0.0, 1.0);
+        slider.set_step(0.25);
+        slider.set_value(0.25);
+        assert_eq!(slider.value_label(), "0.25");
+
+        slider.set_step(1.0);
+        slider.set_value(1.0);
+        assert_eq!(slider.value_label(), "1");
+    }
+
+    #[test]
+    fn keys_emit_on_input() {
+        let mut slider = Slider::new(0.0);
+        slider.set_range(0.0, 100.0);
+        slider.set_step(5.0);
+        slider.on_input(|event| match event {
+            Event::InputEvent(ie) => ie.value.as_f64(),
+            _ => None,
+        });
+        let msgs =
+            slider.process_event(Event::from(KeyEvent::from(KeyCode::Right)));
+        assert_eq!(msgs, vec![Some(5.0)]);
+        let msgs =
+            slider.process_event(Event::from(KeyEvent::from(KeyCode::End)));
+        assert_eq!(msgs, vec![Some(100.0)]);
+        // no change at the max, so no message
+        let msgs =
+            slider.process_event(Event::from(KeyEvent::from(KeyCode::Up)));
+        assert!(msgs.is_empty());
+    }
+
+    #[test]
+    fn vertical_thumb_has_max_at_top() {
+        let mut slider = Slider::<()>::new(0.75);
+        assert_eq!(slider.thumb_offset(5), 3);
+        slider.set_vertical(true);
+        assert_eq!(slider.thumb_offset(5), 1);
+    }
+}
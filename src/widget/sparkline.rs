@@ -0,0 +1,349 @@
+use crate::crossterm::style::Color;
+use crate::{
+    buffer::{Buffer, Cell},
+    symbol::bar,
+    Cmd, Widget,
+};
+use expanse::{
+    geometry::Size,
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use std::{
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    sync::mpsc::{self, Receiver, Sender},
+};
+
+/// the number of samples kept by default
+const DEFAULT_MAX_SAMPLES: usize = 256;
+
+/// A one row chart of the most recent samples, where each sample is drawn
+/// as an eighth bar. The newest sample is at the right end.
+///```ignore
+///   ▁▂▃▅▂▁▄▇█▆▃
+/// ```
+pub struct Sparkline<MSG> {
+    layout: Option<Layout>,
+    samples: VecDeque<f64>,
+    /// the oldest samples are dropped when this number is exceeded
+    max_samples: usize,
+    /// fixed bounds of the values, otherwise the min and max of the visible samples are used
+    range: Option<(f64, f64)>,
+    color: Option<Color>,
+    /// the samples at or above the threshold value are drawn with its color
+    thresholds: Vec<(f64, Color)>,
+    /// the colors of the lowest and highest visible sample
+    min_max_markers: Option<(Color, Color)>,
+    /// samples sent from other threads, they are added to the samples on tick
+    channel: Option<(Sender<f64>, Receiver<f64>)>,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    _phantom_msg: PhantomData<MSG>,
+}
+
+impl<MSG> Default for Sparkline<MSG> {
+    fn default() -> Self {
+        Sparkline {
+            layout: None,
+            samples: VecDeque::new(),
+            max_samples: DEFAULT_MAX_SAMPLES,
+            range: None,
+            color: None,
+            thresholds: vec![],
+            min_max_markers: None,
+            channel: None,
+            width: None,
+            height: None,
+            id: None,
+            _phantom_msg: PhantomData,
+        }
+    }
+}
+
+impl<MSG> Sparkline<MSG> {
+    /// create a new sparkline with the initial samples
+    pub fn new(samples: Vec<f64>) -> Self {
+        let mut sparkline = Sparkline::default();
+        sparkline.extend(samples);
+        sparkline
+    }
+
+    /// add a new sample, the oldest sample is dropped when the window is full
+    pub fn push(&mut self, sample: f64) {
+        self.samples.push_back(sample);
+        self.truncate();
+    }
+
+    /// add multiple samples
+    pub fn extend<I: IntoIterator<Item = f64>>(&mut self, samples: I) {
+        self.samples.extend(samples);
+        self.truncate();
+    }
+
+    /// remove all the samples
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// return the samples, from the oldest to the newest
+    pub fn samples(&self) -> &VecDeque<f64> {
+        &self.samples
+    }
+
+    /// set the maximum number of samples kept in the window
+    pub fn set_max_samples(&mut self, max_samples: usize) {
+        self.max_samples = max_samples;
+        self.truncate();
+    }
+
+    /// set fixed bounds of the values, instead of scaling to the visible samples
+    pub fn set_range(&mut self, min: f64, max: f64) {
+        self.range = Some((min, max));
+    }
+
+    /// set the color of the bars
+    pub fn set_color(&mut self, color: Color) {
+        self.color = Some(color);
+    }
+
+    /// samples with value at or above the threshold will be drawn with this color,
+    /// the highest matching threshold is used. A NaN threshold is ignored,
+    /// since no sample can be at or above it
+    pub fn add_threshold(&mut self, value: f64, color: Color) {
+        if value.is_nan() {
+            return;
+        }
+        self.thresholds.push((value, color));
+        self.thresholds.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    }
+
+    /// mark the lowest and highest of the visible samples with these colors
+    pub fn set_min_max_markers(&mut self, min_color: Color, max_color: Color) {
+        self.min_max_markers = Some((min_color, max_color));
+    }
+
+    /// return a sender which can be used to stream samples from other threads,
+    /// the samples are added when the renderer ticks, so the renderer needs a tick rate.
+    pub fn sender(&mut self) -> Sender<f64> {
+        let (sender, _) = self.channel.get_or_insert_with(mpsc::channel);
+        sender.clone()
+    }
+
+    fn truncate(&mut self) {
+        while self.samples.len() > self.max_samples {
+            self.samples.pop_front();
+        }
+    }
+
+    /// the samples that fits in the width, aligned to the right
+    fn visible_samples(&self, width: usize) -> Vec<f64> {
+        let skip = self.samples.len().saturating_sub(width);
+        self.samples.iter().skip(skip).copied().collect()
+    }
+
+    fn color_of(&self, sample: f64) -> Option<Color> {
+        self.thresholds
+            .iter()
+            .rev()
+            .find(|(threshold, _)| sample >= *threshold)
+            .map(|(_, color)| *color)
+            .or(self.color)
+    }
+
+    /// create the cells of the visible samples, each sample has at least
+    /// one eighth, so the lowest values are still visible
+    fn create_cells(&self, width: usize) -> Vec<Cell> {
+        let samples = self.visible_samples(width);
+        let (min, max) = self.range.unwrap_or_else(|| {
+            samples
+                .iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), s| {
+                    (min.min(*s), max.max(*s))
+                })
+        });
+        let min_index = index_of(&samples, |a, b| a < b);
+        let max_index = index_of(&samples, |a, b| a > b);
+        samples
+            .iter()
+            .enumerate()
+            .map(|(i, sample)| {
+                let fraction = if max > min {
+                    ((sample - min) / (max - min)).clamp(0.0, 1.0)
+                } else {
+                    1.0
+                };
+                let eighths = 1 + (fraction * 7.0).round() as usize;
+                let mut cell = Cell::new(bar::eighths(eighths));
+                let marker = match self.min_max_markers {
+                    Some((min_color, _)) if Some(i) == min_index => {
+                        Some(min_color)
+                    }
+                    Some((_, max_color)) if Some(i) == max_index => {
+                        Some(max_color)
+                    }
+                    _ => None,
+                };
+                if let Some(color) = marker.or_else(|| self.color_of(*sample)) {
+                    cell.color(color);
+                }
+                cell
+            })
+            .collect()
+    }
+}
+
+/// the index of the first sample which is preferred over all the others
+fn index_of(samples: &[f64], prefer: fn(f64, f64) -> bool) -> Option<usize> {
+    let mut found: Option<usize> = None;
+    for (i, sample) in samples.iter().enumerate() {
+        if found.map(|f| prefer(*sample, samples[f])).unwrap_or(true) {
+            found = Some(i);
+        }
+    }
+    found
+}
+
+impl<MSG> Widget<MSG> for Sparkline<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Points(1.0)
+                },
+            },
+            min_size: Size {
+                height: Dimension::Points(1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        let layout = self.layout.expect("must have a layout");
+        let loc_x = layout.location.x.round() as usize;
+        let loc_y = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        let height = layout.size.height.round() as usize;
+
+        let cells = self.create_cells(width);
+        // right align, so the newest sample is always at the right end
+        let start = loc_x + width - cells.len();
+        // draw at the bottom row
+        let row = loc_y + height.saturating_sub(1);
+        for (i, cell) in cells.into_iter().enumerate() {
+            buf.set_cell(start + i, row, cell);
+        }
+        vec![]
+    }
+
+    fn tick(&mut self) {
+        let received: Vec<f64> = match &self.channel {
+            Some((_, receiver)) => receiver.try_iter().collect(),
+            None => return,
+        };
+        self.extend(received);
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for Sparkline<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Sparkline")
+            .field("samples", &self.samples)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbols(cells: &[Cell]) -> String {
+        cells.iter().map(|cell| cell.symbol.clone()).collect()
+    }
+
+    #[test]
+    fn scaled_to_visible_samples() {
+        let sparkline = Sparkline::<()>::new(vec![100.0, 0.0, 7.0, 3.5, 1.0]);
+        assert_eq!(symbols(&sparkline.create_cells(3)), "█▄▁");
+        assert_eq!(symbols(&sparkline.create_cells(10)), "█▁▁▁▁");
+    }
+
+    #[test]
+    fn sliding_window() {
+        let mut sparkline = Sparkline::<()>::new(vec![1.0, 2.0, 3.0]);
+        sparkline.set_max_samples(3);
+        sparkline.push(4.0);
+        assert_eq!(sparkline.samples(), &[2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn thresholds_and_markers() {
+        let mut sparkline = Sparkline::<()>::new(vec![5.0, 1.0, 9.0, 6.0]);
+        sparkline.add_threshold(8.0, Color::Red);
+        sparkline.add_threshold(5.0, Color::Yellow);
+        sparkline.add_threshold(f64::NAN, Color::Magenta);
+        assert_eq!(sparkline.thresholds.len(), 2);
+        let cells = sparkline.create_cells(4);
+        assert_eq!(cells[0].foreground_color, Some(Color::Yellow));
+        assert_eq!(cells[1].foreground_color, None);
+        assert_eq!(cells[2].foreground_color, Some(Color::Red));
+
+        sparkline.set_min_max_markers(Color::Blue, Color::Green);
+        let cells = sparkline.create_cells(4);
+        assert_eq!(cells[1].foreground_color, Some(Color::Blue));
+        assert_eq!(cells[2].foreground_color, Some(Color::Green));
+        assert_eq!(cells[3].foreground_color, Some(Color::Yellow));
+    }
+
+    #[test]
+    fn streamed_samples_are_added_on_tick() {
+        let mut sparkline = Sparkline::<()>::new(vec![]);
+        let sender = sparkline.sender();
+        std::thread::spawn(move || {
+            sender.send(1.0).expect("must send");
+            sender.send(2.0).expect("must send");
+        })
+        .join()
+        .expect("must join");
+        assert!(sparkline.samples().is_empty());
+        Widget::<()>::tick(&mut sparkline);
+        assert_eq!(sparkline.samples(), &[1.0, 2.0]);
+    }
+}
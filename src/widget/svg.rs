@@ -0,0 +1,282 @@
+use crate::{buffer::Buffer, Cmd, Widget};
+use crate::{Alignment, Image, ImageRenderMode, VerticalAlignment};
+use expanse::result::Layout;
+use expanse::style::Style;
+use image::{DynamicImage, RgbaImage};
+use lazy_static::lazy_static;
+use resvg::tiny_skia::{self, Pixmap, Transform};
+use resvg::usvg::{self, fontdb, Tree};
+use std::{fmt, sync::Arc};
+
+lazy_static! {
+    /// the system fonts used by the text of the svg, loaded only once
+    /// when the first svg is created and shared by all the svg
+    static ref SYSTEM_FONTS: Arc<fontdb::Database> = {
+        let mut fontdb = fontdb::Database::new();
+        fontdb.load_system_fonts();
+        Arc::new(fontdb)
+    };
+}
+
+/// Svg widget, the vector image is rasterized to the size of the widget
+/// and displayed in the same way as the `Image` widget.
+///
+/// The image is scaled to fit inside the widget, keeping the aspect ratio.
+pub struct Svg<MSG> {
+    tree: Tree,
+    /// the image of the rasterized svg, which also holds the layout
+    image: Image<MSG>,
+    /// the color under the transparent parts of the svg
+    background: tiny_skia::Color,
+    /// the number of columns and rows the svg is rasterized for
+    rasterized_size: Option<(usize, usize)>,
+    id: Option<String>,
+}
+
+impl<MSG> Svg<MSG>
+where
+    MSG: 'static,
+{
+    /// create a new svg widget from the svg data,
+    /// returns an error when the data is not a valid svg
+    pub fn new(data: Vec<u8>) -> Result<Self, usvg::Error> {
+        let options = usvg::Options {
+            fontdb: Arc::clone(&SYSTEM_FONTS),
+            ..Default::default()
+        };
+        let tree = Tree::from_data(&data, &options)?;
+        Ok(Svg {
+            tree,
+            image: Image::from_image(DynamicImage::new_rgba8(1, 1)),
+            background: tiny_skia::Color::WHITE,
+            rasterized_size: None,
+            id: None,
+        })
+    }
+
+    /// set the color under the transparent parts of the svg, default is white
+    pub fn set_background(&mut self, r: u8, g: u8, b: u8) {
+        self.background = tiny_skia::Color::from_rgba8(r, g, b, 255);
+        self.rasterize();
+    }
+
+    /// set how the pixels are displayed,
+    /// overriding the mode detected from the terminal
+    pub fn set_render_mode(&mut self, render_mode: ImageRenderMode) {
+        self.image.set_render_mode(render_mode);
+        self.rasterize();
+    }
+
+    /// set the horizontal alignment of the svg inside the widget,
+    /// default is `Center`
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.image.set_alignment(alignment);
+    }
+
+    /// set the vertical alignment of the svg inside the widget,
+    /// default is `Middle`
+    pub fn set_vertical_alignment(
+        &mut self,
+        vertical_alignment: VerticalAlignment,
+    ) {
+        self.image.set_vertical_alignment(vertical_alignment);
+    }
+
+    /// set the width of the terminal cell divided by its height, default is 0.5.
+    /// The ratio is ignored when it is not positive, or is not finite
+    pub fn set_cell_aspect_ratio(&mut self, cell_aspect_ratio: f32) {
+        self.image.set_cell_aspect_ratio(cell_aspect_ratio);
+        self.rasterize();
+    }
+
+    /// rasterize the svg to the pixels of the laid out cells,
+    /// so the image doesn't need to be scaled when it is drawn
+    fn rasterize(&mut self) {
+        let (columns, rows) = match self.image.layout() {
+            Some(layout) => (
+                layout.size.width.round() as usize,
+                layout.size.height.round() as usize,
+            ),
+            None => return,
+        };
+        self.rasterized_size = Some((columns, rows));
+        if columns == 0 || rows == 0 {
+            return;
+        }
+        let (area_width, area_height) = self.image.pixel_area(columns, rows);
+        let size = self.tree.size();
+        let scale =
+            (area_width / size.width()).min(area_height / size.height());
+        let width = ((size.width() * scale).round() as u32).max(1);
+        let height = ((size.height() * scale).round() as u32).max(1);
+
+        let mut pixmap =
+            Pixmap::new(width, height).expect("must have a non-zero size");
+        pixmap.fill(self.background);
+        resvg::render(
+            &self.tree,
+            Transform::from_scale(scale, scale),
+            &mut pixmap.as_mut(),
+        );
+        // the background is opaque, so the premultiplied pixels
+        // are the same as the rgba pixels
+        let rgba = RgbaImage::from_raw(width, height, pixmap.take())
+            .expect("must have the pixels of the size");
+        self.image.set_image(DynamicImage::ImageRgba8(rgba));
+    }
+}
+
+impl<MSG> Widget<MSG> for Svg<MSG>
+where
+    MSG: 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.image.layout()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        let size = (
+            layout.size.width.round() as usize,
+            layout.size.height.round() as usize,
+        );
+        self.image.set_layout(layout);
+        if self.rasterized_size != Some(size) {
+            self.rasterize();
+        }
+    }
+
+    fn style(&self) -> Style {
+        self.image.style()
+    }
+
+    /// draw the rasterized svg to the buffer
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        self.image.draw(buf)
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.image.set_size(width, height);
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for Svg<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Svg")
+            .field("size", &self.tree.size())
+            .field("rasterized_size", &self.rasterized_size)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossterm::style::Color;
+    use expanse::{geometry::Size, number::Number};
+
+    const SQUARE: &str = r##"<svg xmlns="http://www.w3.org/2000/svg"
+        width="10" height="10" viewBox="0 0 10 10">
+        <rect x="0" y="0" width="10" height="10" fill="#ff0000"/>
+    </svg>"##;
+
+    fn draw(svg: &mut Svg<()>, columns: usize, rows: usize) -> Buffer {
+        svg.set_size(Some(columns as f32), Some(rows as f32));
+        svg.compute_node_layout(Size {
+            width: Number::Defined(columns as f32),
+            height: Number::Defined(rows as f32),
+        });
+        let mut buf = Buffer::new(columns, rows);
+        svg.draw(&mut buf);
+        buf
+    }
+
+    fn symbols(buf: &Buffer, y: usize) -> String {
+        buf.cells[y]
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect()
+    }
+
+    #[test]
+    fn invalid_svg_is_an_error() {
+        assert!(Svg::<()>::new(b"not an svg".to_vec()).is_err());
+    }
+
+    #[test]
+    fn rasterized_to_the_size_of_the_widget() {
+        let mut svg: Svg<()> =
+            Svg::new(SQUARE.as_bytes().to_vec()).expect("must parse the svg");
+        svg.set_render_mode(ImageRenderMode::TrueColor);
+        svg.set_alignment(Alignment::Left);
+        // a square of 8x8 pixels is 8 columns and 4 rows
+        let buf = draw(&mut svg, 10, 4);
+        assert_eq!(svg.rasterized_size, Some((10, 4)));
+        assert_eq!(symbols(&buf, 0), "▄▄▄▄▄▄▄▄  ");
+        let red = Some(Color::Rgb { r: 255, g: 0, b: 0 });
+        assert_eq!(buf.cells[0][0].foreground_color, red);
+        assert_eq!(buf.cells[3][7].background_color, red);
+
+        // the svg is rasterized again when the widget is resized
+        let buf = draw(&mut svg, 20, 2);
+        assert_eq!(svg.rasterized_size, Some((20, 2)));
+        assert_eq!(
+            symbols(&buf, 1),
+            format!("{}{}", "▄".repeat(4), " ".repeat(16))
+        );
+    }
+
+    #[test]
+    fn braille_of_the_svg() {
+        let white = SQUARE.replace("#ff0000", "#ffffff");
+        let mut svg: Svg<()> =
+            Svg::new(white.into_bytes()).expect("must parse the svg");
+        svg.set_render_mode(ImageRenderMode::Braille);
+        // the 8x8 pixels of the white square are drawn with all the dots
+        let buf = draw(&mut svg, 6, 2);
+        assert_eq!(symbols(&buf, 0), " ⣿⣿⣿⣿ ");
+        assert_eq!(symbols(&buf, 1), " ⣿⣿⣿⣿ ");
+
+        // the black square is blank over the black background
+        let black = SQUARE.replace("#ff0000", "#000000");
+        let mut svg: Svg<()> =
+            Svg::new(black.into_bytes()).expect("must parse the svg");
+        svg.set_render_mode(ImageRenderMode::Braille);
+        svg.set_background(0, 0, 0);
+        let buf = draw(&mut svg, 6, 2);
+        assert_eq!(symbols(&buf, 0), " \u{2800}\u{2800}\u{2800}\u{2800} ");
+    }
+
+    #[test]
+    fn system_fonts_are_shared() {
+        let svg: Svg<()> =
+            Svg::new(SQUARE.as_bytes().to_vec()).expect("must parse the svg");
+        let other: Svg<()> =
+            Svg::new(SQUARE.as_bytes().to_vec()).expect("must parse the svg");
+        assert!(Arc::ptr_eq(svg.tree.fontdb(), other.tree.fontdb()));
+    }
+
+    #[test]
+    fn tiger() {
+        let mut svg: Svg<()> =
+            Svg::new(include_bytes!("../../tiger.svg").to_vec())
+                .expect("must parse the svg");
+        svg.set_render_mode(ImageRenderMode::Ascii);
+        let buf = draw(&mut svg, 40, 20);
+        let drawn = buf
+            .cells
+            .iter()
+            .flatten()
+            .filter(|cell| !cell.is_blank())
+            .count();
+        assert!(drawn > 0);
+    }
+}
@@ -1,5 +1,7 @@
+use crate::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::event::InputEvent;
 use crate::Event;
-use crate::{buffer::Buffer, Cmd, Widget};
+use crate::{buffer::Buffer, symbol, unicode, Callback, Cmd, Value, Widget};
 use expanse::{
     geometry::{Rect, Size},
     result::Layout,
@@ -8,6 +10,14 @@ use expanse::{
 use ito_canvas::unicode_canvas::{Border, Canvas};
 use std::fmt;
 
+/// the space before the first tab
+const LEFT_PAD: usize = 3;
+/// the width of the close button and the space before it
+const CLOSE_BUTTON_WIDTH: usize = 2;
+
+/// the tabs that are visible and where they are located
+type TabRects = Vec<(usize, ((usize, usize), (usize, usize)))>;
+
 /// A Tab box contains multiple box which
 /// can only be shown one at a time
 ///```ignore
@@ -21,6 +31,8 @@ use std::fmt;
 ///  │                                │
 ///  └────────────────────────────────┘
 /// ```
+/// When the tabs don't fit in the width, arrows are displayed
+/// to switch to the previous and next tabs.
 #[derive(Debug)]
 pub struct TabBox<MSG> {
     layout: Option<Layout>,
@@ -37,6 +49,12 @@ pub struct TabBox<MSG> {
     has_border: bool,
     is_rounded_border: bool,
     is_thick_border: bool,
+    /// show a close button on each of the tabs
+    is_closable: bool,
+    /// the tab that is pressed, it is moved when dragged to the other tabs
+    dragged_tab: Option<usize>,
+    on_tab_change: Vec<Callback<Event, MSG>>,
+    on_tab_close: Vec<Callback<Event, MSG>>,
 }
 
 impl<MSG> TabBox<MSG> {
@@ -55,6 +73,10 @@ impl<MSG> TabBox<MSG> {
             has_border: true,
             is_rounded_border: true,
             is_thick_border: false,
+            is_closable: false,
+            dragged_tab: None,
+            on_tab_change: vec![],
+            on_tab_close: vec![],
         }
     }
 
@@ -96,21 +118,64 @@ impl<MSG> TabBox<MSG> {
         self.flex_direction = FlexDirection::Row;
     }
 
-    /// return the calculation of tab_level
-    fn tab_label_rects(&self) -> Vec<((usize, usize), (usize, usize))> {
+    /// the width of the tab, including the borders and the close button
+    fn tab_width(&self, label: &str) -> usize {
+        let close_width = if self.is_closable {
+            CLOSE_BUTTON_WIDTH
+        } else {
+            0
+        };
+        unicode::str_width(label) + close_width + 3
+    }
+
+    /// whether the tabs don't fit in the width of the tab box
+    fn has_overflow(&self) -> bool {
+        let layout = self.layout.expect("must have a layout");
+        let width = layout.size.width.round() as usize;
+        let total: usize =
+            self.tab_labels.iter().map(|l| self.tab_width(l)).sum();
+        LEFT_PAD + total + 1 > width
+    }
+
+    /// return the calculation of the visible tab labels, together with its tab index.
+    /// When the tabs overflow, the tabs are scrolled so the active tab is visible.
+    fn tab_label_rects(&self) -> TabRects {
         let layout = self.layout.expect("must have a layout");
         let loc_x = layout.location.x.round() as usize;
         let loc_y = layout.location.y.round() as usize;
-        let left_pad = 3;
-        let mut left = loc_x + left_pad;
+        let width = layout.size.width.round() as usize;
+        let widths: Vec<usize> =
+            self.tab_labels.iter().map(|l| self.tab_width(l)).collect();
         let top = loc_y;
         let height = 2;
         let bottom = top + height;
-        let mut tab_rects: Vec<((usize, usize), (usize, usize))> = vec![];
-        for label in self.tab_labels.iter() {
-            let label_width = label.len() + 3;
+        let (first_tab, limit) = if self.has_overflow() {
+            // leave space for the right arrow
+            let limit = (loc_x + width).saturating_sub(3);
+            let first_tab = (0..=self.active_tab)
+                .find(|start| {
+                    let active_right = loc_x
+                        + LEFT_PAD
+                        + widths[*start..=self.active_tab]
+                            .iter()
+                            .sum::<usize>();
+                    active_right <= limit
+                })
+                .unwrap_or(self.active_tab);
+            (first_tab, limit)
+        } else {
+            (0, loc_x + width)
+        };
+        let mut left = loc_x + LEFT_PAD;
+        let mut tab_rects: TabRects = vec![];
+        for (tab_index, label_width) in
+            widths.iter().enumerate().skip(first_tab)
+        {
             let right = left + label_width;
-            tab_rects.push(((left, top), (right, bottom)));
+            if right > limit && !tab_rects.is_empty() {
+                break;
+            }
+            tab_rects.push((tab_index, ((left, top), (right, bottom))));
             left += label_width;
         }
         tab_rects
@@ -118,16 +183,39 @@ impl<MSG> TabBox<MSG> {
 
     fn hit_tab_label(&self, x: usize, y: usize) -> Option<usize> {
         let tab_rects = self.tab_label_rects();
-        for (tab_index, ((left, top), (right, bottom))) in
-            tab_rects.iter().enumerate()
-        {
+        for (tab_index, ((left, top), (right, bottom))) in tab_rects.iter() {
             if x >= *left && x <= *right && y >= *top && y <= *bottom {
-                return Some(tab_index);
+                return Some(*tab_index);
             }
         }
         None
     }
 
+    /// whether the location is at the close button of the tab
+    fn hit_close_button(&self, tab_index: usize, x: usize, y: usize) -> bool {
+        self.is_closable
+            && self
+                .tab_label_rects()
+                .iter()
+                .find(|(index, _)| *index == tab_index)
+                .map(|(_, ((_, top), (right, _)))| {
+                    y == top + 1 && x == right - 2
+                })
+                .unwrap_or(false)
+    }
+
+    /// the location of the left and right scroll arrows
+    fn arrow_locations(&self) -> ((usize, usize), (usize, usize)) {
+        let layout = self.layout.expect("must have a layout");
+        let loc_x = layout.location.x.round() as usize;
+        let loc_y = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        (
+            (loc_x + 1, loc_y + 1),
+            (loc_x + width.saturating_sub(2), loc_y + 1),
+        )
+    }
+
     ///  ╭──────╮──────┬──────╮
     ///  │ tab1 │ tab2 │ tab2 │
     ///  └──────┴──────┴──────┴
@@ -143,11 +231,19 @@ impl<MSG> TabBox<MSG> {
 
         let tab_rects = self.tab_label_rects();
 
+        if self.has_overflow() {
+            let (left_arrow, right_arrow) = self.arrow_locations();
+            buf.set_symbol(left_arrow.0, left_arrow.1, symbol::ARROW_LEFT);
+            buf.set_symbol(right_arrow.0, right_arrow.1, symbol::ARROW_RIGHT);
+        }
+
         // draw the tabs
-        for (tab_index, ((left, top), (right, bottom))) in
-            tab_rects.iter().enumerate()
-        {
+        for (tab_index, ((left, top), (right, bottom))) in tab_rects.iter() {
+            let tab_index = *tab_index;
             let tab_label = &self.tab_labels[tab_index];
+            if self.is_closable {
+                buf.set_symbol(right - 2, top + 1, symbol::CLOSE);
+            }
             if self.active_tab == tab_index {
                 buf.write_bold_str(left + 2, top + 1, tab_label);
                 canvas.eraser_horizontal_line(
@@ -176,8 +272,14 @@ impl<MSG> TabBox<MSG> {
             );
         }
         // redraw the active tab
+        let active_rect = tab_rects
+            .iter()
+            .find(|(index, _)| *index == self.active_tab);
         let ((active_left, active_top), (active_right, active_bottom)) =
-            &tab_rects[self.active_tab];
+            match active_rect {
+                Some((_, rect)) => rect,
+                None => return,
+            };
         canvas.draw_rect(
             (*active_left, *active_top),
             (*active_right, *active_bottom),
@@ -208,6 +310,152 @@ impl<MSG> TabBox<MSG> {
         }
     }
 
+    /// return the active tab index
+    pub fn active_tab(&self) -> usize {
+        self.active_tab
+    }
+
+    /// set whether to show a close button on each of the tabs
+    pub fn set_closable(&mut self, is_closable: bool) {
+        self.is_closable = is_closable;
+    }
+
+    /// attach a callback which is called when the active tab is changed by the user,
+    /// the label of the active tab is sent as the value of the input event
+    pub fn on_tab_change<F>(&mut self, f: F)
+    where
+        F: FnMut(Event) -> MSG + 'static,
+    {
+        self.on_tab_change.push(f.into());
+    }
+
+    /// attach a callback which is called when a tab is closed by the user,
+    /// the label of the closed tab is sent as the value of the input event
+    pub fn on_tab_close<F>(&mut self, f: F)
+    where
+        F: FnMut(Event) -> MSG + 'static,
+    {
+        self.on_tab_close.push(f.into());
+    }
+
+    /// remove the tab and its children
+    pub fn remove_tab(&mut self, index: usize) {
+        if index >= self.tab_labels.len() {
+            return;
+        }
+        self.tab_labels.remove(index);
+        if index < self.children.len() {
+            self.children.remove(index);
+        }
+        if index < self.active_tab || self.active_tab >= self.tab_labels.len() {
+            self.active_tab = self.active_tab.saturating_sub(1);
+        }
+    }
+
+    /// move the tab and its children to a new index
+    pub fn move_tab(&mut self, from: usize, to: usize) {
+        let len = self.tab_labels.len();
+        if from >= len || to >= len || from == to {
+            return;
+        }
+        let label = self.tab_labels.remove(from);
+        self.tab_labels.insert(to, label);
+        self.ensure_has_tab_index(len);
+        let children = self.children.remove(from);
+        self.children.insert(to, children);
+        if self.active_tab == from {
+            self.active_tab = to;
+        } else if from < self.active_tab && self.active_tab <= to {
+            self.active_tab -= 1;
+        } else if to <= self.active_tab && self.active_tab < from {
+            self.active_tab += 1;
+        }
+    }
+
+    fn emit(callbacks: &mut [Callback<Event, MSG>], label: String) -> Vec<MSG> {
+        let event = Event::from(InputEvent::from(Value::from(label)));
+        callbacks
+            .iter_mut()
+            .map(|cb| cb.emit(event.clone()))
+            .collect()
+    }
+
+    /// change the active tab and return the messages of the on_tab_change callbacks
+    fn change_tab(&mut self, index: usize) -> Vec<MSG> {
+        if index == self.active_tab || index >= self.tab_labels.len() {
+            return vec![];
+        }
+        self.active_tab = index;
+        let label = self.tab_labels[index].clone();
+        Self::emit(&mut self.on_tab_change, label)
+    }
+
+    /// close the tab, the on_tab_change callbacks is also called
+    /// when the active tab is closed
+    fn close_tab(&mut self, index: usize) -> Vec<MSG> {
+        let label = self.tab_labels[index].clone();
+        let was_active = index == self.active_tab;
+        self.remove_tab(index);
+        let mut msgs = Self::emit(&mut self.on_tab_close, label);
+        if was_active {
+            if let Some(label) = self.tab_labels.get(self.active_tab).cloned() {
+                msgs.extend(Self::emit(&mut self.on_tab_change, label));
+            }
+        }
+        msgs
+    }
+
+    fn process_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        let is_ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        match key_event.code {
+            KeyCode::PageUp if is_ctrl => {
+                self.change_tab(self.active_tab.saturating_sub(1))
+            }
+            KeyCode::PageDown if is_ctrl => {
+                self.change_tab(self.active_tab + 1)
+            }
+            KeyCode::Left => self.change_tab(self.active_tab.saturating_sub(1)),
+            KeyCode::Right => self.change_tab(self.active_tab + 1),
+            _ => vec![],
+        }
+    }
+
+    fn process_mouse(&mut self, event: &Event) -> Vec<MSG> {
+        let (x, y) = event.extract_location().expect("must have a location");
+        let (x, y) = (x as usize, y as usize);
+        if event.is_mouse_click() {
+            self.dragged_tab = None;
+            if self.has_overflow() {
+                let (left_arrow, right_arrow) = self.arrow_locations();
+                if (x, y) == left_arrow {
+                    return self.change_tab(self.active_tab.saturating_sub(1));
+                } else if (x, y) == right_arrow {
+                    return self.change_tab(self.active_tab + 1);
+                }
+            }
+            match self.hit_tab_label(x, y) {
+                Some(tab_index) if self.hit_close_button(tab_index, x, y) => {
+                    self.close_tab(tab_index)
+                }
+                Some(tab_index) => {
+                    self.dragged_tab = Some(tab_index);
+                    self.change_tab(tab_index)
+                }
+                None => vec![],
+            }
+        } else if event.is_mouse_drag() {
+            if let (Some(from), Some(to)) =
+                (self.dragged_tab, self.hit_tab_label(x, y))
+            {
+                self.move_tab(from, to);
+                self.dragged_tab = Some(to);
+            }
+            vec![]
+        } else {
+            vec![]
+        }
+    }
+
     fn border_top(&self) -> f32 {
         if self.has_border {
             1.0
@@ -360,16 +608,72 @@ where
     }
 
     fn process_event(&mut self, event: Event) -> Vec<MSG> {
-        if event.is_mouse_click() {
-            let (x, y) =
-                event.extract_location().expect("must have a location");
-            if let Some(active_tab) = self.hit_tab_label(x as usize, y as usize)
-            {
-                self.active_tab = active_tab;
-            }
-            vec![]
-        } else {
-            vec![]
+        match event {
+            Event::Key(ke) => self.process_key(ke),
+            Event::Mouse(_) => self.process_mouse(&event),
+            _ => vec![],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expanse::{geometry::Size, number::Number};
+
+    fn tab_box(width: f32, labels: &[&str]) -> TabBox<String> {
+        let mut tab_box = TabBox::new();
+        tab_box.set_tab_labels(labels.iter().map(|l| l.to_string()).collect());
+        tab_box.on_tab_change(|event| match event {
+            Event::InputEvent(ie) => ie.value.to_string(),
+            _ => String::new(),
+        });
+        tab_box.compute_node_layout(Size {
+            width: Number::Defined(width),
+            height: Number::Defined(10.0),
+        });
+        tab_box
+    }
+
+    #[test]
+    fn labels_are_measured_by_display_width() {
+        let tab_box = tab_box(40.0, &["文字", "ab"]);
+        let rects: Vec<usize> = tab_box
+            .tab_label_rects()
+            .iter()
+            .map(|(_, ((left, _), (right, _)))| right - left)
+            .collect();
+        assert_eq!(rects, vec![7, 5]);
+    }
+
+    #[test]
+    fn overflowing_tabs_scroll_to_the_active_tab() {
+        let mut tab_box = tab_box(20.0, &["one", "two", "three", "four"]);
+        assert!(tab_box.has_overflow());
+        let visible = |tab_box: &TabBox<String>| -> Vec<usize> {
+            tab_box.tab_label_rects().iter().map(|(i, _)| *i).collect()
+        };
+        assert_eq!(visible(&tab_box), vec![0, 1]);
+        let ctrl_page_down = Event::from(KeyEvent::new(
+            KeyCode::PageDown,
+            KeyModifiers::CONTROL,
+        ));
+        tab_box.process_event(ctrl_page_down.clone());
+        let msgs = tab_box.process_event(ctrl_page_down);
+        assert_eq!(msgs, vec!["three"]);
+        assert_eq!(visible(&tab_box), vec![1, 2]);
+    }
+
+    #[test]
+    fn move_and_remove_tabs() {
+        let mut tab_box = tab_box(40.0, &["a", "b", "c"]);
+        tab_box.set_active_tab(1);
+        tab_box.move_tab(0, 2);
+        assert_eq!(tab_box.tab_labels, vec!["b", "c", "a"]);
+        assert_eq!(tab_box.active_tab(), 0);
+        tab_box.set_active_tab(2);
+        tab_box.remove_tab(2);
+        assert_eq!(tab_box.tab_labels, vec!["b", "c"]);
+        assert_eq!(tab_box.active_tab(), 1);
+    }
+}
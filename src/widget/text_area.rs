@@ -1,19 +1,54 @@
-use crate::crossterm::event::KeyEvent;
 use crate::crossterm::event::KeyModifiers;
+use crate::crossterm::event::{KeyCode, KeyEvent};
+use crate::crossterm::style::{Attribute, Color};
 use crate::Event;
 use crate::Value;
 use crate::{
-    buffer::Buffer, event::InputEvent, symbol, symbol::bar,
-    text_buffer::AreaBuffer, Callback, Cmd, Widget,
+    buffer::{Buffer, Cell},
+    clipboard,
+    event::InputEvent,
+    highlight::HighlightCache,
+    symbol,
+    symbol::bar,
+    text_buffer::AreaBuffer,
+    unicode, Callback, Cmd, Highlighter, StyledText, Widget,
 };
 use expanse::{
     geometry::Size,
     result::Layout,
     style::{Dimension, PositionType, Style},
 };
+use find::{FindBar, FindField};
 use ito_canvas::unicode_canvas::{Border, Canvas};
 use std::fmt;
 
+mod find;
+
+/// the color of the line numbers and the end of buffer markers
+const GUTTER_COLOR: Color = Color::DarkGrey;
+/// the background color of the line where the cursor is
+const CURRENT_LINE_BACKGROUND: Color = Color::AnsiValue(236);
+/// the marker displayed in the rows after the last line
+const END_OF_BUFFER: &str = "~";
+/// the background color of the matches of the search
+const MATCH_BACKGROUND: Color = Color::DarkYellow;
+/// the background color of the current match of the search
+const CURRENT_MATCH_BACKGROUND: Color = Color::Yellow;
+/// the background color of the find bar
+const FIND_BAR_BACKGROUND: Color = Color::AnsiValue(238);
+
+/// A row on the screen, which is a whole line, or a part of the line when
+/// the lines are wrapped. The columns are the display columns in the line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Row {
+    line: usize,
+    /// the index of this row in the rows of the line
+    index: usize,
+    start_column: usize,
+    end_column: usize,
+    is_last: bool,
+}
+
 /// A textarea is a 2 dimensional editor
 /// where each line is separated by \n.
 #[derive(Debug)]
@@ -23,10 +58,32 @@ pub struct TextArea<MSG> {
     focused: bool,
     width: Option<f32>,
     height: Option<f32>,
+    /// the first visible line
     scroll_top: f32,
+    /// the first visible row of the first visible line, when the lines are wrapped
+    scroll_top_row: usize,
     scroll_left: f32,
     id: Option<String>,
     on_input: Vec<Callback<Event, MSG>>,
+    /// the content can not be edited, only scrolled and navigated
+    read_only: bool,
+    /// the styles of each line, these are dropped once the content is edited
+    styled_lines: Option<Vec<StyledText>>,
+    /// highlight the syntax of the lines when there are no styled lines
+    highlight: Option<HighlightCache>,
+    /// also copy to the clipboard of the system with OSC 52
+    use_osc52: bool,
+    line_numbers: bool,
+    /// show the distance from the line of the cursor instead of the line number
+    relative_line_numbers: bool,
+    /// the minimum width of the gutter where the line numbers are displayed
+    gutter_width: usize,
+    highlight_current_line: bool,
+    /// wrap the long lines instead of scrolling horizontally
+    soft_wrap: bool,
+    show_end_of_buffer: bool,
+    /// the find bar is displayed at the bottom when searching
+    find: Option<FindBar>,
     has_border: bool,
     is_rounded_border: bool,
     is_thick_border: bool,
@@ -45,9 +102,21 @@ impl<MSG> TextArea<MSG> {
             height: None,
             focused: false,
             scroll_top: 0.0,
+            scroll_top_row: 0,
             scroll_left: 0.0,
             id: None,
             on_input: vec![],
+            read_only: false,
+            styled_lines: None,
+            highlight: None,
+            use_osc52: false,
+            line_numbers: false,
+            relative_line_numbers: false,
+            gutter_width: 0,
+            highlight_current_line: false,
+            soft_wrap: false,
+            show_end_of_buffer: false,
+            find: None,
             has_border: true,
             is_rounded_border: false,
             is_thick_border: false,
@@ -64,19 +133,666 @@ impl<MSG> TextArea<MSG> {
         self.area_buffer.to_string()
     }
 
-    /// process the keypress event
-    pub fn process_key(&mut self, key_event: KeyEvent) {
-        self.area_buffer.process_key_event(key_event);
+    /// process the keypress event, return true if the content is changed
+    pub fn process_key(&mut self, key_event: KeyEvent) -> bool {
+        if self.find.is_some() {
+            return self.process_find_key(key_event);
+        }
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+        let page = self
+            .layout
+            .map(|layout| self.text_height(&layout).max(1.0) as isize)
+            .unwrap_or(1);
+        let is_changed = match key_event.code {
+            KeyCode::Char('c') if ctrl => {
+                self.copy();
+                false
+            }
+            KeyCode::Char('x') if ctrl && !self.read_only => {
+                self.copy();
+                self.area_buffer.delete_selection()
+            }
+            KeyCode::Char('v') if ctrl && !self.read_only => {
+                self.area_buffer.insert_str(&clipboard::get());
+                true
+            }
+            KeyCode::Up | KeyCode::Down if self.soft_wrap && !ctrl => {
+                let delta = if key_event.code == KeyCode::Up { -1 } else { 1 };
+                self.move_rows(delta, shift);
+                false
+            }
+            KeyCode::PageUp => {
+                self.area_buffer.move_lines(-page, shift);
+                false
+            }
+            KeyCode::PageDown => {
+                self.area_buffer.move_lines(page, shift);
+                false
+            }
+            KeyCode::Char('a') if ctrl => {
+                self.area_buffer.select_all();
+                false
+            }
+            KeyCode::Char('f') if ctrl => {
+                self.open_find(false);
+                false
+            }
+            KeyCode::Char('h') if ctrl && !self.read_only => {
+                self.open_find(true);
+                false
+            }
+            _ if self.read_only => {
+                if is_navigation_key(&key_event) {
+                    self.area_buffer.process_key_event(key_event);
+                }
+                false
+            }
+            _ => self.area_buffer.process_key_event(key_event),
+        };
+        if is_changed {
+            self.content_changed();
+        }
+        self.scroll_to_cursor();
+        is_changed
+    }
+
+    /// process the key while the find bar is open,
+    /// return true if the content is changed by the replace
+    fn process_find_key(&mut self, key_event: KeyEvent) -> bool {
+        let ctrl = key_event.modifiers.contains(KeyModifiers::CONTROL);
+        let shift = key_event.modifiers.contains(KeyModifiers::SHIFT);
+        let alt = key_event.modifiers.contains(KeyModifiers::ALT);
+        let find = self.find.as_mut().expect("must have a find bar");
+        let is_replacing = find.focus == FindField::Replacement;
+        match key_event.code {
+            KeyCode::Esc => {
+                self.close_find();
+                false
+            }
+            KeyCode::Char('c') | KeyCode::Char('C') if alt => {
+                find.case_sensitive = !find.case_sensitive;
+                self.search();
+                false
+            }
+            KeyCode::Char('r') | KeyCode::Char('R') if alt => {
+                find.use_regex = !find.use_regex;
+                self.search();
+                false
+            }
+            KeyCode::Char('a') | KeyCode::Char('A') if alt => {
+                self.replace_all() > 0
+            }
+            KeyCode::Char('f') if ctrl => {
+                find.focus = FindField::Query;
+                false
+            }
+            KeyCode::Char('h') if ctrl && !self.read_only => {
+                find.show_replace = true;
+                find.focus = FindField::Replacement;
+                self.scroll_to_cursor();
+                false
+            }
+            KeyCode::Tab | KeyCode::BackTab if find.show_replace => {
+                find.focus = if is_replacing {
+                    FindField::Query
+                } else {
+                    FindField::Replacement
+                };
+                false
+            }
+            KeyCode::Enter if is_replacing => self.replace_current(),
+            KeyCode::Enter | KeyCode::F(3) if shift => {
+                self.find_previous();
+                false
+            }
+            KeyCode::Up => {
+                self.find_previous();
+                false
+            }
+            KeyCode::Enter | KeyCode::F(3) | KeyCode::Down => {
+                self.find_next();
+                false
+            }
+            _ => {
+                if is_replacing {
+                    find.replacement.process_key_event(key_event);
+                } else {
+                    let query = find.query.get_content().to_string();
+                    find.query.process_key_event(key_event);
+                    // incremental search as the query is typed
+                    if find.query.get_content() != query {
+                        self.search();
+                    }
+                }
+                false
+            }
+        }
+    }
+
+    /// open the find bar, searching from the cursor.
+    /// The replace field is also displayed when `replace` is true.
+    pub fn open_find(&mut self, replace: bool) {
+        let replace = replace && !self.read_only;
+        match &mut self.find {
+            Some(find) => {
+                find.show_replace |= replace;
+                find.focus = if replace {
+                    FindField::Replacement
+                } else {
+                    FindField::Query
+                };
+            }
+            None => {
+                let (x, y) = self.area_buffer.get_cursor_location();
+                let line = self.area_buffer.line(y);
+                let origin = (y, unicode::byte_index(&line, x));
+                self.find = Some(FindBar::new(replace, origin));
+            }
+        }
+        self.scroll_to_cursor();
+    }
+
+    /// close the find bar, the current match stays selected
+    pub fn close_find(&mut self) {
+        self.find = None;
+        self.scroll_to_cursor();
+    }
+
+    /// set the text to search, the first match after the cursor is selected
+    pub fn set_find_query(&mut self, query: &str) {
+        if self.find.is_none() {
+            self.open_find(false);
+        }
+        if let Some(find) = &mut self.find {
+            find.query.set_content(query);
+        }
+        self.search();
+    }
+
+    /// set the text which replaces the matches, with regex enabled
+    /// `$1` or `${name}` is replaced with the captured group
+    pub fn set_replacement(&mut self, replacement: &str) {
+        if let Some(find) = &mut self.find {
+            find.replacement.set_content(replacement);
+        }
+    }
+
+    /// set whether the case of the letters has to match in the search
+    pub fn set_find_case_sensitive(&mut self, case_sensitive: bool) {
+        if let Some(find) = &mut self.find {
+            find.case_sensitive = case_sensitive;
+        }
+        self.search();
+    }
+
+    /// set whether the query is a regular expression
+    pub fn set_find_regex(&mut self, use_regex: bool) {
+        if let Some(find) = &mut self.find {
+            find.use_regex = use_regex;
+        }
+        self.search();
+    }
+
+    /// return the number of matches and the index of the current match
+    pub fn find_status(&self) -> Option<(usize, Option<usize>)> {
+        self.find
+            .as_ref()
+            .map(|find| (find.matches().len(), find.current()))
+    }
+
+    /// search the content again and select the current match
+    fn search(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.search(&self.area_buffer);
+        }
+        self.select_current_match();
+    }
+
+    /// select the next match of the search
+    pub fn find_next(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.next();
+        }
+        self.select_current_match();
+    }
+
+    /// select the previous match of the search
+    pub fn find_previous(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.previous();
+        }
+        self.select_current_match();
+    }
+
+    fn select_current_match(&mut self) {
+        let current = self.find.as_ref().and_then(|find| find.current_match());
+        if let Some(m) = current {
+            let line = self.area_buffer.line(m.line);
+            let start = unicode::grapheme_count(&line[..m.start]);
+            let end = unicode::grapheme_count(&line[..m.end]);
+            self.area_buffer.set_cursor_loc(start, m.line);
+            self.area_buffer.move_cursor_to(end, m.line, true);
+            self.scroll_to_cursor();
+        }
+    }
+
+    /// replace the current match and select the next match,
+    /// return true if a match is replaced
+    pub fn replace_current(&mut self) -> bool {
+        if self.read_only {
+            return false;
+        }
+        let find = match &mut self.find {
+            Some(find) => find,
+            None => return false,
+        };
+        let m = match find.current_match() {
+            Some(m) => m,
+            None => return false,
+        };
+        let replacement = find.replacement_for(&self.area_buffer, m);
+        // the next search starts after the replaced text
+        find.set_origin((m.line, m.start + replacement.len()));
+        self.area_buffer.replace_ranges(vec![(
+            m.line,
+            m.start..m.end,
+            replacement,
+        )]);
+        self.content_changed();
+        self.search();
+        true
+    }
+
+    /// replace all the matches as one undo step, return the number of replaced matches
+    pub fn replace_all(&mut self) -> usize {
+        if self.read_only {
+            return 0;
+        }
+        let find = match &self.find {
+            Some(find) => find,
+            None => return 0,
+        };
+        let replacements: Vec<_> = find
+            .matches()
+            .iter()
+            .map(|m| {
+                (
+                    m.line,
+                    m.start..m.end,
+                    find.replacement_for(&self.area_buffer, *m),
+                )
+            })
+            .collect();
+        let count = replacements.len();
+        if count > 0 {
+            self.area_buffer.replace_ranges(replacements);
+            self.content_changed();
+            self.search();
+        }
+        count
+    }
+
+    /// copy the selected text into the clipboard
+    fn copy(&self) {
+        if let Some(selected) = self.area_buffer.selected_text() {
+            clipboard::set(&selected);
+            if self.use_osc52 {
+                // the system clipboard is optional, the text is still
+                // in the internal clipboard when this fails
+                clipboard::set_osc52(&selected).ok();
+            }
+        }
+    }
+
+    /// return the selected text
+    pub fn selected_text(&self) -> Option<String> {
+        self.area_buffer.selected_text()
+    }
+
+    /// set whether the copied text is also sent to the clipboard of the system
+    /// using the OSC 52 escape sequence, this needs a terminal which supports it.
+    /// The text is always copied to the internal clipboard.
+    pub fn set_use_osc52(&mut self, use_osc52: bool) {
+        self.use_osc52 = use_osc52;
+    }
+
+    /// scroll the content so that the cursor is visible
+    fn scroll_to_cursor(&mut self) {
+        let layout = match self.layout {
+            Some(layout) => layout,
+            None => return,
+        };
+        if self.soft_wrap {
+            self.scroll_to_cursor_row(&layout);
+            return;
+        }
+        let (column, line) = self.area_buffer.get_cursor_column();
+        let (column, line) = (column as f32, line as f32);
+        let text_width = self.text_width(&layout);
+        let inner_height = self.text_height(&layout);
+        if line < self.scroll_top {
+            self.scroll_top = line;
+        } else if line >= self.scroll_top + inner_height {
+            self.scroll_top = line - inner_height + 1.0;
+        }
+        if column < self.scroll_left {
+            self.scroll_left = column;
+        } else if column >= self.scroll_left + text_width {
+            self.scroll_left = column - text_width + 1.0;
+        }
+    }
+
+    /// scroll the wrapped rows so that the row of the cursor is visible
+    fn scroll_to_cursor_row(&mut self, layout: &Layout) {
+        let inner_height = (self.text_height(layout) as usize).max(1);
+        let cursor = self.cursor_row(layout);
+        let top = (self.scroll_top as usize, self.scroll_top_row);
+        if (cursor.line, cursor.index) < top {
+            self.scroll_top = cursor.line as f32;
+            self.scroll_top_row = cursor.index;
+            return;
+        }
+        // every line has at least 1 row, so the rows are only counted
+        // when the line of the cursor is near the top
+        if cursor.line - top.0 < inner_height {
+            let rows_before_top = top.1;
+            let rows_after_cursor =
+                self.line_rows(cursor.line, layout).len() - cursor.index - 1;
+            let rows: usize = (top.0..=cursor.line)
+                .map(|line| self.line_rows(line, layout).len())
+                .sum();
+            if rows - rows_before_top - rows_after_cursor <= inner_height {
+                return;
+            }
+        }
+        // the cursor is at the bottom row of the view
+        let (mut line, mut index) = (cursor.line, cursor.index);
+        let mut remaining = inner_height - 1;
+        while remaining > 0 {
+            if index >= remaining {
+                index -= remaining;
+                remaining = 0;
+            } else if line > 0 {
+                remaining -= index + 1;
+                line -= 1;
+                index = self.line_rows(line, layout).len() - 1;
+            } else {
+                index = 0;
+                remaining = 0;
+            }
+        }
+        self.scroll_top = line as f32;
+        self.scroll_top_row = index;
+    }
+
+    /// the rows of the line, this is 1 row when the lines are not wrapped
+    fn line_rows(&self, y: usize, layout: &Layout) -> Vec<Row> {
+        if !self.soft_wrap {
+            return vec![Row {
+                line: y,
+                index: 0,
+                start_column: 0,
+                end_column: usize::MAX,
+                is_last: true,
+            }];
+        }
+        let text_width = (self.text_width(layout) as usize).max(1);
+        let line = self.area_buffer.line(y);
+        let mut columns: Vec<(usize, usize)> =
+            unicode::wrap_chars(&line, text_width)
+                .into_iter()
+                .map(|range| {
+                    let start = unicode::str_width(&line[..range.start]);
+                    (start, start + unicode::str_width(&line[range]))
+                })
+                .collect();
+        // a full last row leaves no room for the cursor at the end of the line
+        if let Some(&(start, end)) = columns.last() {
+            if end - start >= text_width {
+                columns.push((end, end));
+            }
+        }
+        let count = columns.len();
+        columns
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start_column, end_column))| Row {
+                line: y,
+                index,
+                start_column,
+                end_column,
+                is_last: index + 1 == count,
+            })
+            .collect()
+    }
+
+    /// the rows which are visible in the text area
+    fn visible_rows(&self, layout: &Layout) -> Vec<Row> {
+        let inner_height = self.text_height(layout) as usize;
+        let mut rows = vec![];
+        let mut skip = self.scroll_top_row;
+        for y in self.scroll_top.max(0.0) as usize..self.area_buffer.height() {
+            if rows.len() >= inner_height {
+                break;
+            }
+            let line_rows = self.line_rows(y, layout);
+            rows.extend(line_rows.into_iter().skip(skip));
+            skip = 0;
+        }
+        rows.truncate(inner_height);
+        rows
+    }
+
+    /// the row where the cursor is
+    fn cursor_row(&self, layout: &Layout) -> Row {
+        let (column, y) = self.area_buffer.get_cursor_column();
+        let rows = self.line_rows(y, layout);
+        let index = rows
+            .iter()
+            .rposition(|row| row.start_column <= column)
+            .unwrap_or(0);
+        rows[index]
+    }
+
+    /// move the cursor up or down by a number of rows of the wrapped lines,
+    /// keeping the cursor in the same column of the row as much as possible
+    fn move_rows(&mut self, delta: isize, select: bool) {
+        let layout = match self.layout {
+            Some(layout) => layout,
+            None => return,
+        };
+        let (column, _) = self.area_buffer.get_cursor_column();
+        let mut row = self.cursor_row(&layout);
+        let column_in_row = column - row.start_column;
+        for _ in 0..delta.unsigned_abs() {
+            row = if delta < 0 {
+                if row.index > 0 {
+                    self.line_rows(row.line, &layout)[row.index - 1]
+                } else if row.line > 0 {
+                    let rows = self.line_rows(row.line - 1, &layout);
+                    rows[rows.len() - 1]
+                } else {
+                    break;
+                }
+            } else if !row.is_last {
+                self.line_rows(row.line, &layout)[row.index + 1]
+            } else if row.line + 1 < self.area_buffer.height() {
+                self.line_rows(row.line + 1, &layout)[0]
+            } else {
+                break;
+            };
+        }
+        let line = self.area_buffer.line(row.line);
+        let mut x =
+            unicode::index_at_column(&line, row.start_column + column_in_row);
+        // stay in the row when the row is shorter than the column
+        if !row.is_last {
+            let row_end = unicode::index_at_column(&line, row.end_column);
+            x = x.min(row_end.saturating_sub(1));
+        }
+        self.area_buffer.move_cursor_to(x, row.line, select);
+    }
+
+    /// the columns scrolled to the left, the wrapped lines are not scrolled
+    fn scroll_left_columns(&self) -> usize {
+        if self.soft_wrap {
+            0
+        } else {
+            self.scroll_left.max(0.0) as usize
+        }
+    }
+
+    /// the column and line of the content at this location of the screen
+    fn content_location(&self, x: u16, y: u16) -> (usize, usize) {
+        let layout = self.layout.expect("must have a layout");
+        let x = x as f32
+            - layout.location.x.round()
+            - self.border_left()
+            - self.gutter_width() as f32;
+        let y = y as f32 - layout.location.y.round() - self.border_top();
+        let rows = self.visible_rows(&layout);
+        let row = match rows.get(y.max(0.0) as usize).or_else(|| rows.last()) {
+            Some(row) => *row,
+            None => return (0, 0),
+        };
+        let mut column =
+            row.start_column + x.max(0.0) as usize + self.scroll_left_columns();
+        if !row.is_last {
+            column = column.min(row.end_column.saturating_sub(1));
+        }
+        (column, row.line)
+    }
+
+    /// set whether to show the line numbers at the left of the text
+    pub fn set_line_numbers(&mut self, line_numbers: bool) {
+        self.line_numbers = line_numbers;
+    }
+
+    /// set whether to show the distance of the lines from the line of the cursor,
+    /// the line of the cursor still shows its line number.
+    /// This is used together with `set_line_numbers`.
+    pub fn set_relative_line_numbers(&mut self, relative_line_numbers: bool) {
+        self.relative_line_numbers = relative_line_numbers;
+    }
+
+    /// set the minimum width of the gutter where the line numbers are displayed,
+    /// the gutter is widened when the line numbers does not fit
+    pub fn set_gutter_width(&mut self, gutter_width: usize) {
+        self.gutter_width = gutter_width;
+    }
+
+    /// set whether to highlight the line where the cursor is
+    pub fn set_highlight_current_line(&mut self, highlight_current_line: bool) {
+        self.highlight_current_line = highlight_current_line;
+    }
+
+    /// set whether to wrap the lines which are wider than the text area,
+    /// the up and down keys then move the cursor by the wrapped rows
+    pub fn set_soft_wrap(&mut self, soft_wrap: bool) {
+        self.soft_wrap = soft_wrap;
+        self.scroll_left = 0.0;
+        self.scroll_top_row = 0;
+    }
+
+    /// set whether to show a marker in the rows after the last line
+    pub fn set_show_end_of_buffer(&mut self, show_end_of_buffer: bool) {
+        self.show_end_of_buffer = show_end_of_buffer;
     }
 
     /// set the value of this text area
     pub fn set_value<S: ToString>(&mut self, value: S) {
-        self.area_buffer = AreaBuffer::from(value.to_string());
+        self.area_buffer.set_content(value.to_string());
+        self.styled_lines = None;
+        self.reset_highlight();
+        self.refresh_find();
+    }
+
+    /// set the value with a text which has colors and attributes,
+    /// such as the text parsed with `StyledText::from_ansi`.
+    /// The styles are dropped once the content is edited, so this is best
+    /// used together with `set_read_only`.
+    pub fn set_styled_value<T: Into<StyledText>>(&mut self, value: T) {
+        let value = value.into();
+        self.area_buffer.set_content(value.to_string());
+        self.styled_lines = Some(value.lines());
+        self.reset_highlight();
+        self.refresh_find();
+    }
+
+    /// undo the last edit, return true if there is an edit undone
+    pub fn undo(&mut self) -> bool {
+        let is_undone = self.area_buffer.undo();
+        if is_undone {
+            self.content_changed();
+            self.scroll_to_cursor();
+        }
+        is_undone
+    }
+
+    /// redo the last undone edit, return true if there is an edit redone
+    pub fn redo(&mut self) -> bool {
+        let is_redone = self.area_buffer.redo();
+        if is_redone {
+            self.content_changed();
+            self.scroll_to_cursor();
+        }
+        is_redone
+    }
+
+    /// set the maximum number of edits which can be undone, the default is 100
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.area_buffer.set_history_depth(depth);
+    }
+
+    /// set the highlighter which colors the syntax of the content,
+    /// the lines are highlighted again as they are edited
+    pub fn set_highlighter<H: Highlighter + 'static>(
+        &mut self,
+        highlighter: H,
+    ) {
+        self.highlight = Some(HighlightCache::new(
+            Box::new(highlighter),
+            self.area_buffer.height(),
+        ));
+    }
+
+    /// drop the styles of the edited lines
+    fn content_changed(&mut self) {
+        self.styled_lines = None;
+        let changes = self.area_buffer.take_line_changes();
+        if let Some(highlight) = &mut self.highlight {
+            for change in changes {
+                highlight.apply(change);
+            }
+        }
+        self.refresh_find();
+    }
+
+    /// the matches are byte ranges of the content, so they are searched
+    /// again whenever the content changes, without moving the cursor
+    fn refresh_find(&mut self) {
+        if let Some(find) = &mut self.find {
+            find.search(&self.area_buffer);
+        }
+    }
+
+    fn reset_highlight(&mut self) {
+        if let Some(highlight) = &self.highlight {
+            highlight.reset(self.area_buffer.height());
+        }
+    }
+
+    /// set whether the content can be edited, a read only text area
+    /// can still be scrolled and the cursor moved with the arrow keys
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
     }
 
     /// add a line to the last end of buffer of this text area
     pub fn add_line<S: ToString>(&mut self, s: S) {
         self.area_buffer.add_line(s);
+        self.content_changed();
     }
 
     /// return the string value of this text_area
@@ -138,6 +854,28 @@ impl<MSG> TextArea<MSG> {
         }
     }
 
+    /// the width of the line numbers including the space after it
+    fn gutter_width(&self) -> usize {
+        if self.line_numbers {
+            let digits = self.area_buffer.height().to_string().len();
+            self.gutter_width.max(digits + 1)
+        } else {
+            0
+        }
+    }
+
+    /// the width where the text is displayed, after the gutter
+    fn text_width(&self, layout: &Layout) -> f32 {
+        (self.inner_width(layout) - self.gutter_width() as f32).max(0.0)
+    }
+
+    /// the height where the text is displayed, above the find bar
+    fn text_height(&self, layout: &Layout) -> f32 {
+        let find_bar_height =
+            self.find.as_ref().map(|find| find.height()).unwrap_or(0);
+        (self.inner_height(layout) - find_bar_height as f32).max(0.0)
+    }
+
     fn content_height(&self) -> f32 {
         self.area_buffer.height() as f32
     }
@@ -154,16 +892,166 @@ impl<MSG> TextArea<MSG> {
         3.0
     }
 
-    fn cursor_location(&self, layout: &Layout) -> (f32, f32) {
-        let (cursor_loc_x, cursor_loc_y) =
-            self.area_buffer.get_cursor_location();
+    /// the location of the cursor on the screen, when it is visible
+    fn cursor_location(
+        &self,
+        layout: &Layout,
+        rows: &[Row],
+    ) -> Option<(usize, usize)> {
+        let (column, _) = self.area_buffer.get_cursor_column();
+        let cursor = self.cursor_row(layout);
+        let i = rows.iter().position(|row| *row == cursor)?;
+        let x = (column - cursor.start_column)
+            .checked_sub(self.scroll_left_columns())?;
+        if x as f32 > self.text_width(layout) {
+            return None;
+        }
+        let text_left = layout.location.x.round()
+            + self.border_left()
+            + self.gutter_width() as f32;
+        let top = layout.location.y.round() + self.border_top();
+        Some((text_left as usize + x, top as usize + i))
+    }
 
-        let loc_x = layout.location.x.round();
-        let loc_y = layout.location.y.round();
+    /// draw the line number of the row, or its distance from the line of the cursor
+    fn draw_line_number(
+        &self,
+        buf: &mut Buffer,
+        left: usize,
+        top: usize,
+        line: usize,
+        cursor_line: usize,
+    ) {
+        let number = if self.relative_line_numbers && line != cursor_line {
+            line.abs_diff(cursor_line)
+        } else {
+            line + 1
+        };
+        // right aligned, with a space before the text
+        let number = format!(
+            "{:>width$} ",
+            number,
+            width = self.gutter_width().saturating_sub(1)
+        );
+        for (i, ch) in number.chars().enumerate() {
+            let mut cell = Cell::new(ch);
+            if line == cursor_line {
+                cell.bold();
+            } else {
+                cell.color(GUTTER_COLOR);
+            }
+            buf.set_cell(left + i, top, cell);
+        }
+    }
+
+    /// draw the find bar at the bottom of the text area,
+    /// return the location of the cursor in the focused field
+    fn draw_find_bar(
+        &self,
+        buf: &mut Buffer,
+        find: &FindBar,
+        layout: &Layout,
+    ) -> (usize, usize) {
+        let left = (layout.location.x.round() + self.border_left()) as usize;
+        let top = (layout.location.y.round()
+            + self.border_top()
+            + self.text_height(layout)) as usize;
+        let width = self.inner_width(layout) as usize;
+        let status = if find.is_invalid {
+            "invalid regex".to_string()
+        } else if find.query.get_content().is_empty() {
+            String::new()
+        } else {
+            match find.current() {
+                Some(current) => {
+                    format!("{}/{}", current + 1, find.matches().len())
+                }
+                None => "no results".to_string(),
+            }
+        };
+        let mut rows = vec![("Find: ", &find.query, status, FindField::Query)];
+        if find.show_replace {
+            rows.push((
+                "Replace: ",
+                &find.replacement,
+                String::new(),
+                FindField::Replacement,
+            ));
+        }
+        let mut cursor = (left, top);
+        for (i, (label, input, status, field)) in rows.into_iter().enumerate() {
+            let y = top + i;
+            for x in 0..width {
+                let mut cell = Cell::new(" ");
+                cell.background(FIND_BAR_BACKGROUND);
+                buf.set_cell(left + x, y, cell);
+            }
+            let mut cells = StyledText::from(label).to_cells();
+            cells.extend(StyledText::from(input.get_content()).to_cells());
+            // the status is followed by the 2 toggles of 5 columns each
+            let status_width = match field {
+                FindField::Query => unicode::str_width(&status) + 10,
+                FindField::Replacement => 0,
+            };
+            let text_width = width.saturating_sub(status_width + 1);
+            let mut column = 0;
+            for mut cell in cells {
+                let cell_width = cell.unicode_width();
+                if column + cell_width > text_width {
+                    break;
+                }
+                cell.background(FIND_BAR_BACKGROUND);
+                buf.set_cell(left + column, y, cell);
+                column += cell_width;
+            }
+            // the toggles are reversed when they are enabled
+            let toggles = if field == FindField::Query {
+                vec![(" [Aa]", find.case_sensitive), (" [.*]", find.use_regex)]
+            } else {
+                vec![]
+            };
+            let mut status_cells = StyledText::from(status.as_str()).to_cells();
+            for (toggle, enabled) in toggles {
+                for (j, mut cell) in
+                    StyledText::from(toggle).to_cells().into_iter().enumerate()
+                {
+                    // the leading space is not reversed
+                    if enabled && j > 0 {
+                        cell.attributes(vec![Attribute::Reverse]);
+                    }
+                    status_cells.push(cell);
+                }
+            }
+            let status_left = left + width.saturating_sub(status_cells.len());
+            for (j, mut cell) in status_cells.into_iter().enumerate() {
+                if status_left + j < left {
+                    continue;
+                }
+                cell.background(FIND_BAR_BACKGROUND);
+                buf.set_cell(status_left + j, y, cell);
+            }
+            if field == find.focus {
+                let x = unicode::str_width(label) + input.get_cursor_column();
+                cursor = (left + x.min(text_width), y);
+            }
+        }
+        cursor
+    }
 
-        let abs_cursor_x = loc_x + cursor_loc_x as f32 + 1.0;
-        let abs_cursor_y = loc_y + cursor_loc_y as f32 + 1.0 - self.scroll_top;
-        (abs_cursor_x, abs_cursor_y)
+    /// the styled cells of the line
+    fn line_cells(&self, y: usize, line: &str) -> Vec<Cell> {
+        match &self.styled_lines {
+            Some(styled_lines) => styled_lines
+                .get(y)
+                .map(|styled| styled.to_cells())
+                .unwrap_or_default(),
+            None => match &self.highlight {
+                Some(highlight) => {
+                    highlight.line(&self.area_buffer, y).to_cells()
+                }
+                None => StyledText::from(line).to_cells(),
+            },
+        }
     }
 
     fn draw_scrollers(&self, buf: &mut Buffer) {
@@ -232,6 +1120,21 @@ impl<MSG> TextArea<MSG> {
     }
 }
 
+/// whether the key only moves the cursor and does not edit the content
+fn is_navigation_key(key_event: &KeyEvent) -> bool {
+    matches!(
+        key_event.code,
+        KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Up
+            | KeyCode::Down
+            | KeyCode::Home
+            | KeyCode::End
+            | KeyCode::PageUp
+            | KeyCode::PageDown
+    )
+}
+
 impl<MSG> Widget<MSG> for TextArea<MSG>
 where
     MSG: fmt::Debug + 'static,
@@ -264,48 +1167,128 @@ where
     /// draw this button to the buffer, with the given computed layout
     fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
         let layout = self.layout.expect("must have a layout");
-        let loc_x = layout.location.x.round();
-        let loc_y = layout.location.y.round();
-        let height = layout.size.height.round();
+        let inner_left =
+            (layout.location.x.round() + self.border_left()) as usize;
+        let inner_top =
+            (layout.location.y.round() + self.border_top()) as usize;
+        let inner_height = self.text_height(&layout) as usize;
+        let text_left = inner_left + self.gutter_width();
+        let text_width = self.text_width(&layout) as usize;
+        let scroll_left = self.scroll_left_columns();
+        let (_, cursor_line) = self.area_buffer.get_cursor_column();
+        let selection = self.area_buffer.selection();
 
-        let bottom = loc_y + height - 1.0;
-
-        // draw the text content
-        let text_loc_y = loc_y - self.scroll_top;
-        let text_loc_x = loc_x - self.scroll_left;
-        let bottom_scroll = self.inner_height(&layout) + self.scroll_top;
-        let right_scroll = self.inner_width(&layout) + self.scroll_left;
-
-        for (j, line) in self.area_buffer.content.iter().enumerate() {
-            if (j as f32) >= self.scroll_top && (j as f32) < bottom_scroll {
-                for (i, ch) in line.iter().enumerate() {
-                    if (i as f32) >= self.scroll_left
-                        && (i as f32) < right_scroll
+        // only the visible lines are taken from the buffer
+        let rows = self.visible_rows(&layout);
+        for (i, row) in rows.iter().enumerate() {
+            let top = inner_top + i;
+            if self.line_numbers && row.index == 0 {
+                self.draw_line_number(
+                    buf,
+                    inner_left,
+                    top,
+                    row.line,
+                    cursor_line,
+                );
+            }
+            let is_current =
+                self.highlight_current_line && row.line == cursor_line;
+            if is_current {
+                for x in 0..text_width {
+                    let mut cell = Cell::new(" ");
+                    cell.background(CURRENT_LINE_BACKGROUND);
+                    buf.set_cell(text_left + x, top, cell);
+                }
+            }
+            let line = self.area_buffer.line(row.line);
+            // the selected columns in this line
+            let selected = match selection {
+                Some(((start_x, start_y), (end_x, end_y)))
+                    if start_y <= row.line && row.line <= end_y =>
+                {
+                    let start = if row.line == start_y {
+                        unicode::column_of(&line, start_x)
+                    } else {
+                        0
+                    };
+                    let end = if row.line == end_y {
+                        unicode::column_of(&line, end_x)
+                    } else {
+                        usize::MAX
+                    };
+                    start..end
+                }
+                _ => 0..0,
+            };
+            // the columns of the matches of the search in this line
+            let matches: Vec<(std::ops::Range<usize>, bool)> = match &self.find
+            {
+                Some(find) => find
+                    .line_matches(row.line)
+                    .map(|(i, m)| {
+                        let start = unicode::str_width(&line[..m.start]);
+                        let end = unicode::str_width(&line[..m.end]);
+                        (start..end, find.current() == Some(i))
+                    })
+                    .collect(),
+                None => vec![],
+            };
+            let mut column = 0;
+            for mut cell in self.line_cells(row.line, &line) {
+                let width = cell.unicode_width();
+                let is_in_row = column >= row.start_column
+                    && column + width <= row.end_column;
+                if is_in_row {
+                    let x = column - row.start_column;
+                    if x >= scroll_left && x + width <= scroll_left + text_width
                     {
-                        buf.set_symbol(
-                            (text_loc_x + i as f32 + 1.0) as usize,
-                            (text_loc_y + j as f32 + 1.0) as usize,
-                            ch,
-                        );
+                        if is_current {
+                            cell.background(CURRENT_LINE_BACKGROUND);
+                        }
+                        let found = matches
+                            .iter()
+                            .find(|(range, _)| range.contains(&column));
+                        match found {
+                            Some((_, true)) => {
+                                cell.color(Color::Black);
+                                cell.background(CURRENT_MATCH_BACKGROUND);
+                            }
+                            Some((_, false)) => {
+                                cell.color(Color::Black);
+                                cell.background(MATCH_BACKGROUND);
+                            }
+                            None if selected.contains(&column) => {
+                                cell.attributes(vec![Attribute::Reverse]);
+                            }
+                            None => (),
+                        }
+                        buf.set_cell(text_left + x - scroll_left, top, cell);
                     }
                 }
+                column += width;
+            }
+        }
+        if self.show_end_of_buffer {
+            for i in rows.len()..inner_height {
+                let mut cell = Cell::new(END_OF_BUFFER);
+                cell.color(GUTTER_COLOR);
+                buf.set_cell(inner_left, inner_top + i, cell);
             }
         }
 
-        let (abs_cursor_x, abs_cursor_y) = self.cursor_location(&layout);
-
-        let is_cursor_visible = abs_cursor_y > loc_y && abs_cursor_y < bottom;
+        let cursor_location = match &self.find {
+            Some(find) => Some(self.draw_find_bar(buf, find, &layout)),
+            None => self.cursor_location(&layout, &rows),
+        };
 
         self.draw_border(buf);
         self.draw_scrollers(buf);
 
-        if self.focused && is_cursor_visible {
-            vec![
-                Cmd::ShowCursor,
-                Cmd::MoveTo(abs_cursor_x as usize, abs_cursor_y as usize),
-            ]
-        } else {
-            vec![]
+        match cursor_location {
+            Some((x, y)) if self.focused => {
+                vec![Cmd::ShowCursor, Cmd::MoveTo(x, y)]
+            }
+            _ => vec![],
         }
     }
 
@@ -313,6 +1296,10 @@ where
         self.focused = focused;
     }
 
+    fn value(&self) -> Option<Value> {
+        Some(Value::String(self.get_value()))
+    }
+
     fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
         self.width = width;
         self.height = height;
@@ -322,7 +1309,9 @@ where
         let layout = self.layout.expect("must have a layout");
         match event {
             Event::Key(ke) => {
-                self.process_key(ke);
+                if !self.process_key(ke) {
+                    return vec![];
+                }
                 let s_event: Event = Event::from(InputEvent::from(
                     Value::from(self.get_content()),
                 ));
@@ -338,31 +1327,13 @@ where
                 let modifiers = event.modifiers();
 
                 if event.is_mouse_click() {
-                    let mut x = x as f32 - layout.location.x.round();
-                    let mut y = y as f32 - layout.location.y.round() - 1.0;
-
-                    if y < 0.0 {
-                        y = 0.0;
-                    }
-                    let rows = self.content_height();
-                    if y >= rows {
-                        y = rows - 1.0;
-                    }
-                    if x < 0.0 {
-                        x = 0.0;
-                    }
-                    let cursor_y = y + self.scroll_top;
-                    if let Some(line) =
-                        self.area_buffer.content.get(cursor_y as usize)
-                    {
-                        if x > line.len() as f32 {
-                            x = line.len() as f32;
-                        }
-                    }
-                    let cursor_x = x;
-
-                    self.area_buffer
-                        .set_cursor_loc(cursor_x as usize, cursor_y as usize);
+                    let (column, line) = self.content_location(x, y);
+                    self.area_buffer.set_cursor_column(column, line);
+                    vec![]
+                } else if event.is_mouse_drag() {
+                    let (column, line) = self.content_location(x, y);
+                    self.area_buffer.select_to_column(column, line);
+                    self.scroll_to_cursor();
                     vec![]
                 } else if event.is_scrollup() {
                     if modifiers.unwrap().contains(KeyModifiers::SHIFT) {
@@ -371,7 +1342,8 @@ where
                         }
                     } else {
                         if self.scroll_top > 0.0 {
-                            self.scroll_top -= 4.0;
+                            self.scroll_top = (self.scroll_top - 4.0).max(0.0);
+                            self.scroll_top_row = 0;
                         }
                     }
                     vec![]
@@ -380,9 +1352,10 @@ where
                         self.scroll_left += 4.0;
                     } else {
                         if self.content_height() - self.scroll_top
-                            > self.inner_height(&layout)
+                            > self.text_height(&layout)
                         {
                             self.scroll_top += 4.0;
+                            self.scroll_top_row = 0;
                         }
                     }
                     vec![]
@@ -394,6 +1367,19 @@ where
         }
     }
 
+    fn captures_key(&self, key_event: &KeyEvent) -> bool {
+        // copy and undo instead of quitting
+        let is_shortcut = key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(
+                key_event.code,
+                KeyCode::Char('c') | KeyCode::Char('z') | KeyCode::Char('Z')
+            );
+        // a new line or the next match instead of submitting
+        let is_enter = key_event.code == KeyCode::Enter
+            && (!self.read_only || self.find.is_some());
+        is_shortcut || is_enter
+    }
+
     fn set_id(&mut self, id: &str) {
         self.id = Some(id.to_string());
     }
@@ -402,3 +1388,114 @@ where
         &self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expanse::number::Number;
+
+    fn text_area(value: &str, width: f32, height: f32) -> TextArea<()> {
+        let mut text_area = TextArea::new(value);
+        text_area.set_size(Some(width), Some(height));
+        text_area.compute_node_layout(Size {
+            width: Number::Defined(width),
+            height: Number::Defined(height),
+        });
+        text_area
+    }
+
+    fn row_text(buf: &Buffer, y: usize, x: usize, width: usize) -> String {
+        buf.cells[y][x..x + width]
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect()
+    }
+
+    #[test]
+    fn soft_wrap_moves_by_rows() {
+        let mut text_area = text_area("abcdefghijkl\nxy", 10.0, 5.0);
+        text_area.set_soft_wrap(true);
+        text_area.area_buffer.set_cursor_loc(12, 0);
+        text_area.process_key(KeyCode::Up.into());
+        assert_eq!((4, 0), text_area.area_buffer.get_cursor_location());
+        text_area.process_key(KeyCode::Down.into());
+        assert_eq!((12, 0), text_area.area_buffer.get_cursor_location());
+        text_area.process_key(KeyCode::Down.into());
+        assert_eq!((2, 1), text_area.area_buffer.get_cursor_location());
+
+        let mut buf = Buffer::new(10, 5);
+        text_area.draw(&mut buf);
+        assert_eq!(row_text(&buf, 1, 1, 8), "abcdefgh");
+        assert_eq!(row_text(&buf, 2, 1, 4), "ijkl");
+        assert_eq!(row_text(&buf, 3, 1, 2), "xy");
+        let layout = text_area.layout.expect("must have a layout");
+        let rows = text_area.visible_rows(&layout);
+        assert_eq!(text_area.cursor_location(&layout, &rows), Some((3, 3)));
+    }
+
+    #[test]
+    fn gutter_and_end_of_buffer() {
+        let mut text_area = text_area("one\ntwo\nthree", 12.0, 6.0);
+        text_area.set_line_numbers(true);
+        text_area.set_relative_line_numbers(true);
+        text_area.set_gutter_width(3);
+        text_area.set_show_end_of_buffer(true);
+        text_area.area_buffer.set_cursor_loc(0, 1);
+        let mut buf = Buffer::new(12, 6);
+        text_area.draw(&mut buf);
+        assert_eq!(row_text(&buf, 1, 1, 6), " 1 one");
+        assert_eq!(row_text(&buf, 2, 1, 6), " 2 two");
+        assert_eq!(row_text(&buf, 3, 1, 8), " 1 three");
+        assert_eq!(row_text(&buf, 4, 1, 1), "~");
+    }
+
+    #[test]
+    fn find_and_replace_all() {
+        let mut text_area = text_area("cat dog\nCat cat", 30.0, 6.0);
+        text_area.area_buffer.set_cursor_loc(2, 0);
+        text_area.process_key(KeyEvent::new(
+            KeyCode::Char('f'),
+            KeyModifiers::CONTROL,
+        ));
+        for ch in "cat".chars() {
+            text_area.process_key(KeyCode::Char(ch).into());
+        }
+        assert_eq!(text_area.find_status(), Some((3, Some(1))));
+        assert_eq!(text_area.selected_text(), Some("Cat".to_string()));
+        text_area.process_key(KeyCode::Enter.into());
+        assert_eq!(text_area.find_status(), Some((3, Some(2))));
+
+        let mut buf = Buffer::new(30, 6);
+        text_area.draw(&mut buf);
+        assert_eq!(row_text(&buf, 4, 1, 9), "Find: cat");
+        assert_eq!(row_text(&buf, 4, 16, 13), "3/3 [Aa] [.*]");
+
+        text_area.set_find_case_sensitive(true);
+        text_area.set_replacement("cow");
+        assert_eq!(text_area.replace_all(), 2);
+        assert_eq!(text_area.get_value(), "cow dog\nCat cow");
+        assert_eq!(text_area.find_status(), Some((0, None)));
+        text_area.close_find();
+        text_area.undo();
+        assert_eq!(text_area.get_value(), "cat dog\nCat cat");
+    }
+
+    #[test]
+    fn matches_are_refreshed_when_the_content_changes() {
+        let mut text_area = text_area("foo bar foo", 30.0, 6.0);
+        text_area.open_find(false);
+        text_area.set_find_query("foo");
+        assert_eq!(text_area.find_status(), Some((2, Some(0))));
+
+        // the old byte ranges are out of bounds of the new content
+        text_area.set_value("hé");
+        assert_eq!(text_area.find_status(), Some((0, None)));
+        text_area.draw(&mut Buffer::new(30, 6));
+
+        text_area.add_line("a foo");
+        assert_eq!(text_area.find_status(), Some((1, Some(0))));
+        text_area.find_next();
+        assert_eq!(text_area.selected_text(), Some("foo".to_string()));
+        text_area.draw(&mut Buffer::new(30, 6));
+    }
+}
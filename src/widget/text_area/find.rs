@@ -0,0 +1,261 @@
+//! The find bar of the `TextArea`, which searches the lines for the
+//! plain text or the regular expression, and replaces the matches.
+use crate::text_buffer::{AreaBuffer, InputBuffer};
+use regex::{Regex, RegexBuilder};
+
+/// A match of the search, the start and end are the byte indexes in the line.
+/// The matches do not span multiple lines.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) struct Match {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// the field of the find bar which receives the keys
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum FindField {
+    Query,
+    Replacement,
+}
+
+#[derive(Debug)]
+pub(super) struct FindBar {
+    pub query: InputBuffer,
+    pub replacement: InputBuffer,
+    pub focus: FindField,
+    pub show_replace: bool,
+    pub case_sensitive: bool,
+    pub use_regex: bool,
+    /// the query is not a valid regular expression
+    pub is_invalid: bool,
+    regex: Option<Regex>,
+    matches: Vec<Match>,
+    current: Option<usize>,
+    /// the line and byte index where the search starts,
+    /// the first match after it is the current match
+    origin: (usize, usize),
+}
+
+impl FindBar {
+    pub fn new(show_replace: bool, origin: (usize, usize)) -> Self {
+        FindBar {
+            query: InputBuffer::new(),
+            replacement: InputBuffer::new(),
+            focus: if show_replace {
+                FindField::Replacement
+            } else {
+                FindField::Query
+            },
+            show_replace,
+            case_sensitive: false,
+            use_regex: false,
+            is_invalid: false,
+            regex: None,
+            matches: vec![],
+            current: None,
+            origin,
+        }
+    }
+
+    /// the number of rows used to display the find bar
+    pub fn height(&self) -> usize {
+        if self.show_replace {
+            2
+        } else {
+            1
+        }
+    }
+
+    fn compile(&mut self) {
+        let query = self.query.get_content();
+        self.is_invalid = false;
+        self.regex = if query.is_empty() {
+            None
+        } else {
+            let pattern = if self.use_regex {
+                query.to_string()
+            } else {
+                regex::escape(query)
+            };
+            let regex = RegexBuilder::new(&pattern)
+                .case_insensitive(!self.case_sensitive)
+                .build();
+            self.is_invalid = regex.is_err();
+            regex.ok()
+        };
+    }
+
+    /// find all the matches in the lines of the buffer,
+    /// the current match is the first match after the origin
+    pub fn search(&mut self, area_buffer: &AreaBuffer) {
+        self.compile();
+        self.matches.clear();
+        if let Some(regex) = &self.regex {
+            for y in 0..area_buffer.height() {
+                let line = area_buffer.line(y);
+                // the empty matches can not be selected nor replaced
+                self.matches.extend(
+                    regex
+                        .find_iter(&line)
+                        .filter(|found| found.start() < found.end())
+                        .map(|found| Match {
+                            line: y,
+                            start: found.start(),
+                            end: found.end(),
+                        }),
+                );
+            }
+        }
+        self.current = if self.matches.is_empty() {
+            None
+        } else {
+            let after_origin = self
+                .matches
+                .iter()
+                .position(|m| (m.line, m.start) >= self.origin);
+            Some(after_origin.unwrap_or(0))
+        };
+    }
+
+    /// set the location where the next search starts
+    pub fn set_origin(&mut self, origin: (usize, usize)) {
+        self.origin = origin;
+    }
+
+    pub fn matches(&self) -> &[Match] {
+        &self.matches
+    }
+
+    /// the index of the current match
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn current_match(&self) -> Option<Match> {
+        self.current.map(|i| self.matches[i])
+    }
+
+    /// the matches in this line, with their index in all the matches
+    pub fn line_matches(
+        &self,
+        line: usize,
+    ) -> impl Iterator<Item = (usize, &Match)> {
+        let first = self.matches.partition_point(|m| m.line < line);
+        self.matches[first..]
+            .iter()
+            .take_while(move |m| m.line == line)
+            .enumerate()
+            .map(move |(i, m)| (first + i, m))
+    }
+
+    /// move to the next match, going back to the first match after the last
+    pub fn next(&mut self) {
+        if let Some(current) = self.current {
+            let next = (current + 1) % self.matches.len();
+            self.current = Some(next);
+            self.origin = (self.matches[next].line, self.matches[next].start);
+        }
+    }
+
+    /// move to the previous match, going to the last match before the first
+    pub fn previous(&mut self) {
+        if let Some(current) = self.current {
+            let len = self.matches.len();
+            let previous = (current + len - 1) % len;
+            self.current = Some(previous);
+            self.origin =
+                (self.matches[previous].line, self.matches[previous].start);
+        }
+    }
+
+    /// the text which replaces the match, the `$1` and `${name}` in the
+    /// replacement are expanded to the captured groups when using regex
+    pub fn replacement_for(
+        &self,
+        area_buffer: &AreaBuffer,
+        m: Match,
+    ) -> String {
+        let replacement = self.replacement.get_content();
+        match &self.regex {
+            Some(regex) if self.use_regex => {
+                let line = area_buffer.line(m.line);
+                match regex.captures_at(&line, m.start) {
+                    Some(captures) => {
+                        let mut expanded = String::new();
+                        captures.expand(replacement, &mut expanded);
+                        expanded
+                    }
+                    None => replacement.to_string(),
+                }
+            }
+            _ => replacement.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crossterm::event::{KeyCode, KeyEvent};
+
+    fn find(area_buffer: &AreaBuffer, query: &str) -> FindBar {
+        let mut find_bar = FindBar::new(false, (0, 0));
+        for ch in query.chars() {
+            find_bar
+                .query
+                .process_key_event(KeyEvent::from(KeyCode::Char(ch)));
+        }
+        find_bar.search(area_buffer);
+        find_bar
+    }
+
+    #[test]
+    fn case_and_regex() {
+        let area_buffer = AreaBuffer::from("Foo foo\nbar fOO f.o".to_string());
+        let mut find_bar = find(&area_buffer, "foo");
+        assert_eq!(find_bar.matches().len(), 3);
+        find_bar.case_sensitive = true;
+        find_bar.search(&area_buffer);
+        assert_eq!(
+            find_bar.matches(),
+            &[Match {
+                line: 0,
+                start: 4,
+                end: 7
+            }]
+        );
+        let mut find_bar = find(&area_buffer, "f.o");
+        assert_eq!(find_bar.matches().len(), 1);
+        find_bar.use_regex = true;
+        find_bar.search(&area_buffer);
+        assert_eq!(find_bar.matches().len(), 4);
+        assert_eq!(find_bar.line_matches(1).count(), 2);
+    }
+
+    #[test]
+    fn next_and_previous_wrap_around() {
+        let area_buffer = AreaBuffer::from("a a\na".to_string());
+        let mut find_bar = FindBar::new(false, (0, 1));
+        find_bar.query.set_content("a");
+        find_bar.search(&area_buffer);
+        assert_eq!(find_bar.current(), Some(1));
+        find_bar.next();
+        find_bar.next();
+        assert_eq!(find_bar.current(), Some(0));
+        find_bar.previous();
+        assert_eq!(find_bar.current(), Some(2));
+    }
+
+    #[test]
+    fn regex_replacement_expands_groups() {
+        let area_buffer = AreaBuffer::from("key = value".to_string());
+        let mut find_bar = FindBar::new(true, (0, 0));
+        find_bar.use_regex = true;
+        find_bar.query.set_content(r"(\w+) = (\w+)");
+        find_bar.replacement.set_content("$2 = $1");
+        find_bar.search(&area_buffer);
+        let m = find_bar.current_match().expect("must have a match");
+        assert_eq!(find_bar.replacement_for(&area_buffer, m), "value = key");
+    }
+}
@@ -1,20 +1,59 @@
-use crate::crossterm::event::KeyEvent;
+use crate::crossterm::{
+    event::{KeyCode, KeyEvent, KeyModifiers},
+    style::{Attribute, Color},
+};
 use crate::Callback;
 use crate::Event;
-use crate::{buffer::Buffer, text_buffer::InputBuffer, Cmd, Widget};
+use crate::Value;
+use crate::{
+    buffer::Buffer, text_buffer::InputBuffer, unicode, Cmd, Span, Widget,
+};
 use expanse::{
     geometry::Size,
     result::Layout,
     style::{Dimension, PositionType, Style},
 };
+pub use history::HistoryStore;
+use history::InputHistory;
 use ito_canvas::unicode_canvas::{Border, Canvas};
+use regex::Regex;
 use std::fmt;
 
+mod history;
+
+/// the character displayed in place of each character of the password
+const PASSWORD_MASK: &str = "*";
+/// the color of the placeholder text
+const PLACEHOLDER_COLOR: Color = Color::DarkGrey;
+/// the color of the border and the message when the value is invalid
+const ERROR_COLOR: Color = Color::Red;
+/// the maximum number of suggestions displayed below the input
+const MAX_SUGGESTIONS: usize = 5;
+/// the background color of the suggestions popup
+const SUGGESTION_BACKGROUND: Color = Color::AnsiValue(238);
+
 /// A one line text input
 #[derive(Default, Debug)]
 pub struct TextInput<MSG> {
     layout: Option<Layout>,
     input_buffer: InputBuffer,
+    /// the text displayed when the value is empty
+    placeholder: Option<String>,
+    /// the characters of the value are masked
+    is_password: bool,
+    /// the value has to match the regex, else the message is the error
+    pattern: Option<(Regex, String)>,
+    validator: Option<Callback<String, Result<(), String>>>,
+    /// the error message when the value is invalid
+    error: Option<String>,
+    /// the number of columns of the value scrolled to the left
+    scroll_left: usize,
+    /// returns the suggestions for the value
+    completer: Option<Callback<String, Vec<String>>>,
+    /// the suggestions displayed in a popup below the input
+    suggestions: Vec<String>,
+    selected_suggestion: usize,
+    history: Option<InputHistory>,
     is_rounded: bool,
     has_border: bool,
     focused: bool,
@@ -33,6 +72,16 @@ impl<MSG> TextInput<MSG> {
         TextInput {
             layout: None,
             input_buffer: InputBuffer::new_with_value(value),
+            placeholder: None,
+            is_password: false,
+            pattern: None,
+            validator: None,
+            error: None,
+            scroll_left: 0,
+            completer: None,
+            suggestions: vec![],
+            selected_suggestion: 0,
+            history: None,
             is_rounded: false,
             has_border: true,
             focused: false,
@@ -45,7 +94,17 @@ impl<MSG> TextInput<MSG> {
 
     /// process the key event for this text input
     pub fn process_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
-        self.input_buffer.process_key_event(key_event);
+        if !self.process_suggestion_key(key_event)
+            && !self.process_history_key(key_event)
+        {
+            let value = self.get_value().to_string();
+            self.input_buffer.process_key_event(key_event);
+            if self.get_value() != value {
+                self.validate();
+                self.complete();
+            }
+        }
+        self.scroll_to_cursor();
         self.on_input
             .iter_mut()
             .map(|cb| cb.emit(key_event.into()))
@@ -54,7 +113,266 @@ impl<MSG> TextInput<MSG> {
 
     /// set the value of the buffer
     pub fn set_value<S: ToString>(&mut self, value: S) {
-        self.input_buffer = InputBuffer::new_with_value(value);
+        self.input_buffer.set_content(value);
+        self.validate();
+        self.scroll_to_cursor();
+    }
+
+    /// navigate and accept the suggestions when they are displayed,
+    /// return true if the key is used
+    fn process_suggestion_key(&mut self, key_event: KeyEvent) -> bool {
+        if self.suggestions.is_empty() {
+            return false;
+        }
+        let count = self.suggestions.len().min(MAX_SUGGESTIONS);
+        match key_event.code {
+            KeyCode::Down => {
+                self.selected_suggestion =
+                    (self.selected_suggestion + 1) % count;
+            }
+            KeyCode::Up => {
+                self.selected_suggestion =
+                    (self.selected_suggestion + count - 1) % count;
+            }
+            KeyCode::Tab | KeyCode::Enter => {
+                self.accept_suggestion(self.selected_suggestion);
+            }
+            KeyCode::Esc => {
+                self.suggestions.clear();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// recall the history with Up and Down, Enter adds the value to the history,
+    /// return true if the key is used
+    fn process_history_key(&mut self, key_event: KeyEvent) -> bool {
+        let history = match &mut self.history {
+            Some(history) => history,
+            None => return false,
+        };
+        let recalled = match key_event.code {
+            KeyCode::Up => history
+                .previous(self.input_buffer.get_content())
+                .map(ToString::to_string),
+            KeyCode::Down => history.next(),
+            KeyCode::Enter => {
+                history.push(self.input_buffer.get_content());
+                // the Enter is still processed by the input buffer
+                return false;
+            }
+            _ => return false,
+        };
+        if let Some(recalled) = recalled {
+            self.input_buffer.replace_content(&recalled);
+            self.validate();
+        }
+        true
+    }
+
+    /// replace the value with the suggestion and close the popup
+    fn accept_suggestion(&mut self, index: usize) {
+        if let Some(suggestion) = self.suggestions.get(index).cloned() {
+            self.input_buffer.replace_content(&suggestion);
+            self.validate();
+            self.suggestions.clear();
+        }
+    }
+
+    /// the number of rows of the suggestions popup, these rows are reserved
+    /// below the input so the popup is not covered by the next widget
+    fn suggestion_rows(&self) -> usize {
+        self.suggestions.len().min(MAX_SUGGESTIONS)
+    }
+
+    /// the height of the input without the suggestions popup
+    fn input_height(&self) -> f32 {
+        self.height.unwrap_or(3.0)
+    }
+
+    /// the layout of the input, excluding the rows of the suggestions popup
+    fn input_layout(&self, layout: &Layout) -> Layout {
+        let mut layout = *layout;
+        layout.size.height =
+            (layout.size.height - self.suggestion_rows() as f32).max(1.0);
+        layout
+    }
+
+    /// set the function which returns the suggestions for the value,
+    /// the suggestions are displayed below the input as the value is typed
+    /// and accepted with Tab or Enter
+    pub fn set_completer<F>(&mut self, f: F)
+    where
+        F: FnMut(String) -> Vec<String> + 'static,
+    {
+        self.completer = Some(f.into());
+    }
+
+    /// the suggestions for the current value
+    pub fn suggestions(&self) -> &[String] {
+        &self.suggestions
+    }
+
+    fn complete(&mut self) {
+        let value = self.input_buffer.get_content().to_string();
+        self.suggestions = match &mut self.completer {
+            Some(completer) => completer.emit(value),
+            None => vec![],
+        };
+        self.selected_suggestion = 0;
+    }
+
+    /// keep the entered values, which are recalled with Up and Down.
+    /// A value is added to the history when Enter is pressed.
+    pub fn set_input_history(&mut self, enabled: bool) {
+        self.history = if enabled {
+            Some(InputHistory::new(None))
+        } else {
+            None
+        };
+    }
+
+    /// keep the entered values and load and save them with this store
+    pub fn set_input_history_store<S: HistoryStore + 'static>(
+        &mut self,
+        store: S,
+    ) {
+        self.history = Some(InputHistory::new(Some(Box::new(store))));
+    }
+
+    /// the entered values, the oldest is first
+    pub fn input_history(&self) -> &[String] {
+        self.history
+            .as_ref()
+            .map(|history| history.entries())
+            .unwrap_or(&[])
+    }
+
+    /// set the text which is displayed when the value is empty
+    pub fn set_placeholder<S: ToString>(&mut self, placeholder: S) {
+        self.placeholder = Some(placeholder.to_string());
+    }
+
+    /// set whether the characters are masked, such as when entering a password.
+    /// `get_value` still returns the text as typed.
+    pub fn set_password(&mut self, is_password: bool) {
+        self.is_password = is_password;
+        self.scroll_to_cursor();
+    }
+
+    /// set the maximum number of characters of the value,
+    /// the value is truncated when it is longer
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.input_buffer.set_max_length(max_length);
+        self.validate();
+        self.scroll_to_cursor();
+    }
+
+    /// set the regular expression which the whole value has to match,
+    /// the message is displayed when it does not match
+    pub fn set_pattern<S: ToString>(
+        &mut self,
+        pattern: &str,
+        message: S,
+    ) -> Result<(), regex::Error> {
+        let regex = Regex::new(&format!("^(?:{})$", pattern))?;
+        self.pattern = Some((regex, message.to_string()));
+        self.validate();
+        Ok(())
+    }
+
+    /// set the function which validates the value everytime it changes,
+    /// the returned error message is displayed when it is invalid
+    pub fn set_validator<F>(&mut self, f: F)
+    where
+        F: FnMut(String) -> Result<(), String> + 'static,
+    {
+        self.validator = Some(f.into());
+        self.validate();
+    }
+
+    /// return true if the value passes the pattern and the validator
+    pub fn is_valid(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// the error message when the value is invalid
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    fn validate(&mut self) {
+        let value = self.input_buffer.get_content();
+        self.error = match &self.pattern {
+            Some((regex, message)) if !regex.is_match(value) => {
+                Some(message.to_string())
+            }
+            _ => None,
+        };
+        if self.error.is_none() {
+            if let Some(validator) = &mut self.validator {
+                self.error = validator.emit(value.to_string()).err();
+            }
+        }
+    }
+
+    /// the text which is displayed, the characters are masked in password mode
+    fn display_text(&self) -> String {
+        if self.is_password {
+            PASSWORD_MASK.repeat(unicode::grapheme_count(self.get_value()))
+        } else {
+            self.get_value().to_string()
+        }
+    }
+
+    /// the column of the cursor in the displayed text
+    fn cursor_column(&self) -> usize {
+        if self.is_password {
+            self.input_buffer.get_cursor_location()
+        } else {
+            self.input_buffer.get_cursor_column()
+        }
+    }
+
+    /// scroll the value horizontally so the cursor is visible
+    fn scroll_to_cursor(&mut self) {
+        let inner_width = match self.layout {
+            Some(layout) => self.inner_width(&layout),
+            None => return,
+        };
+        let column = self.cursor_column();
+        // the cursor needs a cell after the last character
+        let text_width = unicode::str_width(&self.display_text()) + 1;
+        if column < self.scroll_left {
+            self.scroll_left = column;
+        } else if column >= self.scroll_left + inner_width {
+            self.scroll_left = (column + 1).saturating_sub(inner_width);
+        }
+        // show as much of the text as possible when it gets shorter
+        self.scroll_left =
+            self.scroll_left.min(text_width.saturating_sub(inner_width));
+    }
+
+    /// undo the last edit, return true if there is an edit undone
+    pub fn undo(&mut self) -> bool {
+        let undone = self.input_buffer.undo();
+        self.validate();
+        self.scroll_to_cursor();
+        undone
+    }
+
+    /// redo the last undone edit, return true if there is an edit redone
+    pub fn redo(&mut self) -> bool {
+        let redone = self.input_buffer.redo();
+        self.validate();
+        self.scroll_to_cursor();
+        redone
+    }
+
+    /// set the maximum number of edits which can be undone, the default is 100
+    pub fn set_history_depth(&mut self, depth: usize) {
+        self.input_buffer.set_history_depth(depth);
     }
 
     /// returns a reference to the text value of this text input widget
@@ -122,6 +440,54 @@ impl<MSG> TextInput<MSG> {
         }
     }
 
+    /// draw the suggestions in the rows below the input,
+    /// the layout is the layout of the input without the reserved rows
+    fn draw_suggestions(&self, buf: &mut Buffer, layout: &Layout) {
+        let left = layout.location.x.round() as usize;
+        let top = (layout.location.y + layout.size.height).round() as usize;
+        let width = layout.size.width.round() as usize;
+        for (i, suggestion) in
+            self.suggestions.iter().take(MAX_SUGGESTIONS).enumerate()
+        {
+            let text = unicode::take_width(&format!(" {}", suggestion), width);
+            let padding = width - unicode::str_width(&text);
+            let mut span =
+                Span::new(format!("{}{}", text, " ".repeat(padding)));
+            span.background(SUGGESTION_BACKGROUND);
+            if i == self.selected_suggestion {
+                span.attributes(vec![Attribute::Reverse]);
+            }
+            buf.write_styled(left, top + i, &span.into());
+        }
+    }
+
+    /// color the border and write the error message at the bottom border
+    fn draw_error(&self, buf: &mut Buffer, layout: &Layout, error: &str) {
+        let left = layout.location.x.round() as usize;
+        let top = layout.location.y.round() as usize;
+        let right = left + (layout.size.width.round() as usize).max(1) - 1;
+        let bottom = top + (layout.size.height.round() as usize).max(1) - 1;
+        for y in top..=bottom {
+            for x in left..=right {
+                let is_border =
+                    x == left || x == right || y == top || y == bottom;
+                let cell =
+                    buf.cells.get_mut(y).and_then(|line| line.get_mut(x));
+                if let (true, Some(cell)) = (is_border, cell) {
+                    cell.color(ERROR_COLOR);
+                }
+            }
+        }
+        let mut span = Span::new(error);
+        span.color(ERROR_COLOR);
+        buf.write_styled_truncated(
+            left + 1,
+            bottom,
+            &span.into(),
+            self.inner_width(layout),
+        );
+    }
+
     pub fn on_input<F>(&mut self, f: F)
     where
         F: FnMut(Event) -> MSG + 'static,
@@ -139,6 +505,7 @@ where
     }
     fn set_layout(&mut self, layout: Layout) {
         self.layout = Some(layout);
+        self.scroll_to_cursor();
     }
     fn style(&self) -> Style {
         Style {
@@ -149,11 +516,9 @@ where
                 } else {
                     Dimension::Percent(1.0)
                 },
-                height: if let Some(height) = self.height {
-                    Dimension::Points(height)
-                } else {
-                    Dimension::Points(3.0)
-                },
+                height: Dimension::Points(
+                    self.input_height() + self.suggestion_rows() as f32,
+                ),
             },
             min_size: Size {
                 width: if let Some(width) = self.width {
@@ -161,11 +526,9 @@ where
                 } else {
                     Dimension::Percent(1.0)
                 },
-                height: if let Some(height) = self.height {
-                    Dimension::Points(height)
-                } else {
-                    Dimension::Points(3.0)
-                },
+                height: Dimension::Points(
+                    self.input_height() + self.suggestion_rows() as f32,
+                ),
             },
             ..Default::default()
         }
@@ -173,7 +536,8 @@ where
 
     /// draw this button to the buffer, with the given computed layout
     fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
-        let layout = self.layout.expect("must have a layout");
+        let layout =
+            self.input_layout(&self.layout.expect("must have a layout"));
         let loc_x = layout.location.x;
         let loc_y = layout.location.y;
         let width = layout.size.width;
@@ -203,26 +567,48 @@ where
                 border,
             );
             buf.write_canvas(canvas);
+            if let Some(error) = &self.error {
+                self.draw_error(buf, &layout, error);
+            }
         }
 
-        let _inner_width = self.inner_width(&layout);
-        for (t, ch) in self.get_value().chars().enumerate() {
-            buf.set_symbol(
-                (left + self.border_left() + t as f32) as usize,
-                (top + self.border_top()) as usize,
-                ch,
+        let inner_width = self.inner_width(&layout);
+        let text_left = (left + self.border_left()) as usize;
+        let text_top = (top + self.border_top()) as usize;
+        if self.get_value().is_empty() {
+            if let Some(placeholder) = &self.placeholder {
+                let mut span = Span::new(placeholder);
+                span.color(PLACEHOLDER_COLOR);
+                buf.write_styled_truncated(
+                    text_left,
+                    text_top,
+                    &span.into(),
+                    inner_width,
+                );
+            }
+        } else {
+            let text = self.display_text();
+            // the first grapheme which is fully past the scrolled columns
+            let mut start = unicode::index_at_column(&text, self.scroll_left);
+            if unicode::column_of(&text, start) < self.scroll_left {
+                start += 1;
+            }
+            let offset = unicode::column_of(&text, start) - self.scroll_left;
+            let visible = unicode::take_width(
+                &text[unicode::byte_index(&text, start)..],
+                inner_width.saturating_sub(offset),
             );
+            buf.write_str(text_left + offset, text_top, visible);
         }
 
-        let cursor_loc_x = self.input_buffer.get_cursor_location() as f32;
         if self.focused {
-            vec![
-                Cmd::ShowCursor,
-                Cmd::MoveTo(
-                    (left + cursor_loc_x + self.border_left()) as usize,
-                    (top + self.border_top()) as usize,
-                ),
-            ]
+            self.draw_suggestions(buf, &layout);
+        }
+
+        if self.focused {
+            let cursor_x =
+                self.cursor_column().saturating_sub(self.scroll_left);
+            vec![Cmd::ShowCursor, Cmd::MoveTo(text_left + cursor_x, text_top)]
         } else {
             vec![]
         }
@@ -230,6 +616,13 @@ where
 
     fn set_focused(&mut self, focused: bool) {
         self.focused = focused;
+        if !focused {
+            self.suggestions.clear();
+        }
+    }
+
+    fn value(&self) -> Option<Value> {
+        Some(Value::String(self.get_value().to_string()))
     }
 
     fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
@@ -245,16 +638,46 @@ where
                 vec![]
             }
             Event::Mouse(_me) => {
-                let (x, _y) =
+                let (x, y) =
                     event.extract_location().expect("must have a location");
-                let cursor_loc = x as i32 - layout.location.x.round() as i32;
-                self.input_buffer.set_cursor_loc(cursor_loc as usize);
+                let input_layout = self.input_layout(&layout);
+                let popup_top =
+                    (input_layout.location.y + input_layout.size.height) as u16;
+                if y >= popup_top {
+                    if event.is_mouse_click() {
+                        self.accept_suggestion((y - popup_top) as usize);
+                    }
+                    return vec![];
+                }
+                let column = x as i32
+                    - layout.location.x.round() as i32
+                    - self.border_left() as i32;
+                let column = column.max(0) as usize + self.scroll_left;
+                if self.is_password {
+                    let count = unicode::grapheme_count(self.get_value());
+                    self.input_buffer.set_cursor_loc(column.min(count));
+                } else {
+                    self.input_buffer.set_cursor_column(column);
+                }
+                self.scroll_to_cursor();
                 vec![]
             }
             _ => vec![],
         }
     }
 
+    fn captures_key(&self, key_event: &KeyEvent) -> bool {
+        // undo instead of quitting, accept the suggestion instead of submitting
+        let is_undo = key_event.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(
+                key_event.code,
+                KeyCode::Char('z') | KeyCode::Char('Z')
+            );
+        let is_accept =
+            key_event.code == KeyCode::Enter && !self.suggestions.is_empty();
+        is_undo || is_accept
+    }
+
     fn set_id(&mut self, id: &str) {
         self.id = Some(id.to_string());
     }
@@ -263,3 +686,118 @@ where
         &self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expanse::number::Number;
+
+    fn text_input(value: &str, width: f32) -> TextInput<()> {
+        let mut text_input = TextInput::new(value);
+        text_input.set_size(Some(width), Some(3.0));
+        text_input.compute_node_layout(Size {
+            width: Number::Defined(width),
+            height: Number::Defined(3.0),
+        });
+        text_input
+    }
+
+    fn row_text(buf: &Buffer, y: usize, x: usize, width: usize) -> String {
+        buf.cells[y][x..x + width]
+            .iter()
+            .map(|cell| cell.symbol.clone())
+            .collect()
+    }
+
+    #[test]
+    fn password_and_scrolling() {
+        let mut text_input = text_input("secret", 6.0);
+        text_input.set_password(true);
+        assert_eq!(text_input.get_value(), "secret");
+        text_input.set_focused(true);
+        let mut buf = Buffer::new(6, 3);
+        text_input.draw(&mut buf);
+        // the cursor after the last character is kept visible
+        assert_eq!(row_text(&buf, 1, 1, 4), "*** ");
+        assert_eq!(text_input.scroll_left, 3);
+
+        text_input.set_password(false);
+        text_input.process_key(KeyCode::Home.into());
+        let mut buf = Buffer::new(6, 3);
+        text_input.draw(&mut buf);
+        assert_eq!(row_text(&buf, 1, 1, 4), "secr");
+    }
+
+    #[test]
+    fn placeholder_and_validation() {
+        let mut text_input = text_input("", 12.0);
+        text_input.set_placeholder("Age");
+        text_input.set_pattern("[0-9]+", "not a number").unwrap();
+        assert_eq!(text_input.error(), Some("not a number"));
+        let mut buf = Buffer::new(12, 3);
+        text_input.draw(&mut buf);
+        assert_eq!(row_text(&buf, 1, 1, 3), "Age");
+        assert_eq!(row_text(&buf, 2, 1, 10), "not a num…");
+        assert_eq!(buf.cells[0][0].foreground_color, Some(ERROR_COLOR));
+
+        text_input.set_validator(|value| {
+            if value.parse::<u32>().map_or(false, |age| age < 150) {
+                Ok(())
+            } else {
+                Err("too old".to_string())
+            }
+        });
+        text_input.process_key(KeyCode::Char('9').into());
+        assert!(text_input.is_valid());
+        text_input.set_value("200");
+        assert_eq!(text_input.error(), Some("too old"));
+
+        text_input.set_max_length(Some(2));
+        assert_eq!(text_input.get_value(), "20");
+        assert!(text_input.is_valid());
+    }
+
+    #[test]
+    fn completion_and_history() {
+        let mut text_input = text_input("", 12.0);
+        text_input.set_completer(|value| {
+            ["open", "options", "quit"]
+                .iter()
+                .filter(|command| {
+                    !value.is_empty() && command.starts_with(&value)
+                })
+                .map(ToString::to_string)
+                .collect()
+        });
+        text_input.set_input_history(true);
+        text_input.set_focused(true);
+        text_input.process_key(KeyCode::Char('o').into());
+        assert_eq!(text_input.suggestions(), ["open", "options"]);
+        // the rows of the popup are reserved below the input
+        assert_eq!(text_input.style().size.height, Dimension::Points(5.0));
+        text_input.compute_node_layout(Size {
+            width: Number::Defined(12.0),
+            height: Number::Defined(6.0),
+        });
+        let mut buf = Buffer::new(12, 6);
+        text_input.draw(&mut buf);
+        assert_eq!(row_text(&buf, 2, 0, 1), "┗");
+        assert_eq!(row_text(&buf, 4, 0, 8), " options");
+
+        text_input.process_key(KeyCode::Down.into());
+        text_input.process_key(KeyCode::Tab.into());
+        assert_eq!(text_input.get_value(), "options");
+        assert!(text_input.suggestions().is_empty());
+        assert_eq!(text_input.style().size.height, Dimension::Points(3.0));
+        text_input.process_key(KeyCode::Enter.into());
+        assert_eq!(text_input.input_history(), ["options"]);
+
+        text_input.set_value("q");
+        text_input.process_key(KeyCode::Up.into());
+        assert_eq!(text_input.get_value(), "options");
+        text_input.process_key(KeyCode::Down.into());
+        assert_eq!(text_input.get_value(), "q");
+        text_input.undo();
+        assert_eq!(text_input.get_value(), "options");
+    }
+}
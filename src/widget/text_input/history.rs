@@ -0,0 +1,132 @@
+//! The history of the entered values of a `TextInput`,
+//! which is navigated with Up and Down like in a shell.
+use std::fmt;
+
+/// A store which keeps the history of a `TextInput`, such as a file,
+/// so the entries are still available the next time the application runs.
+pub trait HistoryStore {
+    /// load the entries, the oldest entry is first
+    fn load(&mut self) -> Vec<String>;
+
+    /// save the entries, this is called everytime an entry is added
+    fn save(&mut self, entries: &[String]);
+}
+
+pub(super) struct InputHistory {
+    entries: Vec<String>,
+    /// the index of the entry being displayed while navigating
+    index: Option<usize>,
+    /// the text typed before navigating the history, restored after the newest entry
+    draft: String,
+    store: Option<Box<dyn HistoryStore>>,
+}
+
+impl InputHistory {
+    /// create a history, the entries are loaded from the store
+    pub fn new(mut store: Option<Box<dyn HistoryStore>>) -> Self {
+        InputHistory {
+            entries: store
+                .as_mut()
+                .map(|store| store.load())
+                .unwrap_or_default(),
+            index: None,
+            draft: String::new(),
+            store,
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// add the entry and save the history into the store,
+    /// empty entries and repeats of the last entry are not added
+    pub fn push(&mut self, entry: &str) {
+        self.index = None;
+        if entry.is_empty()
+            || self.entries.last().map(String::as_str) == Some(entry)
+        {
+            return;
+        }
+        self.entries.push(entry.to_string());
+        if let Some(store) = &mut self.store {
+            store.save(&self.entries);
+        }
+    }
+
+    /// return the entry before the one being displayed,
+    /// the current text is kept when starting to navigate
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let index = match self.index {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(index) if index > 0 => index - 1,
+            Some(_) => return None,
+        };
+        self.index = Some(index);
+        Some(&self.entries[index])
+    }
+
+    /// return the entry after the one being displayed,
+    /// the text typed before navigating is returned after the newest entry
+    pub fn next(&mut self) -> Option<String> {
+        let index = self.index?;
+        if index + 1 < self.entries.len() {
+            self.index = Some(index + 1);
+            Some(self.entries[index + 1].clone())
+        } else {
+            self.index = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+}
+
+impl fmt::Debug for InputHistory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("InputHistory")
+            .field("entries", &self.entries)
+            .field("index", &self.index)
+            .field("draft", &self.draft)
+            .field("has_store", &self.store.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{cell::RefCell, rc::Rc};
+
+    struct MemoryStore(Rc<RefCell<Vec<String>>>);
+
+    impl HistoryStore for MemoryStore {
+        fn load(&mut self) -> Vec<String> {
+            self.0.borrow().clone()
+        }
+
+        fn save(&mut self, entries: &[String]) {
+            *self.0.borrow_mut() = entries.to_vec();
+        }
+    }
+
+    #[test]
+    fn navigate_and_save() {
+        let saved = Rc::new(RefCell::new(vec!["ls".to_string()]));
+        let mut history =
+            InputHistory::new(Some(Box::new(MemoryStore(Rc::clone(&saved)))));
+        history.push("cd");
+        history.push("cd");
+        history.push("");
+        assert_eq!(*saved.borrow(), vec!["ls", "cd"]);
+
+        assert_eq!(history.previous("git"), Some("cd"));
+        assert_eq!(history.previous("cd"), Some("ls"));
+        assert_eq!(history.previous("ls"), None);
+        assert_eq!(history.next(), Some("cd".to_string()));
+        assert_eq!(history.next(), Some("git".to_string()));
+        assert_eq!(history.next(), None);
+    }
+}
@@ -1,5 +1,5 @@
 use crate::Event;
-use crate::{buffer::Buffer, Cmd, Widget};
+use crate::{buffer::Buffer, unicode, Cmd, StyledText, Widget};
 use expanse::{
     geometry::Size,
     result::Layout,
@@ -7,16 +7,58 @@ use expanse::{
 };
 use ito_canvas::unicode_canvas::{Border, Canvas};
 
-/// A one line text input
+/// How the text is broken into lines when it is wider than the label
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Wrap {
+    /// the text is not wrapped, only the new lines starts a new line
+    #[default]
+    None,
+    /// the lines are broken in between words
+    Word,
+    /// the lines are broken at any character
+    Char,
+}
+
+/// The horizontal alignment of each line of the text
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// the words are spread to fill the width,
+    /// except the last line of each paragraph which is aligned to the left
+    Justify,
+}
+
+/// The vertical alignment of the text inside the label
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum VerticalAlignment {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// A label which displays a text, the text can span multiple lines
+/// with `\n` or by wrapping it to the width of the label.
 #[derive(Default, Debug)]
 pub struct TextLabel {
     layout: Option<Layout>,
-    value: String,
+    value: StyledText,
     is_rounded: bool,
     has_border: bool,
+    wrap: Wrap,
+    alignment: Alignment,
+    vertical_alignment: VerticalAlignment,
+    /// show an ellipsis at the end of the text which does not fit
+    use_ellipsis: bool,
     width: Option<f32>,
     height: Option<f32>,
     id: Option<String>,
+    /// the height in the style was wrapped to the width of the previous layout,
+    /// which is different from the height wrapped to the current layout
+    is_height_stale: bool,
 }
 
 impl TextLabel {
@@ -27,7 +69,7 @@ impl TextLabel {
     {
         TextLabel {
             layout: None,
-            value: value.to_string(),
+            value: StyledText::from(value.to_string()),
             is_rounded: false,
             has_border: false,
             id: None,
@@ -37,11 +79,21 @@ impl TextLabel {
 
     /// set the value of the buffer
     pub fn set_value<S: ToString>(&mut self, value: S) {
-        self.value = value.to_string();
+        self.value = StyledText::from(value.to_string());
+    }
+
+    /// set the value with a text which has colors and attributes
+    pub fn set_styled_value<T: Into<StyledText>>(&mut self, value: T) {
+        self.value = value.into();
     }
 
     /// returns a reference to the text value of this text input widget
     pub fn get_value(&self) -> &str {
+        self.value.as_str()
+    }
+
+    /// returns the value together with its styles
+    pub fn get_styled_value(&self) -> &StyledText {
         &self.value
     }
 
@@ -50,6 +102,131 @@ impl TextLabel {
         self.is_rounded = rounded;
     }
 
+    /// set how the text is wrapped, default is no wrapping
+    pub fn set_wrap(&mut self, wrap: Wrap) {
+        self.wrap = wrap;
+    }
+
+    /// set the horizontal alignment of the text, default is left
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        self.alignment = alignment;
+    }
+
+    /// set the vertical alignment of the text, default is top
+    pub fn set_vertical_alignment(&mut self, alignment: VerticalAlignment) {
+        self.vertical_alignment = alignment;
+    }
+
+    /// set whether to show an ellipsis when the text is truncated
+    /// because it does not fit in the label
+    pub fn set_ellipsis(&mut self, use_ellipsis: bool) {
+        self.use_ellipsis = use_ellipsis;
+    }
+
+    /// break the text into lines which fits in the width,
+    /// each line is paired with whether it ends a paragraph
+    fn lines(&self, width: Option<usize>) -> Vec<(StyledText, bool)> {
+        let mut lines = vec![];
+        // the byte index of the paragraph in the value
+        let mut start = 0;
+        for paragraph in self.value.as_str().split('\n') {
+            let wrapped = match (self.wrap, width) {
+                (Wrap::Word, Some(width)) => {
+                    unicode::wrap_words(paragraph, width)
+                }
+                (Wrap::Char, Some(width)) => {
+                    unicode::wrap_chars(paragraph, width)
+                }
+                #[allow(clippy::single_range_in_vec_init)]
+                _ => vec![0..paragraph.len()],
+            };
+            let last = wrapped.len() - 1;
+            lines.extend(wrapped.into_iter().enumerate().map(|(i, range)| {
+                let line =
+                    self.value.slice(start + range.start..start + range.end);
+                (line, i == last)
+            }));
+            start += paragraph.len() + 1;
+        }
+        lines
+    }
+
+    /// the width used in wrapping the text, this is the set width or else
+    /// the width from the last computed layout. When the layout width
+    /// wraps the text into a different height, the layout is computed again
+    fn wrap_width(&self) -> Option<usize> {
+        let width = self
+            .width
+            .or_else(|| self.layout.map(|layout| layout.size.width.round()))?;
+        let inner_width = width - self.border_left() - self.border_right();
+        Some(inner_width.max(0.0) as usize)
+    }
+
+    /// the height needed to display all the lines of the text
+    fn content_height(&self) -> f32 {
+        self.lines(self.wrap_width()).len() as f32
+            + self.border_top()
+            + self.border_bottom()
+    }
+
+    /// fit the line to the width and apply the horizontal alignment,
+    /// return the offset of the line from the left
+    fn align_line(
+        &self,
+        line: &StyledText,
+        ends_paragraph: bool,
+        width: usize,
+    ) -> (usize, StyledText) {
+        let line = if self.use_ellipsis {
+            line.truncate(width)
+        } else {
+            line.take_width(width)
+        };
+        let space = width.saturating_sub(line.width());
+        match self.alignment {
+            Alignment::Left => (0, line),
+            Alignment::Center => (space / 2, line),
+            Alignment::Right => (space, line),
+            Alignment::Justify if ends_paragraph => (0, line),
+            Alignment::Justify => (0, line.justify(width)),
+        }
+    }
+
+    /// the lines which fits in the label and their location
+    /// relative to the inner area of the label
+    fn visible_lines(
+        &self,
+        inner_width: usize,
+        inner_height: usize,
+    ) -> Vec<(usize, usize, StyledText)> {
+        let mut lines = self.lines(Some(inner_width));
+        if lines.len() > inner_height {
+            lines.truncate(inner_height);
+            if self.use_ellipsis {
+                // mark the last visible line that there are more lines
+                if let Some((line, ends_paragraph)) = lines.last_mut() {
+                    *line = line.with_ellipsis(inner_width);
+                    *ends_paragraph = true;
+                }
+            }
+        }
+        let space = inner_height - lines.len();
+        let top = match self.vertical_alignment {
+            VerticalAlignment::Top => 0,
+            VerticalAlignment::Middle => space / 2,
+            VerticalAlignment::Bottom => space,
+        };
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, (line, ends_paragraph))| {
+                let (x, line) =
+                    self.align_line(&line, ends_paragraph, inner_width);
+                (x, top + i, line)
+            })
+            .collect()
+    }
+
     fn border_top(&self) -> f32 {
         if self.has_border {
             1.0
@@ -82,7 +259,6 @@ impl TextLabel {
         }
     }
 
-    #[allow(dead_code)]
     fn inner_height(&self, layout: &Layout) -> usize {
         let ih = layout.size.height.round()
             - self.border_top()
@@ -111,7 +287,14 @@ impl<MSG> Widget<MSG> for TextLabel {
         self.layout.as_ref()
     }
     fn set_layout(&mut self, layout: Layout) {
+        let styled_height = self.content_height();
         self.layout = Some(layout);
+        self.is_height_stale =
+            self.height.is_none() && self.content_height() != styled_height;
+    }
+
+    fn needs_relayout(&self) -> bool {
+        self.is_height_stale
     }
     fn style(&self) -> Style {
         Style {
@@ -125,7 +308,7 @@ impl<MSG> Widget<MSG> for TextLabel {
                 height: if let Some(height) = self.height {
                     Dimension::Points(height)
                 } else {
-                    Dimension::Points(3.0)
+                    Dimension::Points(self.content_height())
                 },
             },
             min_size: Size {
@@ -137,7 +320,7 @@ impl<MSG> Widget<MSG> for TextLabel {
                 height: if let Some(height) = self.height {
                     Dimension::Points(height)
                 } else {
-                    Dimension::Points(3.0)
+                    Dimension::Points(self.content_height())
                 },
             },
             ..Default::default()
@@ -178,13 +361,12 @@ impl<MSG> Widget<MSG> for TextLabel {
             buf.write_canvas(canvas);
         }
 
-        let _inner_width = self.inner_width(&layout);
-        for (t, ch) in self.get_value().chars().enumerate() {
-            buf.set_symbol(
-                (left + self.border_left() + t as f32) as usize,
-                (top + self.border_top()) as usize,
-                ch,
-            );
+        let inner_left = (left + self.border_left()) as usize;
+        let inner_top = (top + self.border_top()) as usize;
+        let inner_width = self.inner_width(&layout);
+        let inner_height = self.inner_height(&layout);
+        for (x, y, line) in self.visible_lines(inner_width, inner_height) {
+            buf.write_styled(inner_left + x, inner_top + y, &line);
         }
 
         vec![]
@@ -207,3 +389,90 @@ impl<MSG> Widget<MSG> for TextLabel {
         &self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use expanse::number::Number;
+
+    fn rows(label: &TextLabel, width: usize, height: usize) -> Vec<String> {
+        let mut rows = vec![" ".repeat(width); height];
+        for (x, y, line) in label.visible_lines(width, height) {
+            rows[y] = format!("{}{}", " ".repeat(x), line.as_str());
+            let padding = width - unicode::str_width(&rows[y]);
+            rows[y].push_str(&" ".repeat(padding));
+        }
+        rows
+    }
+
+    #[test]
+    fn word_wrap_with_alignment() {
+        let mut label = TextLabel::new("the quick brown fox\njumps");
+        label.set_wrap(Wrap::Word);
+        assert_eq!(
+            rows(&label, 11, 3),
+            vec!["the quick  ", "brown fox  ", "jumps      "]
+        );
+        label.set_alignment(Alignment::Right);
+        assert_eq!(
+            rows(&label, 11, 3),
+            vec!["  the quick", "  brown fox", "      jumps"]
+        );
+        label.set_alignment(Alignment::Justify);
+        label.set_vertical_alignment(VerticalAlignment::Bottom);
+        assert_eq!(
+            rows(&label, 11, 4),
+            vec!["           ", "the   quick", "brown fox  ", "jumps      "]
+        );
+    }
+
+    #[test]
+    fn truncate_with_ellipsis() {
+        let mut label = TextLabel::new("hello world\nsecond\nthird");
+        label.set_ellipsis(true);
+        label.set_alignment(Alignment::Center);
+        assert_eq!(rows(&label, 7, 2), vec!["hello… ", "second…"]);
+    }
+
+    #[test]
+    fn style_has_the_wrapped_height() {
+        let mut label = TextLabel::new("文字 文字 文字");
+        label.set_wrap(Wrap::Word);
+        Widget::<()>::set_size(&mut label, Some(9.0), None);
+        assert_eq!(
+            Widget::<()>::style(&label).size.height,
+            Dimension::Points(2.0)
+        );
+    }
+
+    #[test]
+    fn wrapped_height_follows_the_layout_width() {
+        let mut label = TextLabel::new("the quick brown fox");
+        label.set_wrap(Wrap::Word);
+        Widget::<()>::compute_node_layout(
+            &mut label,
+            Size {
+                width: Number::Defined(10.0),
+                height: Number::Undefined,
+            },
+        );
+        // the first pass has no layout width, so it is computed again
+        let layout = Widget::<()>::layout(&label).expect("must have a layout");
+        assert_eq!(layout.size.height, 2.0);
+        assert!(!Widget::<()>::needs_relayout(&label));
+    }
+
+    #[test]
+    fn wrapped_lines_keep_the_styles() {
+        let mut label = TextLabel::new("");
+        label.set_styled_value(StyledText::from_markup("one [b]two three[/b]"));
+        label.set_wrap(Wrap::Word);
+        let lines = label.visible_lines(7, 2);
+        assert_eq!(lines[0].2.as_str(), "one two");
+        assert_eq!(lines[1].2.as_str(), "three");
+        assert_eq!(
+            lines[1].2.spans(),
+            StyledText::from_markup("[b]three").spans()
+        );
+    }
+}
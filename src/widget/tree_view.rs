@@ -0,0 +1,810 @@
+use crate::crossterm::{
+    event::{KeyCode, KeyEvent},
+    style::Attribute,
+};
+use crate::{
+    buffer::{Buffer, Cell},
+    event::InputEvent,
+    symbol,
+    symbol::guide,
+    unicode, Callback, Cmd, Event, Value, Widget,
+};
+use expanse::{
+    geometry::{Rect, Size},
+    result::Layout,
+    style::{Dimension, PositionType, Style},
+};
+use ito_canvas::unicode_canvas::{Border, Canvas};
+use std::fmt;
+
+/// A node in the tree view, which can contain children nodes
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TreeNode {
+    label: String,
+    value: String,
+    children: Vec<TreeNode>,
+    is_expanded: bool,
+    /// the children of this node is not loaded yet, they will be requested
+    /// from the loader of the tree view when this node is expanded
+    is_lazy: bool,
+}
+
+/// a visible row in the tree view
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    /// the index path from the root nodes to this node
+    path: Vec<usize>,
+    /// whether to draw a vertical guide line for each of the ancestor level
+    guides: Vec<bool>,
+    /// whether this is the last node among its siblings
+    is_last: bool,
+}
+
+/// A tree view widget which displays hierarchical data such as
+/// file trees, json documents or config sections
+///```ignore
+///   ▾ src
+///   ├─▸ widget
+///   ├── lib.rs
+///   └─▾ text_buffer
+///     ├── area_buffer.rs
+///     └── input_buffer.rs
+/// ```
+pub struct TreeView<MSG> {
+    layout: Option<Layout>,
+    nodes: Vec<TreeNode>,
+    /// the path of the selected node
+    selected: Option<Vec<usize>>,
+    scroll_top: usize,
+    width: Option<f32>,
+    height: Option<f32>,
+    id: Option<String>,
+    focused: bool,
+    has_border: bool,
+    /// loads the children of a lazy node, this is supplied with the value of the node
+    loader: Option<Callback<String, Vec<TreeNode>>>,
+    on_expand: Vec<Callback<Event, MSG>>,
+    on_select: Vec<Callback<Event, MSG>>,
+}
+
+impl TreeNode {
+    /// create a new tree node with label, the value is the same as the label
+    pub fn new<S: ToString>(label: S) -> Self {
+        let label = label.to_string();
+        TreeNode {
+            value: label.clone(),
+            label,
+            ..Default::default()
+        }
+    }
+
+    /// create a new tree node with label and a value which will be
+    /// passed to the callbacks of the tree view
+    pub fn with_value<S: ToString, V: ToString>(label: S, value: V) -> Self {
+        TreeNode {
+            label: label.to_string(),
+            value: value.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// create a node which children will be loaded only when it is expanded
+    pub fn lazy<S: ToString>(label: S) -> Self {
+        TreeNode {
+            is_lazy: true,
+            ..TreeNode::new(label)
+        }
+    }
+
+    /// add a child to this node
+    pub fn add_child(&mut self, child: TreeNode) {
+        self.children.push(child);
+    }
+
+    /// replace the children of this node, this node will no longer be lazy
+    pub fn set_children(&mut self, children: Vec<TreeNode>) {
+        self.children = children;
+        self.is_lazy = false;
+    }
+
+    /// set whether the children of this node are shown
+    pub fn set_expanded(&mut self, expanded: bool) {
+        self.is_expanded = expanded;
+    }
+
+    /// return the label of this node
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// return the value of this node
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// return the children of this node
+    pub fn children(&self) -> &[TreeNode] {
+        &self.children
+    }
+
+    /// whether the children of this node are shown
+    pub fn is_expanded(&self) -> bool {
+        self.is_expanded
+    }
+
+    /// whether this node can be expanded
+    pub fn is_expandable(&self) -> bool {
+        self.is_lazy || !self.children.is_empty()
+    }
+
+    fn visible_rows(
+        &self,
+        path: Vec<usize>,
+        guides: Vec<bool>,
+        is_last: bool,
+        rows: &mut Vec<Row>,
+    ) {
+        rows.push(Row {
+            path: path.clone(),
+            guides: guides.clone(),
+            is_last,
+        });
+        if self.is_expanded {
+            let mut child_guides = guides;
+            // the root nodes don't have a connector, so there is no guide for them
+            if path.len() > 1 {
+                child_guides.push(!is_last);
+            }
+            let children_len = self.children.len();
+            for (i, child) in self.children.iter().enumerate() {
+                let mut child_path = path.clone();
+                child_path.push(i);
+                child.visible_rows(
+                    child_path,
+                    child_guides.clone(),
+                    i + 1 == children_len,
+                    rows,
+                );
+            }
+        }
+    }
+}
+
+impl<MSG> TreeView<MSG> {
+    /// create a new tree view with the root nodes
+    pub fn new(nodes: Vec<TreeNode>) -> Self {
+        TreeView {
+            layout: None,
+            nodes,
+            selected: None,
+            scroll_top: 0,
+            width: None,
+            height: None,
+            id: None,
+            focused: false,
+            has_border: true,
+            loader: None,
+            on_expand: vec![],
+            on_select: vec![],
+        }
+    }
+
+    /// set the root nodes of this tree view
+    pub fn set_nodes(&mut self, nodes: Vec<TreeNode>) {
+        self.nodes = nodes;
+        self.selected = None;
+        self.scroll_top = 0;
+    }
+
+    /// return the root nodes of this tree view
+    pub fn nodes(&self) -> &[TreeNode] {
+        &self.nodes
+    }
+
+    /// return a reference to the node at this index path
+    pub fn node(&self, path: &[usize]) -> Option<&TreeNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.nodes.get(*first)?, |node, i| node.children.get(*i))
+    }
+
+    /// return a mutable reference to the node at this index path
+    pub fn node_mut(&mut self, path: &[usize]) -> Option<&mut TreeNode> {
+        let (first, rest) = path.split_first()?;
+        rest.iter()
+            .try_fold(self.nodes.get_mut(*first)?, |node, i| {
+                node.children.get_mut(*i)
+            })
+    }
+
+    /// return the selected node
+    pub fn selected_node(&self) -> Option<&TreeNode> {
+        self.selected.as_ref().and_then(|path| self.node(path))
+    }
+
+    /// set whether to draw a border around the tree view
+    pub fn set_border(&mut self, has_border: bool) {
+        self.has_border = has_border;
+    }
+
+    /// set the function which loads the children of lazy nodes,
+    /// the function is called with the value of the node being expanded
+    pub fn set_loader<F>(&mut self, f: F)
+    where
+        F: FnMut(String) -> Vec<TreeNode> + 'static,
+    {
+        self.loader = Some(f.into());
+    }
+
+    /// attach a listener which is triggered when a node is expanded,
+    /// the event contains the value of the node
+    pub fn on_expand<F>(&mut self, f: F)
+    where
+        F: FnMut(Event) -> MSG + 'static,
+    {
+        self.on_expand.push(f.into());
+    }
+
+    /// attach a listener which is triggered when a node is selected,
+    /// the event contains the value of the node
+    pub fn on_select<F>(&mut self, f: F)
+    where
+        F: FnMut(Event) -> MSG + 'static,
+    {
+        self.on_select.push(f.into());
+    }
+
+    fn visible_rows(&self) -> Vec<Row> {
+        let mut rows = vec![];
+        let nodes_len = self.nodes.len();
+        for (i, node) in self.nodes.iter().enumerate() {
+            node.visible_rows(vec![i], vec![], i + 1 == nodes_len, &mut rows);
+        }
+        rows
+    }
+
+    fn selected_row_index(&self, rows: &[Row]) -> Option<usize> {
+        let selected = self.selected.as_ref()?;
+        rows.iter().position(|row| &row.path == selected)
+    }
+
+    fn border_width(&self) -> usize {
+        if self.has_border {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn inner_height(&self) -> usize {
+        if let Some(layout) = self.layout {
+            (layout.size.height.round() as usize)
+                .saturating_sub(2 * self.border_width())
+        } else {
+            0
+        }
+    }
+
+    /// adjust the scroll such that the selected row is visible
+    fn scroll_to_selected(&mut self) {
+        let rows = self.visible_rows();
+        let inner_height = self.inner_height();
+        if let Some(index) = self.selected_row_index(&rows) {
+            if index < self.scroll_top {
+                self.scroll_top = index;
+            } else if inner_height > 0
+                && index >= self.scroll_top + inner_height
+            {
+                self.scroll_top = index + 1 - inner_height;
+            }
+        }
+    }
+
+    fn select_path(&mut self, path: Vec<usize>) -> Vec<MSG> {
+        let value = self.node(&path).map(|node| node.value.clone());
+        self.selected = Some(path);
+        self.scroll_to_selected();
+        match value {
+            Some(value) => Self::emit(&mut self.on_select, value),
+            None => vec![],
+        }
+    }
+
+    fn select_row(&mut self, index: usize) -> Vec<MSG> {
+        let rows = self.visible_rows();
+        if let Some(row) = rows.get(index) {
+            self.select_path(row.path.clone())
+        } else {
+            vec![]
+        }
+    }
+
+    /// expand the node at this path, loading its children if it is lazy
+    pub fn expand(&mut self, path: &[usize]) -> Vec<MSG> {
+        let mut loader = self.loader.take();
+        let value = if let Some(node) = self.node_mut(path) {
+            if node.is_expandable() && !node.is_expanded {
+                if node.is_lazy {
+                    if let Some(loader) = loader.as_mut() {
+                        let children = loader.emit(node.value.clone());
+                        node.set_children(children);
+                    }
+                }
+                node.is_expanded = true;
+                Some(node.value.clone())
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+        self.loader = loader;
+        match value {
+            Some(value) => Self::emit(&mut self.on_expand, value),
+            None => vec![],
+        }
+    }
+
+    /// collapse the node at this path
+    pub fn collapse(&mut self, path: &[usize]) {
+        if let Some(node) = self.node_mut(path) {
+            node.is_expanded = false;
+        }
+        // the selection can not be inside a collapsed node
+        if let Some(selected) = &self.selected {
+            if selected.len() > path.len() && selected.starts_with(path) {
+                self.selected = Some(path.to_vec());
+            }
+        }
+    }
+
+    /// toggle the expanded state of the node at this path
+    pub fn toggle(&mut self, path: &[usize]) -> Vec<MSG> {
+        match self.node(path).map(|node| node.is_expanded) {
+            Some(true) => {
+                self.collapse(path);
+                vec![]
+            }
+            Some(false) => self.expand(path),
+            None => vec![],
+        }
+    }
+
+    fn emit(callbacks: &mut [Callback<Event, MSG>], value: String) -> Vec<MSG> {
+        let event = Event::from(InputEvent::from(Value::from(value)));
+        callbacks
+            .iter_mut()
+            .map(|cb| cb.emit(event.clone()))
+            .collect()
+    }
+
+    fn process_key(&mut self, key_event: KeyEvent) -> Vec<MSG> {
+        // the nodes can be changed with `node_mut`,
+        // which may have removed the selected node
+        if self.selected_node().is_none() {
+            self.selected = None;
+        }
+        let rows = self.visible_rows();
+        if rows.is_empty() {
+            return vec![];
+        }
+        let selected_index = self.selected_row_index(&rows);
+        let selected_path = self.selected.clone();
+        let page = self.inner_height().max(1);
+        let last = rows.len() - 1;
+        match key_event.code {
+            KeyCode::Up => match selected_index {
+                Some(index) => self.select_row(index.saturating_sub(1)),
+                None => self.select_row(0),
+            },
+            KeyCode::Down => match selected_index {
+                Some(index) => self.select_row((index + 1).min(last)),
+                None => self.select_row(0),
+            },
+            KeyCode::PageUp => self
+                .select_row(selected_index.unwrap_or(0).saturating_sub(page)),
+            KeyCode::PageDown => {
+                self.select_row((selected_index.unwrap_or(0) + page).min(last))
+            }
+            KeyCode::Home => self.select_row(0),
+            KeyCode::End => self.select_row(last),
+            KeyCode::Right => {
+                if let Some(path) = selected_path {
+                    let node = match self.node(&path) {
+                        Some(node) => node,
+                        None => return vec![],
+                    };
+                    if !node.is_expanded {
+                        self.expand(&path)
+                    } else if !node.children.is_empty() {
+                        let mut child_path = path;
+                        child_path.push(0);
+                        self.select_path(child_path)
+                    } else {
+                        vec![]
+                    }
+                } else {
+                    vec![]
+                }
+            }
+            KeyCode::Left => {
+                if let Some(mut path) = selected_path {
+                    let node = match self.node(&path) {
+                        Some(node) => node,
+                        None => return vec![],
+                    };
+                    if node.is_expanded {
+                        self.collapse(&path);
+                        vec![]
+                    } else if path.len() > 1 {
+                        path.pop();
+                        self.select_path(path)
+                    } else {
+                        vec![]
+                    }
+                } else {
+                    vec![]
+                }
+            }
+            KeyCode::Enter | KeyCode::Char(' ') => {
+                if let Some(path) = selected_path {
+                    let msgs = self.toggle(&path);
+                    self.scroll_to_selected();
+                    msgs
+                } else {
+                    vec![]
+                }
+            }
+            _ => vec![],
+        }
+    }
+
+    /// the index of the visible row and the column relative to the inner left,
+    /// at this location of the terminal, which must be inside the border
+    fn row_at(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let layout = self.layout?;
+        let inner_left =
+            layout.location.x.round() as usize + self.border_width();
+        let inner_top =
+            layout.location.y.round() as usize + self.border_width();
+        let inner_width = (layout.size.width.round() as usize)
+            .saturating_sub(2 * self.border_width());
+        if x < inner_left
+            || y < inner_top
+            || x >= inner_left + inner_width
+            || y >= inner_top + self.inner_height()
+        {
+            return None;
+        }
+        Some((y - inner_top + self.scroll_top, x - inner_left))
+    }
+
+    fn process_mouse(&mut self, event: &Event) -> Vec<MSG> {
+        let rows = self.visible_rows();
+        let inner_height = self.inner_height();
+        if event.is_mouse_click() {
+            let (x, y) = event.extract_location().expect("must have location");
+            let (index, column) = match self.row_at(x as usize, y as usize) {
+                Some(location) => location,
+                None => return vec![],
+            };
+            if let Some(row) = rows.get(index) {
+                let is_expandable = self
+                    .node(&row.path)
+                    .is_some_and(|node| node.is_expandable());
+                if column == Self::glyph_column(row) && is_expandable {
+                    self.toggle(&row.path)
+                } else {
+                    self.select_path(row.path.clone())
+                }
+            } else {
+                vec![]
+            }
+        } else if event.is_scrollup() {
+            self.scroll_top = self.scroll_top.saturating_sub(1);
+            vec![]
+        } else if event.is_scrolldown() {
+            if self.scroll_top + inner_height < rows.len() {
+                self.scroll_top += 1;
+            }
+            vec![]
+        } else {
+            vec![]
+        }
+    }
+
+    /// the column, relative to the inner left where the expand/collapse glyph is drawn
+    fn glyph_column(row: &Row) -> usize {
+        2 * (row.path.len() - 1)
+    }
+
+    /// the symbols in front of the label of this row
+    fn row_prefix(&self, row: &Row) -> Vec<char> {
+        let node = match self.node(&row.path) {
+            Some(node) => node,
+            None => return vec![],
+        };
+        let mut prefix = vec![];
+        for has_guide in row.guides.iter() {
+            if *has_guide {
+                prefix.push(guide::VERTICAL);
+            } else {
+                prefix.push(symbol::EMPTY);
+            }
+            prefix.push(symbol::EMPTY);
+        }
+        let is_child = row.path.len() > 1;
+        if is_child {
+            if row.is_last {
+                prefix.push(guide::LAST_BRANCH);
+            } else {
+                prefix.push(guide::BRANCH);
+            }
+            prefix.push(guide::HORIZONTAL);
+        }
+        if node.is_expandable() {
+            if node.is_expanded {
+                prefix.push(symbol::TREE_EXPANDED);
+            } else {
+                prefix.push(symbol::TREE_COLLAPSED);
+            }
+        } else if is_child {
+            prefix.push(guide::HORIZONTAL);
+        } else {
+            prefix.push(symbol::EMPTY);
+        }
+        prefix.push(symbol::EMPTY);
+        prefix
+    }
+
+    fn draw_border(&self, buf: &mut Buffer) {
+        let layout = self.layout.expect("must have a layout");
+        let left = layout.location.x.round() as usize;
+        let top = layout.location.y.round() as usize;
+        let width = layout.size.width.round() as usize;
+        let height = layout.size.height.round() as usize;
+        if width < 2 || height < 2 {
+            return;
+        }
+        let border = Border {
+            use_thick_border: self.focused,
+            has_top: true,
+            has_bottom: true,
+            has_left: true,
+            has_right: true,
+            is_top_left_rounded: false,
+            is_top_right_rounded: false,
+            is_bottom_left_rounded: false,
+            is_bottom_right_rounded: false,
+        };
+        let mut canvas = Canvas::new();
+        canvas.draw_rect(
+            (left, top),
+            (left + width - 1, top + height - 1),
+            border,
+        );
+        buf.write_canvas(canvas);
+    }
+
+    fn draw_rows(&self, buf: &mut Buffer) {
+        let layout = self.layout.expect("must have a layout");
+        let inner_left =
+            layout.location.x.round() as usize + self.border_width();
+        let inner_top =
+            layout.location.y.round() as usize + self.border_width();
+        let inner_width = (layout.size.width.round() as usize)
+            .saturating_sub(2 * self.border_width());
+        let inner_right = inner_left + inner_width;
+        let rows = self.visible_rows();
+
+        for (j, row) in rows
+            .iter()
+            .skip(self.scroll_top)
+            .take(self.inner_height())
+            .enumerate()
+        {
+            let y = inner_top + j;
+            let node = self.node(&row.path).expect("must have a node");
+            let is_selected = self.selected.as_ref() == Some(&row.path);
+            let mut x = inner_left;
+            for ch in self.row_prefix(row) {
+                if x >= inner_right {
+                    break;
+                }
+                buf.set_symbol(x, y, ch);
+                x += 1;
+            }
+            for (grapheme, ch_width) in unicode::graphemes(&node.label) {
+                if ch_width == 0 {
+                    continue;
+                }
+                if x + ch_width > inner_right {
+                    break;
+                }
+                let mut cell = Cell::new(grapheme);
+                if is_selected {
+                    cell.attributes(vec![Attribute::Reverse]);
+                }
+                buf.set_cell(x, y, cell);
+                x += ch_width;
+            }
+        }
+    }
+}
+
+impl<MSG> Widget<MSG> for TreeView<MSG>
+where
+    MSG: fmt::Debug + 'static,
+{
+    fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    fn set_layout(&mut self, layout: Layout) {
+        self.layout = Some(layout);
+    }
+
+    fn style(&self) -> Style {
+        Style {
+            position_type: PositionType::Relative,
+            size: Size {
+                width: if let Some(width) = self.width {
+                    Dimension::Points(width)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+                height: if let Some(height) = self.height {
+                    Dimension::Points(height)
+                } else {
+                    Dimension::Percent(1.0)
+                },
+            },
+            border: Rect {
+                top: Dimension::Points(self.border_width() as f32),
+                bottom: Dimension::Points(self.border_width() as f32),
+                start: Dimension::Points(self.border_width() as f32),
+                end: Dimension::Points(self.border_width() as f32),
+            },
+            ..Default::default()
+        }
+    }
+
+    fn draw(&self, buf: &mut Buffer) -> Vec<Cmd> {
+        if self.has_border {
+            self.draw_border(buf);
+        }
+        self.draw_rows(buf);
+        vec![]
+    }
+
+    fn set_focused(&mut self, focused: bool) {
+        self.focused = focused;
+    }
+
+    fn set_size(&mut self, width: Option<f32>, height: Option<f32>) {
+        self.width = width;
+        self.height = height;
+    }
+
+    fn process_event(&mut self, event: Event) -> Vec<MSG> {
+        match event {
+            Event::Key(ke) => self.process_key(ke),
+            Event::Mouse(_) => self.process_mouse(&event),
+            _ => vec![],
+        }
+    }
+
+    fn set_id(&mut self, id: &str) {
+        self.id = Some(id.to_string());
+    }
+
+    fn get_id(&self) -> &Option<String> {
+        &self.id
+    }
+}
+
+impl<MSG> fmt::Debug for TreeView<MSG> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("TreeView")
+            .field("nodes", &self.nodes)
+            .field("selected", &self.selected)
+            .field("id", &self.id)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_tree() -> Vec<TreeNode> {
+        let mut src = TreeNode::new("src");
+        src.add_child(TreeNode::lazy("widget"));
+        src.add_child(TreeNode::new("lib.rs"));
+        src.set_expanded(true);
+        vec![src, TreeNode::new("Cargo.toml")]
+    }
+
+    #[test]
+    fn visible_rows_of_expanded_nodes() {
+        let tree = TreeView::<()>::new(sample_tree());
+        let rows = tree.visible_rows();
+        let paths: Vec<Vec<usize>> =
+            rows.iter().map(|row| row.path.clone()).collect();
+        assert_eq!(paths, vec![vec![0], vec![0, 0], vec![0, 1], vec![1]]);
+
+        let prefix: String = tree.row_prefix(&rows[1]).into_iter().collect();
+        assert_eq!(prefix, "├─▸ ");
+        let prefix: String = tree.row_prefix(&rows[2]).into_iter().collect();
+        assert_eq!(prefix, "└── ");
+    }
+
+    #[test]
+    fn lazy_loaded_on_expand() {
+        let mut tree = TreeView::<String>::new(sample_tree());
+        tree.set_loader(|value| {
+            vec![
+                TreeNode::new(format!("{}/button.rs", value)),
+                TreeNode::new(format!("{}/slider.rs", value)),
+            ]
+        });
+        tree.on_expand(|event| match event {
+            Event::InputEvent(ie) => ie.value.to_string(),
+            _ => unreachable!(),
+        });
+        // select `widget` and expand it
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Down)));
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Down)));
+        assert_eq!(tree.selected_node().map(TreeNode::label), Some("widget"));
+        let msgs =
+            tree.process_event(Event::from(KeyEvent::from(KeyCode::Right)));
+        assert_eq!(msgs, vec!["widget".to_string()]);
+        assert_eq!(tree.visible_rows().len(), 6);
+
+        // move into the first child, then back to the parent and collapse it
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Right)));
+        assert_eq!(
+            tree.selected_node().map(TreeNode::label),
+            Some("widget/button.rs")
+        );
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Left)));
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Left)));
+        assert_eq!(tree.visible_rows().len(), 4);
+    }
+
+    #[test]
+    fn selection_removed_with_node_mut() {
+        let mut tree = TreeView::<()>::new(sample_tree());
+        for _ in 0..3 {
+            tree.process_event(Event::from(KeyEvent::from(KeyCode::Down)));
+        }
+        assert_eq!(tree.selected_node().map(TreeNode::label), Some("lib.rs"));
+        // the selected `lib.rs` is removed
+        tree.node_mut(&[0])
+            .expect("must have a node")
+            .set_children(vec![TreeNode::new("main.rs")]);
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Right)));
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Left)));
+        assert_eq!(tree.selected_node(), None);
+        tree.process_event(Event::from(KeyEvent::from(KeyCode::Down)));
+        assert_eq!(tree.selected_node().map(TreeNode::label), Some("src"));
+    }
+
+    #[test]
+    fn clicks_on_the_border_are_ignored() {
+        use expanse::{geometry::Size, number::Number};
+
+        let mut tree = TreeView::<()>::new(sample_tree());
+        tree.set_size(Some(20.0), Some(4.0));
+        tree.compute_node_layout(Size {
+            width: Number::Defined(20.0),
+            height: Number::Defined(4.0),
+        });
+        // 2 of the 4 rows are visible inside the border
+        assert_eq!(tree.row_at(1, 1), Some((0, 0)));
+        assert_eq!(tree.row_at(3, 2), Some((1, 2)));
+        assert_eq!(tree.row_at(1, 3), None);
+        assert_eq!(tree.row_at(19, 1), None);
+        assert_eq!(tree.row_at(0, 1), None);
+    }
+}